@@ -0,0 +1,258 @@
+use std::{env, fmt::Write, fs, path::Path};
+
+use serde::Deserialize;
+
+// wide tables generated from a column specification, see
+// src/modules/conditions_tiberius_columns/table.json
+const TABLE_SPECS: [(&str, &str); 1] = [(
+    "src/modules/conditions_tiberius_columns/table.json",
+    "conditions_tiberius_columns_schema.rs",
+)];
+
+#[derive(Deserialize)]
+struct TableSpec {
+    name: String,
+    columns: Vec<ColumnSpec>,
+}
+
+#[derive(Deserialize)]
+struct ColumnSpec {
+    prefix: String,
+    count: usize,
+    #[serde(rename = "type")]
+    column_type: ColumnType,
+    nullable: bool,
+    min: i32,
+    max: i32,
+    #[serde(default)]
+    scale: u32,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum ColumnType {
+    String,
+    Float,
+    Decimal,
+}
+
+struct Column<'a> {
+    name: String,
+    spec: &'a ColumnSpec,
+}
+
+impl Column<'_> {
+    fn inner_type(&self) -> &'static str {
+        match self.spec.column_type {
+            ColumnType::String => "String",
+            ColumnType::Float => "f64",
+            ColumnType::Decimal => "::tiberius::numeric::Decimal",
+        }
+    }
+
+    fn rust_type(&self) -> String {
+        if self.spec.nullable {
+            return format!("Option<{}>", self.inner_type());
+        }
+        self.inner_type().to_string()
+    }
+
+    fn generator(&self) -> String {
+        let spec = self.spec;
+        let value = match spec.column_type {
+            ColumnType::String => format!(
+                "crate::util::generator::generate_word(crate::util::generator::generate_numbers_usize({}, {}))",
+                spec.min, spec.max
+            ),
+            ColumnType::Float => format!(
+                "crate::util::generator::generate_numbers_f64({:.1}, {:.1})",
+                spec.min as f64, spec.max as f64
+            ),
+            ColumnType::Decimal => format!(
+                "crate::util::generator::generate_numbers_decimal({}, {}, {})",
+                spec.min, spec.max, spec.scale
+            ),
+        };
+        if spec.nullable {
+            return format!("Some({})", value);
+        }
+        value
+    }
+
+    fn row_getter(&self) -> String {
+        let name = &self.name;
+        match (self.spec.column_type, self.spec.nullable) {
+            (ColumnType::String, false) => {
+                format!("row.get::<&str, _>(\"{name}\").unwrap_or_default().to_owned()")
+            }
+            (ColumnType::String, true) => format!("row.get::<&str, _>(\"{name}\").map(str::to_owned)"),
+            (_, false) => format!("row.get::<{}, _>(\"{name}\").unwrap_or_default()", self.inner_type()),
+            (_, true) => format!("row.get::<{}, _>(\"{name}\")", self.inner_type()),
+        }
+    }
+
+    fn row_value(&self) -> String {
+        let name = &self.name;
+        match (self.spec.column_type, self.spec.nullable) {
+            (ColumnType::String, false) => format!("Some(data.{name}.clone()).into_sql()"),
+            (ColumnType::String, true) => format!("data.{name}.clone().into_sql()"),
+            (ColumnType::Float, false) => format!("Some(data.{name}).into_sql()"),
+            (ColumnType::Float, true) => format!("data.{name}.into_sql()"),
+            (ColumnType::Decimal, _) => format!("data.{name}.to_sql()"),
+        }
+    }
+}
+
+fn main() {
+    let out_dir = env::var("OUT_DIR").unwrap();
+
+    // migrations are embedded with embed_migrations!, rebuild when one is added or edited
+    println!("cargo:rerun-if-changed=migrations");
+
+    for (spec_path, out_file) in TABLE_SPECS {
+        println!("cargo:rerun-if-changed={}", spec_path);
+
+        let spec: TableSpec = serde_json::from_str(&fs::read_to_string(spec_path).unwrap())
+            .unwrap_or_else(|error| panic!("invalid table spec {}: {}", spec_path, error));
+
+        let code = generate(&spec);
+        fs::write(Path::new(&out_dir).join(out_file), code).unwrap();
+    }
+}
+
+fn generate(spec: &TableSpec) -> String {
+    let name = &spec.name;
+    let columns: Vec<Column> = spec
+        .columns
+        .iter()
+        .flat_map(|column| {
+            (1..=column.count).map(move |index| Column {
+                name: format!("{}_{}", column.prefix, index),
+                spec: column,
+            })
+        })
+        .collect();
+
+    let mut code = String::new();
+    let out = &mut code;
+
+    // column names, the table is created outside the service at the configured TIBERIUS_COLUMNS_* target
+    let _ = writeln!(out, "pub const COLUMNS: [&str; {}] = [", columns.len() + 3);
+    let _ = writeln!(out, "\t\"id\",\n\t\"created_on\",\n\t\"modified_on\",");
+    for column in &columns {
+        let _ = writeln!(out, "\t\"{}\",", column.name);
+    }
+    let _ = writeln!(out, "];\n");
+
+    // entity
+    let _ = writeln!(out, "#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]");
+    let _ = writeln!(out, "pub struct {} {{", name);
+    let _ = writeln!(out, "\tpub id: String,");
+    let _ = writeln!(out, "\t#[serde(with = \"datetime_serializer\")]\n\tpub created_on: NaiveDateTime,");
+    let _ = writeln!(out, "\t#[serde(with = \"datetime_serializer\")]\n\tpub modified_on: NaiveDateTime,");
+    for column in &columns {
+        let _ = writeln!(out, "\tpub {}: {},", column.name, column.rust_type());
+    }
+    let _ = writeln!(out, "}}\n");
+
+    // create / update request
+    let _ = writeln!(out, "#[derive(Debug, Deserialize, Serialize, Validate)]");
+    let _ = writeln!(out, "pub struct {}Request {{", name);
+    let _ = writeln!(out, "\t#[serde(skip_serializing_if = \"Option::is_none\")]\n\tpub id: Option<String>,");
+    for column in &columns {
+        let _ = writeln!(out, "\tpub {}: {},", column.name, column.rust_type());
+    }
+    let _ = writeln!(out, "}}\n");
+
+    // patch request, every column is optional, a nullable column takes null to clear it
+    let _ = writeln!(out, "#[derive(Debug, Deserialize, Serialize, Validate)]");
+    let _ = writeln!(out, "pub struct {}PatchRequest {{", name);
+    for column in &columns {
+        if column.spec.nullable {
+            let _ = writeln!(
+                out,
+                "\t#[serde(default, deserialize_with = \"double_option::deserialize\", skip_serializing_if = \"Option::is_none\")]"
+            );
+        } else {
+            let _ = writeln!(out, "\t#[serde(skip_serializing_if = \"Option::is_none\")]");
+        }
+        let _ = writeln!(out, "\tpub {}: Option<{}>,", column.name, column.rust_type());
+    }
+    let _ = writeln!(out, "}}\n");
+
+    let _ = writeln!(out, "impl {} {{", name);
+
+    let _ = writeln!(out, "\tpub fn from_create_request(request: {}Request) -> Self {{", name);
+    let _ = writeln!(out, "\t\tlet date_now = chrono::Utc::now().naive_utc();");
+    let _ = writeln!(out, "\t\t{} {{", name);
+    let _ = writeln!(out, "\t\t\tid: request.id.unwrap_or(Uuid::new_v4().to_string()),");
+    let _ = writeln!(out, "\t\t\tcreated_on: date_now,\n\t\t\tmodified_on: date_now,");
+    for column in &columns {
+        let _ = writeln!(out, "\t\t\t{0}: request.{0},", column.name);
+    }
+    let _ = writeln!(out, "\t\t}}\n\t}}\n");
+
+    let _ = writeln!(
+        out,
+        "\tpub fn from_update_request(request: {0}Request, mut existing: {0}) -> Self {{",
+        name
+    );
+    let _ = writeln!(out, "\t\texisting.modified_on = chrono::Utc::now().naive_utc();");
+    for column in &columns {
+        let _ = writeln!(out, "\t\texisting.{0} = request.{0};", column.name);
+    }
+    let _ = writeln!(out, "\n\t\texisting\n\t}}\n");
+
+    let _ = writeln!(out, "\tpub fn from_row_tiberius(row: &tiberius::Row) -> Self {{");
+    let _ = writeln!(out, "\t\t{} {{", name);
+    let _ = writeln!(out, "\t\t\tid: row.get::<&str, _>(\"id\").unwrap_or_default().to_owned(),");
+    let _ = writeln!(out, "\t\t\tcreated_on: row.get(\"created_on\").unwrap_or_default(),");
+    let _ = writeln!(out, "\t\t\tmodified_on: row.get(\"modified_on\").unwrap_or_default(),");
+    for column in &columns {
+        let _ = writeln!(out, "\t\t\t{}: {},", column.name, column.row_getter());
+    }
+    let _ = writeln!(out, "\t\t}}\n\t}}\n");
+
+    let _ = writeln!(out, "\tpub fn to_row_tiberius(data: &Self) -> tiberius::TokenRow<'_> {{");
+    let _ = writeln!(out, "\t\tlet mut row = TokenRow::new();");
+    let _ = writeln!(out, "\t\trow.push(Some(data.id.clone()).into_sql());");
+    let _ = writeln!(out, "\t\trow.push(Some(data.created_on).into_sql());");
+    let _ = writeln!(out, "\t\trow.push(Some(data.modified_on).into_sql());");
+    for column in &columns {
+        let _ = writeln!(out, "\t\trow.push({});", column.row_value());
+    }
+    let _ = writeln!(out, "\t\trow\n\t}}\n");
+
+    let _ = writeln!(out, "\tpub fn to_params_tiberius(&self) -> Vec<(&'static str, &dyn ToSql)> {{");
+    let _ = writeln!(out, "\t\tvec![");
+    let _ = writeln!(out, "\t\t\t(\"id\", &self.id),");
+    let _ = writeln!(out, "\t\t\t(\"created_on\", &self.created_on),");
+    let _ = writeln!(out, "\t\t\t(\"modified_on\", &self.modified_on),");
+    for column in &columns {
+        let _ = writeln!(out, "\t\t\t(\"{0}\", &self.{0}),", column.name);
+    }
+    let _ = writeln!(out, "\t\t]\n\t}}\n}}\n");
+
+    let _ = writeln!(out, "impl {}Request {{", name);
+    let _ = writeln!(out, "\tpub fn generate_request() -> Self {{");
+    let _ = writeln!(out, "\t\t{}Request {{", name);
+    let _ = writeln!(out, "\t\t\tid: None,");
+    for column in &columns {
+        let _ = writeln!(out, "\t\t\t{}: {},", column.name, column.generator());
+    }
+    let _ = writeln!(out, "\t\t}}\n\t}}\n}}\n");
+
+    let _ = writeln!(out, "impl {}PatchRequest {{", name);
+    let _ = writeln!(out, "\tpub fn to_params_tiberius(&self) -> Vec<(&'static str, &dyn ToSql)> {{");
+    let _ = writeln!(out, "\t\tlet mut params: Vec<(&'static str, &dyn ToSql)> = Vec::new();");
+    for column in &columns {
+        let _ = writeln!(
+            out,
+            "\t\tif let Some(value) = &self.{0} {{\n\t\t\tparams.push((\"{0}\", value));\n\t\t}}",
+            column.name
+        );
+    }
+    let _ = writeln!(out, "\t\tparams\n\t}}\n}}");
+
+    code
+}
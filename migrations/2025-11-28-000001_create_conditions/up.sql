@@ -0,0 +1,9 @@
+-- range partitioned on created_on, partitions are added by the provisioning endpoints
+CREATE TABLE conditions (
+  id varchar(64) NOT NULL,
+  created_on timestamptz NOT NULL,
+  location TEXT NOT NULL,
+  temperature DOUBLE PRECISION NULL,
+  humidity DOUBLE PRECISION NULL,
+  PRIMARY KEY (id, created_on)
+) PARTITION BY RANGE (created_on);
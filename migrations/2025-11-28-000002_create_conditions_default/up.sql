@@ -0,0 +1,2 @@
+-- catches every row no partition covers
+CREATE TABLE conditions_default PARTITION OF conditions DEFAULT;
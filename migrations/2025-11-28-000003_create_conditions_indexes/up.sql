@@ -0,0 +1,2 @@
+CREATE INDEX idx_conditions
+ON conditions (id, created_on, location, temperature, humidity);
@@ -0,0 +1,6 @@
+-- registry of the tables created through /dynamic_table, the definition is the json of the request
+-- IF NOT EXISTS keeps databases where the service created the registry itself
+CREATE TABLE IF NOT EXISTS dynamic_table_definitions (
+  name TEXT PRIMARY KEY,
+  definition TEXT NOT NULL
+);
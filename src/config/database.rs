@@ -4,7 +4,37 @@ use deadpool_postgres::{Config, ManagerConfig, RecyclingMethod, Runtime};
 use diesel::{PgConnection, r2d2};
 use tokio_postgres::NoTls;
 
-use crate::config::environment::CONFIG;
+use crate::{config::environment::CONFIG, dto::app_error::AppError};
+
+// a fully qualified sql server table, every statement of a module goes through one of these
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableTarget {
+    pub database: String,
+    pub schema: String,
+    pub table: String,
+}
+
+impl TableTarget {
+    pub fn qualified(&self) -> String {
+        format!(
+            "{}.{}.{}",
+            quote_identifier(&self.database),
+            quote_identifier(&self.schema),
+            quote_identifier(&self.table)
+        )
+    }
+
+    pub fn quoted_database(&self) -> String {
+        quote_identifier(&self.database)
+    }
+}
+
+// the database the tiberius pool connects to, dynamic tables are created there
+pub const TIBERIUS_DATABASE: &str = "tiberius";
+
+fn quote_identifier(name: &str) -> String {
+    format!("[{}]", name.replace(']', "]]"))
+}
 
 pub fn get_diesel_postgres_db_pool() -> r2d2::Pool<r2d2::ConnectionManager<PgConnection>> {
     let config_env = &CONFIG;
@@ -43,7 +73,7 @@ pub async fn get_tokio_postgresql() -> Result<tokio_postgres::Client, tokio_post
 
     tokio::spawn(async move {
         if let Err(e) = connection.await {
-            eprintln!("database connection error: {}", e);
+            tracing::error!(error = %e, "database connection error");
         }
     });
     return Ok(client);
@@ -54,7 +84,7 @@ pub fn get_deadpool_tiberius_sql_server_db_pool() -> deadpool_tiberius::Pool {
         .host("localhost")
         .port(1433)
         .basic_authentication("sa", "P@ssw0rd")
-        .database("tiberius")
+        .database(TIBERIUS_DATABASE)
         .trust_cert()
         .max_size(17)
         .wait_timeout(Duration::from_secs(30))  
@@ -64,4 +94,34 @@ pub fn get_deadpool_tiberius_sql_server_db_pool() -> deadpool_tiberius::Pool {
         .create_pool();
 
     pool.unwrap()
-}
\ No newline at end of file
+}
+
+// looks the table up in its database's INFORMATION_SCHEMA so a wrong target fails at startup
+// instead of a benchmark writing to one table and reading another
+pub async fn validate_tiberius_target(
+    pool: &deadpool_tiberius::Pool,
+    target: &TableTarget,
+) -> Result<(), AppError> {
+    let mut client = pool
+        .get()
+        .await
+        .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+
+    let statement = format!(
+        "SELECT COUNT(*) AS count_table FROM {}.INFORMATION_SCHEMA.TABLES WHERE TABLE_SCHEMA = @P1 AND TABLE_NAME = @P2",
+        target.quoted_database()
+    );
+    let row = client
+        .query(statement, &[&target.schema, &target.table])
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?
+        .into_row()
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+
+    let count_table: i32 = row.and_then(|row| row.get("count_table")).unwrap_or(0);
+    if count_table == 0 {
+        return Err(AppError::Other(format!("table {} does not exist", target.qualified())));
+    }
+    Ok(())
+}
@@ -41,6 +41,14 @@ use std::fs;
 
 use serde::Deserialize;
 
+use crate::{
+    config::{database::TableTarget, logging::LogFormat},
+    modules::{
+        conditions_kafka::{bus::MessageBusKind, schema::Sink},
+        provisioning::schema::{PartitionInterval, RetentionMode},
+    },
+};
+
 #[derive(Clone, Deserialize, Debug)]
 pub struct Environment {
 
@@ -51,11 +59,57 @@ pub struct Environment {
     pub database_dbname: String,
     pub database_max_pool: u32,
     pub database_min_pool: u32,
+    pub database_run_migrations: bool,
 
     pub server_host: String,
     pub server_port: u16,
     pub server_thread: usize,
 
+    pub kafka_bootstrap_servers: String,
+    pub kafka_topic: String,
+    pub kafka_dead_letter_topic: String,
+    pub kafka_group_id: String,
+    pub kafka_auto_offset_reset: String,
+    pub kafka_message_timeout_ms: u64,
+    pub kafka_message_max_bytes: u64,
+    pub kafka_fetch_message_max_bytes: u64,
+    pub kafka_fetch_max_bytes: u64,
+    pub kafka_receive_message_max_bytes: u64,
+    pub kafka_batch_size: usize,
+    pub kafka_ingest_group_id: String,
+    pub kafka_ingest_workers: usize,
+    pub kafka_ingest_autostart: bool,
+    pub kafka_ingest_sink: Sink,
+
+    pub message_bus: MessageBusKind,
+    pub message_bus_memory_partitions: usize,
+
+    pub tiberius_conditions_database: String,
+    pub tiberius_conditions_schema: String,
+    pub tiberius_conditions_table: String,
+    pub tiberius_columns_database: String,
+    pub tiberius_columns_schema: String,
+    pub tiberius_columns_table: String,
+    pub tiberius_validate_targets: bool,
+    pub tiberius_writer_workers: usize,
+
+    pub partition_manager_enabled: bool,
+    pub partition_manager_interval: PartitionInterval,
+    pub partition_manager_ahead: usize,
+    // 0 keeps every partition
+    pub partition_manager_keep: usize,
+    pub partition_manager_retention_mode: RetentionMode,
+    pub partition_manager_check_seconds: u64,
+
+    pub storage_capture_enabled: bool,
+    pub storage_capture_history: usize,
+    pub storage_capture_settle_ms: u64,
+    pub server_metrics_capture_enabled: bool,
+
+    // EnvFilter directive, e.g. info or axum_benchmark_database=debug
+    pub log_level: String,
+    pub log_format: LogFormat,
+
 }
 
 impl Environment {
@@ -68,6 +122,24 @@ impl Environment {
         format!("{0}:{1}", self.server_host, self.server_port)
     }
 
+    // table used by conditions_tiberius
+    pub fn tiberius_conditions_target(&self) -> TableTarget {
+        TableTarget {
+            database: self.tiberius_conditions_database.clone(),
+            schema: self.tiberius_conditions_schema.clone(),
+            table: self.tiberius_conditions_table.clone(),
+        }
+    }
+
+    // table used by conditions_tiberius_columns
+    pub fn tiberius_columns_target(&self) -> TableTarget {
+        TableTarget {
+            database: self.tiberius_columns_database.clone(),
+            schema: self.tiberius_columns_schema.clone(),
+            table: self.tiberius_columns_table.clone(),
+        }
+    }
+
     pub fn get_database_url(&self) -> String {
         return format!(
             "postgresql://{0}:{1}@{2}:{3}/{4}",
@@ -0,0 +1,94 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use rdkafka::{
+    ClientConfig, ClientContext, Statistics,
+    consumer::StreamConsumer,
+    error::KafkaError,
+    producer::FutureProducer,
+};
+
+use crate::config::environment::CONFIG;
+
+// keeps the latest librdkafka statistics report, emitted every statistics.interval.ms,
+// clones share the report so the caller can read it while the client owns the context
+#[derive(Default, Clone)]
+pub struct StatisticsContext {
+    latest: Arc<Mutex<Option<(Instant, Statistics)>>>,
+}
+
+impl ClientContext for StatisticsContext {
+    fn stats(&self, statistics: Statistics) {
+        if let Ok(mut latest) = self.latest.lock() {
+            *latest = Some((Instant::now(), statistics));
+        }
+    }
+}
+
+impl StatisticsContext {
+    // waits for a report emitted after `since`, None if none arrives within `wait`
+    pub async fn statistics_after(&self, since: Instant, wait: Duration) -> Option<Statistics> {
+        let deadline = Instant::now() + wait;
+        while Instant::now() < deadline {
+            if let Ok(latest) = self.latest.lock()
+                && let Some((received_at, statistics)) = latest.as_ref()
+                && *received_at > since
+            {
+                return Some(statistics.clone());
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        None
+    }
+}
+
+fn producer_config(overrides: &[(&str, String)]) -> ClientConfig {
+    let config_env = &CONFIG;
+    let mut config = ClientConfig::new();
+    config
+        .set("bootstrap.servers", &config_env.kafka_bootstrap_servers)
+        .set("message.timeout.ms", config_env.kafka_message_timeout_ms.to_string())
+        .set("message.max.bytes", config_env.kafka_message_max_bytes.to_string())
+        .set("receive.message.max.bytes", config_env.kafka_receive_message_max_bytes.to_string());
+    for (key, value) in overrides {
+        config.set(*key, value);
+    }
+    config
+}
+
+pub fn get_kafka_producer() -> Result<FutureProducer, KafkaError> {
+    producer_config(&[]).create()
+}
+
+// producer with extra librdkafka settings, e.g. compression.type or linger.ms
+pub fn get_kafka_producer_with(overrides: &[(&str, String)]) -> Result<FutureProducer, KafkaError> {
+    producer_config(overrides).create()
+}
+
+pub fn get_kafka_statistics_producer(
+    overrides: &[(&str, String)],
+    statistics_interval_ms: u64,
+) -> Result<(FutureProducer<StatisticsContext>, StatisticsContext), KafkaError> {
+    let context = StatisticsContext::default();
+    let producer = producer_config(overrides)
+        .set("statistics.interval.ms", statistics_interval_ms.to_string())
+        .create_with_context(context.clone())?;
+    Ok((producer, context))
+}
+
+// consumer without a subscription, the caller decides between subscribe and assign,
+// offsets are only committed explicitly once a message has been handled
+pub fn get_kafka_consumer(group_id: &str) -> Result<StreamConsumer, KafkaError> {
+    let config_env = &CONFIG;
+    ClientConfig::new()
+        .set("bootstrap.servers", &config_env.kafka_bootstrap_servers)
+        .set("group.id", group_id)
+        .set("auto.offset.reset", &config_env.kafka_auto_offset_reset)
+        .set("enable.auto.commit", "false")
+        .set("fetch.message.max.bytes", config_env.kafka_fetch_message_max_bytes.to_string())
+        .set("fetch.max.bytes", config_env.kafka_fetch_max_bytes.to_string())
+        .set("receive.message.max.bytes", config_env.kafka_receive_message_max_bytes.to_string())
+        .create()
+}
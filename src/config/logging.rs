@@ -0,0 +1,39 @@
+use serde::Deserialize;
+use tracing_subscriber::{
+    EnvFilter, Layer, filter::filter_fn, fmt::{self, format::FmtSpan}, layer::SubscriberExt,
+    util::SubscriberInitExt,
+};
+
+use crate::{
+    config::environment::CONFIG,
+    modules::metrics::layer::{DbCallLayer, is_db_call},
+};
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Text,
+    // one json object per line with the span fields, for log shippers
+    Json,
+}
+
+// RUST_LOG wins over LOG_LEVEL, closing spans are logged so every request, benchmark iteration
+// and database call reports its duration
+pub fn init() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&CONFIG.log_level));
+    let output = match CONFIG.log_format {
+        LogFormat::Text => fmt::layer().with_span_events(FmtSpan::CLOSE).boxed(),
+        LogFormat::Json => fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .with_span_events(FmtSpan::CLOSE)
+            .boxed(),
+    };
+
+    // the metrics layer has its own filter, db call metrics do not depend on the log level
+    tracing_subscriber::registry()
+        .with(output.with_filter(filter))
+        .with(DbCallLayer.with_filter(filter_fn(|metadata| metadata.is_span() && is_db_call(metadata.target()))))
+        .init();
+}
@@ -1,2 +1,4 @@
 pub mod database;
-pub mod environment;
\ No newline at end of file
+pub mod environment;
+pub mod kafka;
+pub mod logging;
\ No newline at end of file
@@ -5,9 +5,9 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
-use validator::ValidationErrors;
+use validator::{ValidationError, ValidationErrors};
 
-use crate::dto::app_response::AppResponse;
+use crate::{dto::app_response::AppResponse, util::request_id};
 
 #[derive(Debug, PartialEq)]
 pub enum AppError {
@@ -17,8 +17,19 @@ pub enum AppError {
     InternalServerError,
     Other(String),
 }
+
+impl AppError {
+    // a single field error, for request checks the validate derive cannot express
+    pub fn invalid_request(field: &'static str, message: impl Into<String>) -> Self {
+        let mut errors = ValidationErrors::new();
+        errors.add(field, ValidationError::new("invalid").with_message(message.into().into()));
+        AppError::InvalidRequest(errors)
+    }
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        tracing::warn!(error = ?self, "request failed");
         match self {
             AppError::DataExist => {
                 let status_code = StatusCode::BAD_REQUEST;
@@ -30,6 +41,7 @@ impl IntoResponse for AppError {
                         timestamp: chrono::Utc::now().naive_utc(),
                         error: Some("resource exist".to_string()),
                         data: None,
+                        request_id: request_id::current(),
                     }),
                 )
                     .into_response()
@@ -44,6 +56,7 @@ impl IntoResponse for AppError {
                         timestamp: chrono::Utc::now().naive_utc(),
                         error: Some("resource not found".to_string()),
                         data: None,
+                        request_id: request_id::current(),
                     }),
                 )
                     .into_response()
@@ -57,7 +70,8 @@ impl IntoResponse for AppError {
                         message: "error".to_owned(),
                         timestamp: chrono::Utc::now().naive_utc(),
                         error: Some("internal server error".to_string()),
-                        data: None
+                        data: None,
+                        request_id: request_id::current(),
                     }),
                 )
                     .into_response()
@@ -71,7 +85,8 @@ impl IntoResponse for AppError {
                         message: "error".to_owned(),
                         timestamp: chrono::Utc::now().naive_utc(),
                         error: Some(message),
-                        data: None
+                        data: None,
+                        request_id: request_id::current(),
                     }),
                 )
                     .into_response()
@@ -85,7 +100,8 @@ impl IntoResponse for AppError {
                         message: "error".to_owned(),
                         timestamp: chrono::Utc::now().naive_utc(),
                         error: Some(parse_validation_error_message(&format!("{validation_errors}"))),
-                        data: None
+                        data: None,
+                        request_id: request_id::current(),
                     }),
                 )
                     .into_response()
@@ -1,6 +1,6 @@
 use chrono::{NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
-use crate::util::serializer::datetime_serializer;
+use crate::util::{request_id, serializer::datetime_serializer};
 
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 pub struct AppResponse<T> {
@@ -12,14 +12,31 @@ pub struct AppResponse<T> {
     pub data: Option<T>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<T>,
+    // x-request-id of the request, also on every log line it produced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
 }
 
 
 impl<T> AppResponse<T> {
     pub fn ok(message: impl Into<String>, data: Option<T>) -> Self {
-        AppResponse { status: 200, message: message.into(), timestamp: Utc::now().naive_utc(), data: data, error: None }
+        AppResponse {
+            status: 200,
+            message: message.into(),
+            timestamp: Utc::now().naive_utc(),
+            data,
+            error: None,
+            request_id: request_id::current(),
+        }
     }
     pub fn err(status: u16, message: impl Into<String>, error_data: T) -> Self {
-        AppResponse { status: status, message: message.into(), timestamp: Utc::now().naive_utc(), data: None, error: Some(error_data) }
+        AppResponse {
+            status,
+            message: message.into(),
+            timestamp: Utc::now().naive_utc(),
+            data: None,
+            error: Some(error_data),
+            request_id: request_id::current(),
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,12 @@
+use serde::Serialize;
+
+use crate::{modules::query_plan::schema::QueryPlan, util::resource_usage::ResourceUsage};
+
+// data of the list benchmarks, the durations stay in the message
+#[derive(Debug, Serialize)]
+pub struct ReadBenchmarkReport {
+    // one entry per timed iteration, in order
+    pub iterations: Vec<ResourceUsage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan: Option<QueryPlan>,
+}
@@ -1,2 +1,3 @@
 pub mod app_response;
-pub mod app_error;
\ No newline at end of file
+pub mod app_error;
+pub mod benchmark_report;
\ No newline at end of file
@@ -1,57 +1,148 @@
 use std::sync::Arc;
 
-use axum::{Extension, Json, Router, http::StatusCode, routing::get};
+use axum::{Extension, Json, Router, http::StatusCode, middleware, routing::get};
 use axum_benchmark_database::{
     config::{self, environment::CONFIG},
     dto::{app_error::AppError, app_response::AppResponse},
-    modules::{conditions, conditions_diesel, conditions_kafka, conditions_tiberius, conditions_tiberius_columns},
+    modules::{conditions, conditions_diesel, conditions_kafka::{self, bus::MessageBus}, conditions_tiberius, conditions_tiberius_columns, dynamic_table::{self, schema::Backend}, metrics, provisioning, server_metrics, storage::{self, capture::{around_benchmarks, around_dynamic_table_benchmarks, around_kafka_benchmarks}, schema::StorageTable}},
     state::AppState,
+    util::{request_id, resource_usage::CountingAllocator},
 };
-use tokio::{net::TcpListener, sync::Mutex};
+use tokio::{net::TcpListener, signal::unix::{SignalKind, signal}, sync::{Mutex, RwLock}};
+
+// allocation counts in the benchmark reports
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
 
 #[tokio::main]
 async fn main() {
+    config::logging::init();
+
     let diesel_pool = config::database::get_diesel_postgres_db_pool();
+    if CONFIG.database_run_migrations {
+        let mut connection = diesel_pool.get().unwrap();
+        let applied = provisioning::migration::run_pending(&mut connection).unwrap();
+        tracing::info!(?applied, "applied migrations");
+    }
     let deadpool_postgres_pool = config::database::get_tokio_postgres_db_pool();
     let tokio_postgres_client = config::database::get_tokio_postgresql().await.unwrap();
+    let dynamic_tables = match dynamic_table::repository::load_definitions(&tokio_postgres_client).await {
+        Ok(definitions) => definitions,
+        Err(error) => {
+            tracing::error!(
+                ?error,
+                "can not read the dynamic table registry, apply the migrations or start with DATABASE_RUN_MIGRATIONS=true"
+            );
+            std::process::exit(1);
+        }
+    };
+    let dynamic_tables = dynamic_tables
+        .into_iter()
+        .map(|definition| (definition.name.clone(), Some(definition)))
+        .collect();
     let deadpool_tiberius = config::database::get_deadpool_tiberius_sql_server_db_pool();
+    if CONFIG.tiberius_validate_targets {
+        for target in [CONFIG.tiberius_conditions_target(), CONFIG.tiberius_columns_target()] {
+            if let Err(error) = config::database::validate_tiberius_target(&deadpool_tiberius, &target).await {
+                panic!("sql server target {} is not usable: {:?}", target.qualified(), error);
+            }
+        }
+    }
+    let message_bus = MessageBus::from_config().unwrap();
+    let bus_consumer = message_bus.consumer(&CONFIG.kafka_group_id, &CONFIG.kafka_topic).unwrap();
 
     let state = AppState {
         diesel_pool_pg: Arc::new(diesel_pool),
         pool_pg: deadpool_postgres_pool,
         tokio_postgres_client: Mutex::new(tokio_postgres_client),
         pool_tiberius: deadpool_tiberius,
+        message_bus,
+        bus_consumer: Mutex::new(bus_consumer),
+        ingest_worker: Default::default(),
+        dynamic_tables: RwLock::new(dynamic_tables),
+        storage_runs: Default::default(),
         status: "up".to_string(),
     };
     let shared_state = Arc::new(state);
 
+    if CONFIG.partition_manager_enabled {
+        provisioning::partition_manager::spawn(shared_state.clone());
+    }
+
+    if CONFIG.kafka_ingest_autostart {
+        let started =
+            conditions_kafka::worker::start(&shared_state, CONFIG.kafka_ingest_workers, CONFIG.kafka_ingest_sink).await;
+        if let Err(error) = started {
+            tracing::error!(?error, "can not start the ingest worker, check KAFKA_INGEST_WORKERS against the topic");
+            std::process::exit(1);
+        }
+    }
+
     let app = Router::new()
         .route("/", get(root))
         .route("/health", get(health_check))
 
         // tokio
-        .nest("/conditions/benchmark", conditions::controller_benchmark::new())
+        .nest("/conditions/benchmark", around_benchmarks(conditions::controller_benchmark::new(), Backend::Postgres, StorageTable::Conditions))
         .nest("/conditions/crud", conditions::controller_crud::new())
 
         // diesel
-        .nest("/conditions_diesel/benchmark", conditions_diesel::controller_benchmark::new())
+        .nest("/conditions_diesel/benchmark", around_benchmarks(conditions_diesel::controller_benchmark::new(), Backend::Postgres, StorageTable::Conditions))
         .nest("/conditions_diesel/crud", conditions_diesel::controller_crud::new())
 
         // kafka
         .nest("/conditions_kafka", conditions_kafka::controller::new())
-        .nest("/conditions_kafka/benchmark", conditions_kafka::controller_benchmark::new())
+        .nest("/conditions_kafka/benchmark", around_kafka_benchmarks(conditions_kafka::controller_benchmark::new()))
+        .nest("/conditions_kafka/ingest", conditions_kafka::controller_ingest::new())
 
         // tiberius
         .nest("/conditions_tiberius/crud", conditions_tiberius::controller_crud::new())
-        .nest("/conditions_tiberius/benchmark", conditions_tiberius::controller_benchmark::new())
-        .nest("/conditions_tiberius_column/benchmark", conditions_tiberius_columns::controller_benchmark::new())
+        .nest("/conditions_tiberius/benchmark", around_benchmarks(conditions_tiberius::controller_benchmark::new(), Backend::SqlServer, StorageTable::Conditions))
+        .nest("/conditions_tiberius_column/crud", conditions_tiberius_columns::controller_crud::new())
+        .nest("/conditions_tiberius_column/benchmark", around_benchmarks(conditions_tiberius_columns::controller_benchmark::new(), Backend::SqlServer, StorageTable::Columns))
+
+        // dynamic table
+        .nest("/dynamic_table", dynamic_table::controller::new())
+        .nest("/dynamic_table/benchmark", around_dynamic_table_benchmarks(dynamic_table::controller_benchmark::new()))
+
+        // schema provisioning
+        .nest("/schema", provisioning::controller::new())
+
+        // storage statistics and server counters
+        .nest("/storage", storage::controller::new())
+        .nest("/server_metrics", server_metrics::controller::new())
+
+        // prometheus scrape target
+        .nest("/metrics", metrics::controller::new())
+
+        // http request metrics, per matched route
+        .route_layer(middleware::from_fn(metrics::http::track))
 
         // shared state
-        .layer(Extension(shared_state));
+        .layer(Extension(shared_state.clone()))
+
+        // request span and x-request-id
+        .layer(middleware::from_fn(request_id::propagate));
 
     let config_env = &CONFIG;
     let listener = TcpListener::bind(config_env.get_server_url()).await.unwrap();
-    axum::serve(listener, app).await.unwrap()
+    tracing::info!(address = %config_env.get_server_url(), "listening");
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal(shared_state))
+        .await
+        .unwrap()
+}
+
+// SIGTERM or ctrl-c, the ingest worker finishes its in-flight batches before the server exits
+async fn shutdown_signal(state: Arc<AppState>) {
+    let mut terminate = signal(SignalKind::terminate()).unwrap();
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {},
+        _ = terminate.recv() => {},
+    }
+
+    tracing::info!("shutting down");
+    let _ = conditions_kafka::worker::stop(&state).await;
 }
 
 async fn root() -> Result<(StatusCode, Json<AppResponse<String>>), AppError> {
@@ -2,20 +2,25 @@ use std::sync::Arc;
 
 use axum::{
     Extension, Json, Router,
-    extract::Path,
+    extract::{Path, Query},
     http::StatusCode,
     routing::{delete, get},
 };
 use tokio::time::Instant;
+use tracing::Instrument;
 
 use crate::{
-    dto::{app_error::AppError, app_response::AppResponse},
-    modules::conditions::{
-        repository,
-        schema::{Conditions, ConditionsRequest},
+    dto::{app_error::AppError, app_response::AppResponse, benchmark_report::ReadBenchmarkReport},
+    modules::{
+        conditions::{
+            repository,
+            schema::{Conditions, ConditionsRequest},
+        },
+        provisioning::ddl,
+        query_plan::{self, schema::PlanRequest},
     },
     state::AppState,
-    util,
+    util::{self, resource_usage::ResourceMeter},
 };
 
 pub fn new() -> Router {
@@ -26,18 +31,24 @@ pub fn new() -> Router {
 }
 
 pub async fn find_all(
+    Query(plan_request): Query<PlanRequest>,
     Extension(_state): Extension<Arc<AppState>>,
-) -> Result<(StatusCode, Json<AppResponse<Vec<Conditions>>>), AppError> {
+) -> Result<(StatusCode, Json<AppResponse<ReadBenchmarkReport>>), AppError> {
     // get db connection
     let client = _state.tokio_postgres_client.lock().await;
 
     let mut durations = String::new();
-    for _ in 0..10 {
+    let mut iterations = Vec::new();
+    for index in 0..10 {
+        let meter = ResourceMeter::start();
         let start = Instant::now();
 
-        let mut _result: Vec<Conditions> = repository::find_all(&client).await?;
+        let mut _result: Vec<Conditions> = repository::find_all(&client)
+            .instrument(tracing::info_span!("iteration", index))
+            .await?;
         
         let duration = start.elapsed();
+        iterations.push(meter.finish());
         if durations.len() == 0 {
             durations = format!("{}", duration.as_millis());
             continue;
@@ -47,12 +58,21 @@ pub async fn find_all(
         _result.clear();
     }
 
+    let plan = match plan_request.plan.unwrap_or(false) {
+        true => Some(
+            query_plan::repository::explain_postgres(&client, repository::FIND_ALL_STATEMENT, ddl::POSTGRES_TABLE)
+                .await?,
+        ),
+        false => None,
+    };
+
+    let report = ReadBenchmarkReport { iterations, plan };
     let status_code = StatusCode::OK;
     return Ok((
         status_code,
         Json(AppResponse::ok(
             format!("Time in milliseconds: {} ms", durations),
-            None,
+            Some(report),
         )),
     ));
 }
@@ -9,7 +9,7 @@ use crate::{
 };
 use futures_util::{pin_mut, sink::SinkExt};
 use tempfile::Builder;
-use tokio::{fs::File as AsyncFile, io::AsyncReadExt, time::Instant};
+use tokio::{fs::File as AsyncFile, io::AsyncReadExt};
 
 pub fn map_row_to_condition(row: tokio_postgres::Row) -> Conditions {
     let created_on_datetime: DateTime<Utc> = row.get("created_on");
@@ -22,20 +22,21 @@ pub fn map_row_to_condition(row: tokio_postgres::Row) -> Conditions {
     }
 }
 
+pub const FIND_ALL_STATEMENT: &str = "SELECT id, created_on, location, temperature, humidity FROM conditions";
+
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub async fn find_all(
     client: &tokio_postgres::Client,
 ) -> Result<Vec<modules::conditions::schema::Conditions>, AppError> {
-    let start = Instant::now();
-    let statement = "SELECT id, created_on, location, temperature, humidity FROM conditions";
     let rows = client
-        .query(statement, &[])
+        .query(FIND_ALL_STATEMENT, &[])
         .await
         .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
-    let duration = start.elapsed();
-    println!("{}", duration.as_millis());
+    tracing::Span::current().record("rows", rows.len());
     return Ok(rows.into_par_iter().map(map_row_to_condition).collect());
 }
 
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub async fn find_by_id(
     client: &tokio_postgres::Client,
     id: String,
@@ -46,6 +47,7 @@ pub async fn find_by_id(
         .query(statement, &[&id])
         .await
         .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+    tracing::Span::current().record("rows", rows.len());
     let _conditions: Vec<Conditions> = rows.into_iter().map(map_row_to_condition).collect();
     let _condition = _conditions.first();
     if _condition.is_none() {
@@ -54,6 +56,7 @@ pub async fn find_by_id(
     return Ok(_condition.unwrap().clone());
 }
 
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub async fn delete_all(client: &tokio_postgres::Client) -> Result<(), AppError> {
     let statement = "DELETE FROM conditions";
 
@@ -61,6 +64,7 @@ pub async fn delete_all(client: &tokio_postgres::Client) -> Result<(), AppError>
         .execute(statement, &[])
         .await
         .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+    tracing::Span::current().record("rows", rows_affected);
     if rows_affected == 0 {
         return Err(AppError::NotFound);
     }
@@ -68,6 +72,7 @@ pub async fn delete_all(client: &tokio_postgres::Client) -> Result<(), AppError>
     return Ok(());
 }
 
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub async fn delete_by_id(client: &tokio_postgres::Client, id: String) -> Result<(), AppError> {
     let statement = "DELETE FROM conditions WHERE id=$1";
 
@@ -75,6 +80,7 @@ pub async fn delete_by_id(client: &tokio_postgres::Client, id: String) -> Result
         .execute(statement, &[&id])
         .await
         .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+    tracing::Span::current().record("rows", rows_affected);
     if rows_affected == 0 {
         return Err(AppError::NotFound);
     }
@@ -82,6 +88,7 @@ pub async fn delete_by_id(client: &tokio_postgres::Client, id: String) -> Result
     return Ok(());
 }
 
+#[tracing::instrument(skip_all, fields(rows = data.len()))]
 pub async fn insert_all(
     client: &mut tokio_postgres::Client,
     data: Vec<Conditions>,
@@ -123,6 +130,7 @@ pub async fn insert_all(
     return Ok(());
 }
 
+#[tracing::instrument(skip_all, fields(rows = 1))]
 pub async fn insert_one(
     client: &mut tokio_postgres::Client,
     condition: Conditions,
@@ -162,6 +170,7 @@ pub async fn insert_one(
     return Ok(());
 }
 
+#[tracing::instrument(skip_all, fields(rows = data.len()))]
 pub async fn insert_batch(
     client: &mut tokio_postgres::Client,
     data: Vec<Conditions>,
@@ -267,6 +276,7 @@ pub async fn insert_batch(
     Ok(())
 }
 
+#[tracing::instrument(skip_all, fields(rows = 1))]
 pub async fn update_one(
     client: &mut tokio_postgres::Client,
     condition: Conditions,
@@ -2,20 +2,24 @@ use std::sync::Arc;
 
 use axum::{
     Extension, Json, Router,
-    extract::Path,
+    extract::{Path, Query},
     http::StatusCode,
     routing::{delete, get},
 };
 use tokio::time::Instant;
 
 use crate::{
-    dto::{app_error::AppError, app_response::AppResponse},
-    modules::conditions_diesel::{
-        repository,
-        schema::{Conditions, ConditionsRequest},
+    dto::{app_error::AppError, app_response::AppResponse, benchmark_report::ReadBenchmarkReport},
+    modules::{
+        conditions_diesel::{
+            repository,
+            schema::{Conditions, ConditionsRequest},
+        },
+        provisioning::ddl,
+        query_plan::{self, schema::PlanRequest},
     },
     state::AppState,
-    util,
+    util::{self, resource_usage::ResourceMeter},
 };
 
 pub fn new() -> Router {
@@ -27,8 +31,9 @@ pub fn new() -> Router {
 }
 
 pub async fn find_all(
+    Query(plan_request): Query<PlanRequest>,
     Extension(_state): Extension<Arc<AppState>>,
-) -> Result<(StatusCode, Json<AppResponse<Vec<Conditions>>>), AppError> {
+) -> Result<(StatusCode, Json<AppResponse<ReadBenchmarkReport>>), AppError> {
     // get db connection
     let db_conn_result = _state.diesel_pool_pg.get();
     let mut db_conn;
@@ -42,12 +47,16 @@ pub async fn find_all(
     };
 
     let mut durations = String::new();
-    for _ in 0..10 {
+    let mut iterations = Vec::new();
+    for index in 0..10 {
+        let _iteration = tracing::info_span!("iteration", index).entered();
+        let meter = ResourceMeter::start();
         let start = Instant::now();
         let result: Result<Vec<Conditions>, AppError> = repository::find_all(&mut db_conn);
         match result {
             Ok(_) => {
                 let duration = start.elapsed();
+                iterations.push(meter.finish());
                 if durations.len() == 0 {
                     durations = format!("{}", duration.as_millis());
                     continue;
@@ -60,12 +69,29 @@ pub async fn find_all(
         }
     }
 
+    // diesel has no EXPLAIN, its statement runs on a tokio-postgres connection instead
+    let plan = match plan_request.plan.unwrap_or(false) {
+        true => {
+            let client = _state
+                .pool_pg
+                .get()
+                .await
+                .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+            Some(
+                query_plan::repository::explain_postgres(&client, repository::FIND_ALL_STATEMENT, ddl::POSTGRES_TABLE)
+                    .await?,
+            )
+        }
+        false => None,
+    };
+
+    let report = ReadBenchmarkReport { iterations, plan };
     let status_code = StatusCode::OK;
     return Ok((
         status_code,
         Json(AppResponse::ok(
             format!("Time in milliseconds: {} ms", durations),
-            None,
+            Some(report),
         )),
     ));
 }
@@ -12,6 +12,7 @@ use crate::{
         schema::{Conditions, ConditionsRequest},
     },
     state::AppState,
+    util::request_id,
 };
 
 pub fn new() -> Router {
@@ -109,6 +110,7 @@ pub async fn delete_by_id(
                     timestamp: chrono::Utc::now().naive_utc(),
                     data: None,
                     error: None,
+                    request_id: request_id::current(),
                 }),
             ));
         }
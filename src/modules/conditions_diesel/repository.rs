@@ -10,6 +10,7 @@ use crate::{
     dto::app_error::AppError, modules::conditions_diesel::schema::Conditions, schema::conditions::id,
 };
 
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub fn find_by_id(
     conn: &mut PgConnection,
     conditions_id: String,
@@ -22,10 +23,12 @@ pub fn find_by_id(
         .map_err(|error| {
             AppError::Other(format!("query failed: {}, id: {}", error, conditions_id))
         })?;
+    tracing::Span::current().record("rows", user.iter().len());
 
     Ok(user)
 }
 
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub fn find_all_by_query(conn: &mut PgConnection) -> Result<Vec<Conditions>, AppError> {
     let query = "SELECT id,created_on,temperature,location,humidity
             FROM conditions";
@@ -33,17 +36,26 @@ pub fn find_all_by_query(conn: &mut PgConnection) -> Result<Vec<Conditions>, App
     let user: Vec<Conditions> = sql_query(query)
         .get_results::<Conditions>(conn)
         .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+    tracing::Span::current().record("rows", user.len());
     Ok(user)
 }
 
+const FIND_ALL_LIMIT: i64 = 1000000;
+
+// the sql diesel sends for find_all with the limit written in, EXPLAIN takes no binds
+pub const FIND_ALL_STATEMENT: &str = "SELECT \"conditions\".\"id\", \"conditions\".\"created_on\", \"conditions\".\"location\", \"conditions\".\"temperature\", \"conditions\".\"humidity\" FROM \"conditions\" LIMIT 1000000";
+
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub fn find_all(conn: &mut PgConnection) -> Result<Vec<Conditions>, AppError> {
     let user: Vec<Conditions> = conditions
-        .select(Conditions::as_select()).limit(1000000)
+        .select(Conditions::as_select()).limit(FIND_ALL_LIMIT)
         .load(conn)
         .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+    tracing::Span::current().record("rows", user.len());
     Ok(user)
 }
 
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub fn find_all_by_query_pagination(conn: &mut PgConnection) -> Result<Vec<Conditions>, AppError> {
     let mut data: Vec<Conditions> = Vec::new();
 
@@ -73,9 +85,11 @@ pub fn find_all_by_query_pagination(conn: &mut PgConnection) -> Result<Vec<Condi
             .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
         data.append(&mut result);
     }
+    tracing::Span::current().record("rows", data.len());
     Ok(data)
 }
 
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub fn delete_by_id(
     conn: &mut PgConnection,
     conditions_id: String,
@@ -86,34 +100,40 @@ pub fn delete_by_id(
             AppError::Other(format!("query failed: {}, id: {}", error, conditions_id))
         })?;
 
+    tracing::Span::current().record("rows", rows_affected);
     if rows_affected > 0 {
         return Ok(Some(()));
     }
     return Ok(None);
 }
 
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub fn delete_all(conn: &mut PgConnection) -> Result<Option<()>, AppError> {
     let rows_affected = diesel::delete(conditions)
         .execute(conn)
         .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
 
+    tracing::Span::current().record("rows", rows_affected);
     if rows_affected > 0 {
         return Ok(Some(()));
     }
     return Ok(None);
 }
 
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub fn create(conn: &mut PgConnection, data: Conditions) -> Result<Option<()>, AppError> {
     let rows_affected = insert_into(conditions)
         .values(&data)
         .execute(conn)
         .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+    tracing::Span::current().record("rows", rows_affected);
     if rows_affected > 0 {
         return Ok(Some(()));
     }
     return Ok(None);
 }
 
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub fn create_bacth(
     conn: &mut PgConnection,
     data: Vec<Conditions>,
@@ -122,12 +142,14 @@ pub fn create_bacth(
         .values(&data)
         .execute(conn)
         .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+    tracing::Span::current().record("rows", rows_affected);
     if rows_affected > 0 {
         return Ok(Some(()));
     }
     return Ok(None);
 }
 
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub fn update_data(conn: &mut PgConnection, data: Conditions) -> Result<Option<()>, AppError> {
     let rows_affected = update(conditions.filter(id.eq(data.id.to_owned())))
         .set((
@@ -137,8 +159,27 @@ pub fn update_data(conn: &mut PgConnection, data: Conditions) -> Result<Option<(
         ))
         .execute(conn)
         .map_err(|error| AppError::Other(format!("query failed: {}, id: {}", error, data.id)))?;
+    tracing::Span::current().record("rows", rows_affected);
     if rows_affected > 0 {
         return Ok(Some(()));
     }
     return Ok(None);
 }
+
+#[cfg(test)]
+mod tests {
+    use diesel::{debug_query, pg::Pg};
+
+    use super::*;
+
+    #[test]
+    fn find_all_statement_is_the_diesel_query() {
+        let query = conditions.select(Conditions::as_select()).limit(FIND_ALL_LIMIT);
+        let expected = format!(
+            "{} -- binds: [{}]",
+            FIND_ALL_STATEMENT.replace(&format!("LIMIT {}", FIND_ALL_LIMIT), "LIMIT $1"),
+            FIND_ALL_LIMIT
+        );
+        assert_eq!(debug_query::<Pg, _>(&query).to_string(), expected);
+    }
+}
@@ -0,0 +1,170 @@
+use serde::Deserialize;
+
+use crate::{
+    config::environment::CONFIG,
+    dto::app_error::AppError,
+    modules::{
+        conditions_kafka::{
+            bus_kafka::{KafkaBus, KafkaBusConsumer},
+            bus_memory::{MemoryBus, MemoryBusConsumer},
+            schema::PartitionLag,
+        },
+        metrics::registry,
+    },
+};
+
+// which message bus the kafka module runs on, the in-memory one needs no broker
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageBusKind {
+    #[default]
+    Kafka,
+    Memory,
+}
+
+// a consumed message, owned so it can outlive the consumer call that returned it
+#[derive(Debug, Clone)]
+pub struct BusMessage {
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+    pub key: Option<Vec<u8>>,
+    pub payload: Vec<u8>,
+    pub headers: Vec<(String, Vec<u8>)>,
+}
+
+pub enum MessageBus {
+    Kafka(KafkaBus),
+    Memory(MemoryBus),
+}
+
+pub enum BusConsumer {
+    Kafka(KafkaBusConsumer),
+    Memory(MemoryBusConsumer),
+}
+
+impl BusMessage {
+    pub fn key_str(&self) -> Option<&str> {
+        self.key.as_deref().and_then(|key| std::str::from_utf8(key).ok())
+    }
+
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header_key, _)| header_key == key)
+            .and_then(|(_, value)| std::str::from_utf8(value).ok())
+    }
+}
+
+impl MessageBusKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageBusKind::Kafka => "kafka",
+            MessageBusKind::Memory => "memory",
+        }
+    }
+}
+
+impl MessageBus {
+    pub fn from_config() -> Result<MessageBus, AppError> {
+        match CONFIG.message_bus {
+            MessageBusKind::Kafka => Ok(MessageBus::Kafka(KafkaBus::new(&[])?)),
+            MessageBusKind::Memory => Ok(MessageBus::Memory(MemoryBus::new(
+                CONFIG.message_bus_memory_partitions,
+            ))),
+        }
+    }
+
+    // waits until the message is acknowledged, by the broker for kafka
+    pub async fn send(
+        &self,
+        topic: &str,
+        key: Option<&[u8]>,
+        payload: &[u8],
+        headers: &[(&str, &[u8])],
+    ) -> Result<(), AppError> {
+        let result = match self {
+            MessageBus::Kafka(bus) => bus.send(topic, key, payload, headers).await,
+            MessageBus::Memory(bus) => bus.send(topic, key, payload, headers),
+        };
+        match &result {
+            Ok(()) => registry::record_message(self.kind().as_str(), "produced", topic, payload.len()),
+            Err(_) => registry::record_message_error(self.kind().as_str(), "produced"),
+        }
+        result
+    }
+
+    pub fn kind(&self) -> MessageBusKind {
+        match self {
+            MessageBus::Kafka(_) => MessageBusKind::Kafka,
+            MessageBus::Memory(_) => MessageBusKind::Memory,
+        }
+    }
+
+    // a consumer in `group_id` that starts from the group's committed offsets
+    pub fn consumer(&self, group_id: &str, topic: &str) -> Result<BusConsumer, AppError> {
+        match self {
+            MessageBus::Kafka(bus) => Ok(BusConsumer::Kafka(bus.consumer(group_id, topic)?)),
+            MessageBus::Memory(bus) => Ok(BusConsumer::Memory(bus.consumer(group_id, topic, false))),
+        }
+    }
+
+    // a consumer that only sees messages sent after it was created
+    pub fn consumer_at_end(&self, group_id: &str, topic: &str) -> Result<BusConsumer, AppError> {
+        match self {
+            MessageBus::Kafka(bus) => Ok(BusConsumer::Kafka(bus.consumer_at_end(group_id, topic)?)),
+            MessageBus::Memory(bus) => Ok(BusConsumer::Memory(bus.consumer(group_id, topic, true))),
+        }
+    }
+
+    // blocking for kafka, call it from block_in_place
+    pub fn partition_count(&self, topic: &str) -> Result<usize, AppError> {
+        match self {
+            MessageBus::Kafka(bus) => bus.partition_count(topic),
+            MessageBus::Memory(bus) => Ok(bus.partition_count()),
+        }
+    }
+
+    // blocking for kafka, call it from block_in_place
+    pub fn lag(&self, group_id: &str, topic: &str) -> Result<Vec<PartitionLag>, AppError> {
+        match self {
+            MessageBus::Kafka(bus) => bus.lag(group_id, topic),
+            MessageBus::Memory(bus) => Ok(bus.lag(group_id, topic)),
+        }
+    }
+}
+
+impl BusConsumer {
+    pub async fn recv(&mut self) -> Result<BusMessage, AppError> {
+        let (bus, result) = match self {
+            BusConsumer::Kafka(consumer) => (MessageBusKind::Kafka, consumer.recv().await),
+            BusConsumer::Memory(consumer) => (MessageBusKind::Memory, Ok(consumer.recv().await)),
+        };
+        match &result {
+            Ok(message) => registry::record_message(bus.as_str(), "consumed", &message.topic, message.payload.len()),
+            Err(_) => registry::record_message_error(bus.as_str(), "consumed"),
+        }
+        result
+    }
+
+    pub fn commit(&self, message: &BusMessage) -> Result<(), AppError> {
+        match self {
+            BusConsumer::Kafka(consumer) => consumer.commit(message),
+            BusConsumer::Memory(consumer) => {
+                consumer.commit(message);
+                Ok(())
+            }
+        }
+    }
+
+    // moves the consumer back so `message` is delivered again
+    pub fn seek(&self, message: &BusMessage) -> Result<(), AppError> {
+        match self {
+            BusConsumer::Kafka(consumer) => consumer.seek(message),
+            BusConsumer::Memory(consumer) => {
+                consumer.seek(message);
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use std::time::Duration;
+
+use rdkafka::{
+    Message, Offset, TopicPartitionList,
+    consumer::{CommitMode, Consumer, StreamConsumer},
+    message::{Header, Headers, OwnedHeaders},
+    producer::{FutureProducer, FutureRecord, Producer},
+};
+
+use crate::{
+    config,
+    dto::app_error::AppError,
+    modules::conditions_kafka::{bus::BusMessage, schema::PartitionLag},
+};
+
+pub struct KafkaBus {
+    producer: FutureProducer,
+}
+
+pub struct KafkaBusConsumer {
+    consumer: StreamConsumer,
+}
+
+impl KafkaBus {
+    // overrides are extra librdkafka producer settings, e.g. compression.type
+    pub fn new(overrides: &[(&str, String)]) -> Result<KafkaBus, AppError> {
+        let producer = config::kafka::get_kafka_producer_with(overrides)
+            .map_err(|error| AppError::Other(format!("producer creation failed: {}", error)))?;
+        Ok(KafkaBus { producer })
+    }
+
+    pub async fn send(
+        &self,
+        topic: &str,
+        key: Option<&[u8]>,
+        payload: &[u8],
+        headers: &[(&str, &[u8])],
+    ) -> Result<(), AppError> {
+        let mut owned_headers = OwnedHeaders::new();
+        for (key, value) in headers {
+            owned_headers = owned_headers.insert(Header { key, value: Some(*value) });
+        }
+
+        let mut record = FutureRecord::<[u8], [u8]>::to(topic)
+            .payload(payload)
+            .headers(owned_headers);
+        if let Some(key) = key {
+            record = record.key(key);
+        }
+
+        self.producer
+            .send(record, Duration::from_secs(0))
+            .await
+            .map_err(|(error, _)| AppError::Other(format!("delivery failed: {}", error)))?;
+        Ok(())
+    }
+
+    pub fn consumer(&self, group_id: &str, topic: &str) -> Result<KafkaBusConsumer, AppError> {
+        let consumer = config::kafka::get_kafka_consumer(group_id)
+            .map_err(|error| AppError::Other(format!("consumer creation failed: {}", error)))?;
+        consumer
+            .subscribe(&[topic])
+            .map_err(|error| AppError::Other(format!("subscribe failed: {}", error)))?;
+        Ok(KafkaBusConsumer { consumer })
+    }
+
+    // assigned at the current end of every partition, blocks while fetching watermarks
+    pub fn consumer_at_end(&self, group_id: &str, topic: &str) -> Result<KafkaBusConsumer, AppError> {
+        let consumer = config::kafka::get_kafka_consumer(group_id)
+            .map_err(|error| AppError::Other(format!("consumer creation failed: {}", error)))?;
+
+        let mut assignment = TopicPartitionList::new();
+        for partition in partition_ids(&consumer, topic)? {
+            let (_, high) = consumer
+                .fetch_watermarks(topic, partition, Duration::from_secs(10))
+                .map_err(|error| AppError::Other(format!("fetch watermarks failed: {}", error)))?;
+            assignment
+                .add_partition_offset(topic, partition, Offset::Offset(high))
+                .map_err(|error| AppError::Other(format!("assign offset failed: {}", error)))?;
+        }
+        consumer
+            .assign(&assignment)
+            .map_err(|error| AppError::Other(format!("assign failed: {}", error)))?;
+        Ok(KafkaBusConsumer { consumer })
+    }
+
+    // blocks while fetching the topic metadata
+    pub fn partition_count(&self, topic: &str) -> Result<usize, AppError> {
+        let metadata = self
+            .producer
+            .client()
+            .fetch_metadata(Some(topic), Duration::from_secs(10))
+            .map_err(|error| AppError::Other(format!("fetch metadata failed: {}", error)))?;
+        Ok(metadata.topics().iter().map(|metadata_topic| metadata_topic.partitions().len()).sum())
+    }
+
+    // committed offsets of the group against the current end of every partition
+    pub fn lag(&self, group_id: &str, topic: &str) -> Result<Vec<PartitionLag>, AppError> {
+        let consumer = config::kafka::get_kafka_consumer(group_id)
+            .map_err(|error| AppError::Other(format!("consumer creation failed: {}", error)))?;
+
+        let mut partitions = TopicPartitionList::new();
+        for partition in partition_ids(&consumer, topic)? {
+            partitions.add_partition(topic, partition);
+        }
+        let committed = consumer
+            .committed_offsets(partitions, Duration::from_secs(10))
+            .map_err(|error| AppError::Other(format!("fetch committed offsets failed: {}", error)))?;
+
+        let mut lag = Vec::new();
+        for element in committed.elements() {
+            let (low, high) = consumer
+                .fetch_watermarks(topic, element.partition(), Duration::from_secs(10))
+                .map_err(|error| AppError::Other(format!("fetch watermarks failed: {}", error)))?;
+            let committed_offset = match element.offset() {
+                Offset::Offset(offset) => Some(offset),
+                _ => None,
+            };
+            lag.push(PartitionLag {
+                partition: element.partition(),
+                committed_offset,
+                high_watermark: high,
+                lag: high - committed_offset.unwrap_or(low),
+            });
+        }
+        Ok(lag)
+    }
+}
+
+impl KafkaBusConsumer {
+    pub async fn recv(&self) -> Result<BusMessage, AppError> {
+        let message = self
+            .consumer
+            .recv()
+            .await
+            .map_err(|error| AppError::Other(format!("kafka error: {}", error)))?;
+
+        let headers = message
+            .headers()
+            .map(|headers| {
+                headers
+                    .iter()
+                    .map(|header| (header.key.to_string(), header.value.unwrap_or(&[]).to_vec()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(BusMessage {
+            topic: message.topic().to_string(),
+            partition: message.partition(),
+            offset: message.offset(),
+            key: message.key().map(<[u8]>::to_vec),
+            payload: message.payload().unwrap_or(&[]).to_vec(),
+            headers,
+        })
+    }
+
+    pub fn commit(&self, message: &BusMessage) -> Result<(), AppError> {
+        // the committed offset is the next one to read
+        let mut offsets = TopicPartitionList::new();
+        offsets
+            .add_partition_offset(&message.topic, message.partition, Offset::Offset(message.offset + 1))
+            .map_err(|error| AppError::Other(format!("commit failed: {}", error)))?;
+        self.consumer
+            .commit(&offsets, CommitMode::Async)
+            .map_err(|error| AppError::Other(format!("commit failed: {}", error)))
+    }
+
+    pub fn seek(&self, message: &BusMessage) -> Result<(), AppError> {
+        self.consumer
+            .seek(
+                &message.topic,
+                message.partition,
+                Offset::Offset(message.offset),
+                Duration::from_secs(10),
+            )
+            .map_err(|error| AppError::Other(format!("seek failed: {}", error)))
+    }
+}
+
+fn partition_ids(consumer: &StreamConsumer, topic: &str) -> Result<Vec<i32>, AppError> {
+    let metadata = consumer
+        .fetch_metadata(Some(topic), Duration::from_secs(10))
+        .map_err(|error| AppError::Other(format!("fetch metadata failed: {}", error)))?;
+    Ok(metadata
+        .topics()
+        .iter()
+        .flat_map(|metadata_topic| metadata_topic.partitions().iter().map(|partition| partition.id()))
+        .collect())
+}
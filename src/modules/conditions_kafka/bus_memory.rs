@@ -0,0 +1,335 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::Notify;
+
+use crate::{
+    config::environment::CONFIG,
+    dto::app_error::AppError,
+    modules::conditions_kafka::{bus::BusMessage, schema::PartitionLag},
+};
+
+// topics are kept in process for the lifetime of the server, nothing is ever truncated,
+// which is fine for tests and laptop runs but not for long ingest sessions
+#[derive(Clone)]
+pub struct MemoryBus {
+    inner: Arc<MemoryBusInner>,
+}
+
+struct MemoryBusInner {
+    partitions: usize,
+    topics: Mutex<HashMap<String, Vec<Vec<BusMessage>>>>,
+    // keyed by (group, topic), consumers of one group share its positions like a kafka
+    // consumer group shares partitions
+    groups: Mutex<HashMap<(String, String), GroupState>>,
+    // sends to these fail like a send to a kafka broker that is down
+    closed_topics: Mutex<HashSet<String>>,
+    sent: Notify,
+}
+
+struct GroupState {
+    positions: Vec<i64>,
+    committed: Vec<Option<i64>>,
+}
+
+pub struct MemoryBusConsumer {
+    bus: MemoryBus,
+    group_id: String,
+    topic: String,
+    // partition to look at first, rotated so one busy partition does not starve the others
+    next_partition: usize,
+}
+
+impl MemoryBus {
+    pub fn new(partitions: usize) -> MemoryBus {
+        MemoryBus {
+            inner: Arc::new(MemoryBusInner {
+                partitions: partitions.max(1),
+                topics: Mutex::new(HashMap::new()),
+                groups: Mutex::new(HashMap::new()),
+                closed_topics: Mutex::new(HashSet::new()),
+                sent: Notify::new(),
+            }),
+        }
+    }
+
+    pub fn send(
+        &self,
+        topic: &str,
+        key: Option<&[u8]>,
+        payload: &[u8],
+        headers: &[(&str, &[u8])],
+    ) -> Result<(), AppError> {
+        if self.inner.closed_topics.lock().unwrap().contains(topic) {
+            return Err(AppError::Other(format!("memory bus topic {} is closed", topic)));
+        }
+
+        let partition = match key {
+            Some(key) => {
+                let mut hasher = DefaultHasher::new();
+                key.hash(&mut hasher);
+                (hasher.finish() % self.inner.partitions as u64) as usize
+            }
+            None => 0,
+        };
+
+        let mut topics = self.inner.topics.lock().unwrap();
+        let partitions = topics
+            .entry(topic.to_string())
+            .or_insert_with(|| vec![Vec::new(); self.inner.partitions]);
+        let offset = partitions[partition].len() as i64;
+        partitions[partition].push(BusMessage {
+            topic: topic.to_string(),
+            partition: partition as i32,
+            offset,
+            key: key.map(<[u8]>::to_vec),
+            payload: payload.to_vec(),
+            headers: headers
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_vec()))
+                .collect(),
+        });
+        drop(topics);
+
+        self.inner.sent.notify_waiters();
+        Ok(())
+    }
+
+    // lets tests exercise the failure paths of a send
+    pub fn close_topic(&self, topic: &str) {
+        self.inner.closed_topics.lock().unwrap().insert(topic.to_string());
+    }
+
+    // a new group starts at the beginning or the end depending on KAFKA_AUTO_OFFSET_RESET,
+    // `at_end` always starts it at the end
+    pub fn consumer(&self, group_id: &str, topic: &str, at_end: bool) -> MemoryBusConsumer {
+        let high_watermarks = self.high_watermarks(topic);
+        let mut groups = self.inner.groups.lock().unwrap();
+        let group = groups
+            .entry((group_id.to_string(), topic.to_string()))
+            .or_insert_with(|| GroupState {
+                positions: vec![0; self.inner.partitions],
+                committed: vec![None; self.inner.partitions],
+            });
+        if at_end || (group.committed.iter().all(Option::is_none) && CONFIG.kafka_auto_offset_reset == "latest") {
+            group.positions = high_watermarks;
+        }
+
+        MemoryBusConsumer {
+            bus: self.clone(),
+            group_id: group_id.to_string(),
+            topic: topic.to_string(),
+            next_partition: 0,
+        }
+    }
+
+    // every topic has the partition count the bus was created with
+    pub fn partition_count(&self) -> usize {
+        self.inner.partitions
+    }
+
+    pub fn lag(&self, group_id: &str, topic: &str) -> Vec<PartitionLag> {
+        let high_watermarks = self.high_watermarks(topic);
+        let groups = self.inner.groups.lock().unwrap();
+        let group = groups.get(&(group_id.to_string(), topic.to_string()));
+
+        high_watermarks
+            .into_iter()
+            .enumerate()
+            .map(|(partition, high)| {
+                let committed_offset = group.and_then(|group| group.committed[partition]);
+                PartitionLag {
+                    partition: partition as i32,
+                    committed_offset,
+                    high_watermark: high,
+                    lag: high - committed_offset.unwrap_or(0),
+                }
+            })
+            .collect()
+    }
+
+    fn high_watermarks(&self, topic: &str) -> Vec<i64> {
+        let topics = self.inner.topics.lock().unwrap();
+        match topics.get(topic) {
+            Some(partitions) => partitions.iter().map(|messages| messages.len() as i64).collect(),
+            None => vec![0; self.inner.partitions],
+        }
+    }
+}
+
+impl MemoryBusConsumer {
+    pub async fn recv(&mut self) -> BusMessage {
+        loop {
+            // registered before checking so a send in between is not missed
+            let bus = self.bus.clone();
+            let sent = bus.inner.sent.notified();
+            tokio::pin!(sent);
+            sent.as_mut().enable();
+
+            if let Some(message) = self.try_next() {
+                return message;
+            }
+            sent.await;
+        }
+    }
+
+    pub fn commit(&self, message: &BusMessage) {
+        let mut groups = self.bus.inner.groups.lock().unwrap();
+        if let Some(group) = groups.get_mut(&(self.group_id.clone(), self.topic.clone())) {
+            let committed = &mut group.committed[message.partition as usize];
+            *committed = Some(committed.unwrap_or(0).max(message.offset + 1));
+        }
+    }
+
+    pub fn seek(&self, message: &BusMessage) {
+        let mut groups = self.bus.inner.groups.lock().unwrap();
+        if let Some(group) = groups.get_mut(&(self.group_id.clone(), self.topic.clone())) {
+            group.positions[message.partition as usize] = message.offset;
+        }
+    }
+
+    fn try_next(&mut self) -> Option<BusMessage> {
+        let topics = self.bus.inner.topics.lock().unwrap();
+        let partitions = topics.get(&self.topic)?;
+        let mut groups = self.bus.inner.groups.lock().unwrap();
+        let group = groups.get_mut(&(self.group_id.clone(), self.topic.clone()))?;
+
+        let partition_count = partitions.len();
+        for step in 0..partition_count {
+            let partition = (self.next_partition + step) % partition_count;
+            let position = group.positions[partition] as usize;
+            if let Some(message) = partitions[partition].get(position) {
+                group.positions[partition] += 1;
+                self.next_partition = (partition + 1) % partition_count;
+                return Some(message.clone());
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::time::timeout;
+
+    use super::*;
+
+    const TOPIC: &str = "conditions-test";
+
+    async fn recv_or_none(consumer: &mut MemoryBusConsumer) -> Option<BusMessage> {
+        timeout(Duration::from_millis(50), consumer.recv()).await.ok()
+    }
+
+    #[tokio::test]
+    async fn offsets_grow_per_partition() {
+        let bus = MemoryBus::new(4);
+        for payload in [b"a", b"b", b"c"] {
+            bus.send(TOPIC, Some(b"same-key"), payload, &[]).unwrap();
+        }
+        bus.send(TOPIC, None, b"unkeyed", &[("format", b"json_array")]).unwrap();
+
+        let mut consumer = bus.consumer("group", TOPIC, false);
+        let mut messages = Vec::new();
+        while let Some(message) = recv_or_none(&mut consumer).await {
+            messages.push(message);
+        }
+
+        let keyed: Vec<&BusMessage> = messages.iter().filter(|m| m.key.is_some()).collect();
+        assert_eq!(keyed.len(), 3);
+        assert!(keyed.iter().all(|m| m.partition == keyed[0].partition));
+        assert_eq!(keyed.iter().map(|m| m.offset).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(keyed.iter().map(|m| m.payload.clone()).collect::<Vec<_>>(), vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()]);
+
+        let unkeyed = messages.iter().find(|m| m.key.is_none()).unwrap();
+        assert_eq!(unkeyed.partition, 0);
+        assert_eq!(unkeyed.header("format"), Some("json_array"));
+    }
+
+    #[tokio::test]
+    async fn commit_moves_lag_and_seek_redelivers() {
+        let bus = MemoryBus::new(1);
+        bus.send(TOPIC, Some(b"key"), b"first", &[]).unwrap();
+        bus.send(TOPIC, Some(b"key"), b"second", &[]).unwrap();
+
+        let mut consumer = bus.consumer("group", TOPIC, false);
+        assert_eq!(bus.lag("group", TOPIC)[0].lag, 2);
+
+        let first = consumer.recv().await;
+        consumer.commit(&first);
+        let lag = &bus.lag("group", TOPIC)[0];
+        assert_eq!(lag.committed_offset, Some(1));
+        assert_eq!(lag.lag, 1);
+
+        // a failed write rewinds, the same message comes back instead of the next one
+        let second = consumer.recv().await;
+        consumer.seek(&second);
+        let again = consumer.recv().await;
+        assert_eq!(again.offset, second.offset);
+        assert_eq!(again.payload, b"second");
+
+        // committing an older message never moves the committed offset back
+        consumer.commit(&again);
+        consumer.commit(&first);
+        assert_eq!(bus.lag("group", TOPIC)[0].committed_offset, Some(2));
+        assert_eq!(bus.lag("group", TOPIC)[0].lag, 0);
+    }
+
+    #[tokio::test]
+    async fn consumers_of_a_group_share_partitions() {
+        let bus = MemoryBus::new(4);
+        for index in 0..20 {
+            bus.send(TOPIC, Some(format!("key-{}", index).as_bytes()), b"payload", &[]).unwrap();
+        }
+
+        let mut first = bus.consumer("group", TOPIC, false);
+        let mut second = bus.consumer("group", TOPIC, false);
+        let mut seen = Vec::new();
+        loop {
+            let message = match recv_or_none(&mut first).await {
+                Some(message) => Some(message),
+                None => recv_or_none(&mut second).await,
+            };
+            let Some(message) = message else {
+                break;
+            };
+            seen.push((message.partition, message.offset));
+            std::mem::swap(&mut first, &mut second);
+        }
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), 20);
+
+        // another group keeps its own positions and reads everything again
+        let mut other = bus.consumer("other-group", TOPIC, false);
+        let mut other_count = 0;
+        while recv_or_none(&mut other).await.is_some() {
+            other_count += 1;
+        }
+        assert_eq!(other_count, 20);
+    }
+
+    #[tokio::test]
+    async fn at_end_skips_earlier_messages() {
+        let bus = MemoryBus::new(2);
+        bus.send(TOPIC, Some(b"key"), b"before", &[]).unwrap();
+
+        let mut consumer = bus.consumer("group", TOPIC, true);
+        assert!(recv_or_none(&mut consumer).await.is_none());
+
+        // a consumer waiting in recv is woken by the send
+        let sender = bus.clone();
+        let handle = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            sender.send(TOPIC, Some(b"key"), b"after", &[]).unwrap();
+        });
+        let message = timeout(Duration::from_secs(1), consumer.recv()).await.unwrap();
+        handle.await.unwrap();
+        assert_eq!(message.payload, b"after");
+        assert_eq!(message.offset, 1);
+    }
+}
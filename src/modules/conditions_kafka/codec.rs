@@ -0,0 +1,326 @@
+use std::{
+    borrow::Cow,
+    io::{BufRead, Cursor},
+};
+
+use apache_avro::{
+    Schema, reader::datum::GenericDatumReader, writer::datum::GenericDatumWriter,
+};
+use chrono::{DateTime, NaiveDateTime};
+use lazy_static::lazy_static;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+
+use crate::{dto::app_error::AppError, modules::conditions_kafka::schema::Conditions};
+
+// kafka header carrying the payload format, messages without it are a JSON array
+pub const HEADER_PAYLOAD_FORMAT: &str = "payload_format";
+
+const AVRO_SCHEMA: &str = r#"{
+    "type": "array",
+    "items": {
+        "type": "record",
+        "name": "Conditions",
+        "fields": [
+            {"name": "id", "type": "string"},
+            {"name": "created_on", "type": "string"},
+            {"name": "location", "type": "string"},
+            {"name": "temperature", "type": ["null", "double"]},
+            {"name": "humidity", "type": ["null", "double"]}
+        ]
+    }
+}"#;
+
+lazy_static! {
+    static ref AVRO_CONDITIONS_SCHEMA: Schema =
+        Schema::parse_str(AVRO_SCHEMA).expect("invalid conditions avro schema");
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadFormat {
+    #[default]
+    JsonArray,
+    Ndjson,
+    JsonPerRecord,
+    MessagePack,
+    Postcard,
+    Avro,
+    Protobuf,
+}
+
+// the row as every serde format writes it, kept apart from Conditions so the payloads do not
+// follow the api's local time display, created_on is UTC at microsecond precision like the
+// protobuf field, so a row decodes to the same (id, created_on) whatever format carried it
+#[derive(Deserialize, Serialize)]
+#[serde(rename = "Conditions")]
+struct ConditionsRecord<'a> {
+    id: Cow<'a, str>,
+    #[serde(with = "created_on_utc")]
+    created_on: NaiveDateTime,
+    location: Cow<'a, str>,
+    temperature: Option<f64>,
+    humidity: Option<f64>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ConditionsProto {
+    #[prost(string, tag = "1")]
+    id: String,
+    // microseconds since the unix epoch
+    #[prost(int64, tag = "2")]
+    created_on: i64,
+    #[prost(string, tag = "3")]
+    location: String,
+    #[prost(double, optional, tag = "4")]
+    temperature: Option<f64>,
+    #[prost(double, optional, tag = "5")]
+    humidity: Option<f64>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ConditionsBatchProto {
+    #[prost(message, repeated, tag = "1")]
+    conditions: Vec<ConditionsProto>,
+}
+
+impl PayloadFormat {
+    pub const ALL: [PayloadFormat; 7] = [
+        PayloadFormat::JsonArray,
+        PayloadFormat::Ndjson,
+        PayloadFormat::JsonPerRecord,
+        PayloadFormat::MessagePack,
+        PayloadFormat::Postcard,
+        PayloadFormat::Avro,
+        PayloadFormat::Protobuf,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PayloadFormat::JsonArray => "json_array",
+            PayloadFormat::Ndjson => "ndjson",
+            PayloadFormat::JsonPerRecord => "json_per_record",
+            PayloadFormat::MessagePack => "message_pack",
+            PayloadFormat::Postcard => "postcard",
+            PayloadFormat::Avro => "avro",
+            PayloadFormat::Protobuf => "protobuf",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<PayloadFormat> {
+        PayloadFormat::ALL.into_iter().find(|format| format.name() == name)
+    }
+
+    // one payload per kafka message, only json_per_record splits a batch into several
+    pub fn encode(&self, data: &[Conditions]) -> Result<Vec<Vec<u8>>, AppError> {
+        let records: Vec<ConditionsRecord> = data.iter().map(ConditionsRecord::from).collect();
+        let payload = match self {
+            PayloadFormat::JsonArray => serde_json::to_vec(&records).map_err(encode_error)?,
+            PayloadFormat::Ndjson => {
+                let mut payload = Vec::new();
+                for record in &records {
+                    serde_json::to_writer(&mut payload, record).map_err(encode_error)?;
+                    payload.push(b'\n');
+                }
+                payload
+            }
+            PayloadFormat::JsonPerRecord => {
+                return records
+                    .iter()
+                    .map(|record| serde_json::to_vec(record).map_err(encode_error))
+                    .collect();
+            }
+            PayloadFormat::MessagePack => rmp_serde::to_vec(&records).map_err(encode_error)?,
+            PayloadFormat::Postcard => postcard::to_allocvec(&records).map_err(encode_error)?,
+            PayloadFormat::Avro => GenericDatumWriter::builder(&AVRO_CONDITIONS_SCHEMA)
+                .build()
+                .and_then(|writer| writer.write_ser_to_vec(&records))
+                .map_err(encode_error)?,
+            PayloadFormat::Protobuf => ConditionsBatchProto {
+                conditions: data.iter().map(ConditionsProto::from).collect(),
+            }
+            .encode_to_vec(),
+        };
+        Ok(vec![payload])
+    }
+
+    pub fn decode(&self, payload: &[u8]) -> Result<Vec<Conditions>, AppError> {
+        let records: Vec<ConditionsRecord> = match self {
+            PayloadFormat::JsonArray => serde_json::from_slice(payload).map_err(decode_error)?,
+            PayloadFormat::Ndjson => payload
+                .lines()
+                .filter(|line| !matches!(line, Ok(line) if line.is_empty()))
+                .map(|line| {
+                    let line = line.map_err(decode_error)?;
+                    serde_json::from_str(&line).map_err(decode_error)
+                })
+                .collect::<Result<_, _>>()?,
+            PayloadFormat::JsonPerRecord => {
+                vec![serde_json::from_slice(payload).map_err(decode_error)?]
+            }
+            PayloadFormat::MessagePack => rmp_serde::from_slice(payload).map_err(decode_error)?,
+            PayloadFormat::Postcard => postcard::from_bytes(payload).map_err(decode_error)?,
+            PayloadFormat::Avro => GenericDatumReader::builder(&AVRO_CONDITIONS_SCHEMA)
+                .build()
+                .and_then(|reader| reader.read_deser(&mut Cursor::new(payload)))
+                .map_err(decode_error)?,
+            PayloadFormat::Protobuf => {
+                let batch = ConditionsBatchProto::decode(payload).map_err(decode_error)?;
+                return batch.conditions.into_iter().map(Conditions::try_from).collect();
+            }
+        };
+        Ok(records.into_iter().map(Conditions::from).collect())
+    }
+}
+
+impl<'a> From<&'a Conditions> for ConditionsRecord<'a> {
+    fn from(conditions: &'a Conditions) -> Self {
+        ConditionsRecord {
+            id: Cow::Borrowed(&conditions.id),
+            created_on: conditions.created_on,
+            location: Cow::Borrowed(&conditions.location),
+            temperature: conditions.temperature,
+            humidity: conditions.humidity,
+        }
+    }
+}
+
+impl From<ConditionsRecord<'_>> for Conditions {
+    fn from(record: ConditionsRecord<'_>) -> Self {
+        Conditions {
+            id: record.id.into_owned(),
+            created_on: record.created_on,
+            location: record.location.into_owned(),
+            temperature: record.temperature,
+            humidity: record.humidity,
+        }
+    }
+}
+
+impl From<&Conditions> for ConditionsProto {
+    fn from(conditions: &Conditions) -> Self {
+        ConditionsProto {
+            id: conditions.id.clone(),
+            created_on: conditions.created_on.and_utc().timestamp_micros(),
+            location: conditions.location.clone(),
+            temperature: conditions.temperature,
+            humidity: conditions.humidity,
+        }
+    }
+}
+
+impl TryFrom<ConditionsProto> for Conditions {
+    type Error = AppError;
+
+    fn try_from(proto: ConditionsProto) -> Result<Self, Self::Error> {
+        let created_on = DateTime::from_timestamp_micros(proto.created_on)
+            .ok_or(AppError::Other(format!("invalid created_on: {}", proto.created_on)))?;
+        Ok(Conditions {
+            id: proto.id,
+            created_on: created_on.naive_utc(),
+            location: proto.location,
+            temperature: proto.temperature,
+            humidity: proto.humidity,
+        })
+    }
+}
+
+// RFC 3339 in UTC with microseconds, the fraction beyond that is dropped on encode as the
+// protobuf field drops it. Payloads written before this carried the api's local time at
+// second precision and are still read
+pub(crate) mod created_on_utc {
+    use chrono::{DateTime, Local, NaiveDateTime, SecondsFormat, TimeZone};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error};
+
+    const LEGACY_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+    pub fn serialize<S: Serializer>(time: &NaiveDateTime, serializer: S) -> Result<S::Ok, S::Error> {
+        time.and_utc()
+            .to_rfc3339_opts(SecondsFormat::Micros, true)
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveDateTime, D::Error> {
+        let time: String = Deserialize::deserialize(deserializer)?;
+        if let Ok(datetime) = DateTime::parse_from_rfc3339(&time) {
+            return Ok(datetime.naive_utc());
+        }
+        let local = NaiveDateTime::parse_from_str(&time, LEGACY_FORMAT).map_err(D::Error::custom)?;
+        Local
+            .from_local_datetime(&local)
+            .earliest()
+            .map(|datetime| datetime.naive_utc())
+            .ok_or_else(|| D::Error::custom(format!("invalid local created_on: {}", time)))
+    }
+}
+
+fn encode_error(error: impl std::fmt::Display) -> AppError {
+    AppError::Other(format!("encode failed: {}", error))
+}
+
+fn decode_error(error: impl std::fmt::Display) -> AppError {
+    AppError::Other(format!("decode failed: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, SubsecRound};
+
+    use super::*;
+
+    fn conditions(created_on_micros: i64) -> Vec<Conditions> {
+        let created_on = DateTime::from_timestamp_micros(created_on_micros).unwrap().naive_utc();
+        vec![
+            Conditions {
+                id: "1f0c6a52-7f55-4ab4-a8c3-5b0d5a8c7d11".to_string(),
+                created_on,
+                location: "codec-test".to_string(),
+                temperature: Some(31.25),
+                humidity: None,
+            },
+            Conditions {
+                id: "2f0c6a52-7f55-4ab4-a8c3-5b0d5a8c7d12".to_string(),
+                created_on: created_on + chrono::Duration::microseconds(1),
+                location: String::new(),
+                temperature: None,
+                humidity: Some(99.5),
+            },
+        ]
+    }
+
+    fn round_trip(format: PayloadFormat, data: &[Conditions]) -> Vec<Conditions> {
+        let mut decoded = Vec::new();
+        for payload in format.encode(data).unwrap() {
+            decoded.extend(format.decode(&payload).unwrap());
+        }
+        decoded
+    }
+
+    #[test]
+    fn every_format_round_trips() {
+        let data = conditions(1_760_000_000_123_456);
+        for format in PayloadFormat::ALL {
+            assert_eq!(round_trip(format, &data), data, "{}", format.name());
+        }
+    }
+
+    // nanoseconds are dropped the same way by every format, so replays stay duplicates
+    #[test]
+    fn every_format_truncates_to_microseconds() {
+        let mut data = conditions(0);
+        let created_on = DateTime::from_timestamp(1_760_000_000, 123_456_789).unwrap().naive_utc();
+        data[0].created_on = created_on;
+        for format in PayloadFormat::ALL {
+            let decoded = round_trip(format, &data);
+            assert_eq!(decoded[0].created_on, created_on.trunc_subsecs(6), "{}", format.name());
+        }
+    }
+
+    #[test]
+    fn json_created_on_is_utc() {
+        let data = conditions(1_760_000_000_123_456);
+        let payload = PayloadFormat::JsonArray.encode(&data[..1]).unwrap().remove(0);
+        let json: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(json[0]["created_on"], "2025-10-09T08:53:20.123456Z");
+    }
+}
@@ -1,10 +1,9 @@
 use std::{sync::Arc, time::Duration};
 
-use axum::{http::StatusCode, routing::get, Extension, Json, Router};
-use futures_util::StreamExt;
-use rdkafka::{consumer::{Consumer, StreamConsumer}, producer::{FutureProducer, FutureRecord}, ClientConfig, Message};
+use axum::{extract::Query, http::StatusCode, routing::get, Extension, Json, Router};
+use tokio::time::timeout;
 
-use crate::{dto::{app_error::AppError, app_response::AppResponse}, modules::conditions_kafka::schema::Conditions, state::AppState};
+use crate::{config::environment::CONFIG, dto::{app_error::AppError, app_response::AppResponse}, modules::conditions_kafka::schema::{Conditions, ConsumerRequest}, state::AppState};
 
 pub fn new() -> Router {
     Router::new()
@@ -15,22 +14,15 @@ pub fn new() -> Router {
 pub async fn producer(
     Extension(_state): Extension<Arc<AppState>>,
 ) -> Result<(StatusCode, Json<AppResponse<Vec<Conditions>>>), AppError> {
-    let producer: FutureProducer = ClientConfig::new()
-        .set("bootstrap.servers", "localhost:29092")
-        .set("message.timeout.ms", "5000")
-        .create()
-        .expect("Producer creation error");
-
-    let topic = "my-topic";
+    let topic = &CONFIG.kafka_topic;
     let key = "my_key";
     let payload = "Hello from Rust Kafka!";
 
-    let record = FutureRecord::to(topic).key(key).payload(payload);
-
-    match producer.send(record, Duration::from_secs(0)).await {
-        Ok(delivery) => println!("Delivered message to Kafka: {:?}", delivery),
-        Err((e, _)) => eprintln!("Failed to deliver message: {:?}", e),
-    }
+    _state
+        .message_bus
+        .send(topic, Some(key.as_bytes()), payload.as_bytes(), &[])
+        .await?;
+    tracing::info!(topic = %topic, "delivered message");
 
     let status_code = StatusCode::OK;
     return Ok((
@@ -42,47 +34,37 @@ pub async fn producer(
     ));
 }
 pub async fn consumer(
+    Query(consumer_request): Query<ConsumerRequest>,
     Extension(_state): Extension<Arc<AppState>>,
 ) -> Result<(StatusCode, Json<AppResponse<Vec<Conditions>>>), AppError> {
-
-    let consumer: StreamConsumer = ClientConfig::new()
-        .set("bootstrap.servers", "localhost:29092")
-        .set("group.id", "my-consumer-group")
-        // Start consuming from the beginning of the topic if no offset is stored
-        .set("auto.offset.reset", "earliest") 
-        .create()
-        .expect("Consumer creation error");
-
-    consumer
-        .subscribe(&["my-topic"])
-        .expect("Can't subscribe to topic");
-
-    println!("Consumer started. Waiting for messages on topic '{}'...", "my-topic");
-
-    // Message stream: use .next() to wait for the next message
-    // If you prefer a loop with explicit polling, use `consumer.recv().await`
-    let mut message_stream = consumer.stream(); 
-
-    while let Some(message_result) = message_stream.next().await {
+    // one request at a time reads from the shared consumer
+    let mut consumer = _state.bus_consumer.lock().await;
+
+    tracing::info!(topic = %CONFIG.kafka_topic, "consumer started, waiting for messages");
+
+    // stop after max_messages or when the topic has been idle for idle_timeout_ms
+    let max_messages = consumer_request.max_messages.unwrap_or(usize::MAX);
+    let idle_timeout = Duration::from_millis(consumer_request.idle_timeout_ms.unwrap_or(5000));
+    let mut total_messages = 0;
+
+    while total_messages < max_messages {
+        let message_result = match timeout(idle_timeout, consumer.recv()).await {
+            Ok(message_result) => message_result,
+            Err(_) => break,
+        };
+        total_messages += 1;
         match message_result {
             Ok(message) => {
-                let payload = match message.payload_view::<str>() {
-                    Some(Ok(s)) => s,
-                    _ => "N/A",
-                };
-                let key = match message.key_view::<str>() {
-                    Some(Ok(s)) => s,
-                    _ => "N/A",
-                };
+                let payload = std::str::from_utf8(&message.payload).unwrap_or("N/A");
+                let key = message.key_str().unwrap_or("N/A");
 
-                println!("📦 Received message | Key: {} | Payload: {} | Partition: {} | Offset: {}",
-                    key, payload, message.partition(), message.offset());
+                tracing::debug!(key, payload, partition = message.partition, offset = message.offset, "received message");
 
-                // Manually commit offset for at-least-once processing (optional)
-                // consumer.commit_message(&message, CommitMode::Async).unwrap();
+                // auto commit is disabled, commit once the message has been handled
+                consumer.commit(&message)?;
             }
             Err(e) => {
-                eprintln!("❌ Kafka error: {}", e);
+                tracing::error!(error = ?e, "kafka error");
             }
         }
     }
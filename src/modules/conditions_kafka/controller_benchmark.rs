@@ -1,44 +1,81 @@
 use std::{sync::Arc, time::Duration};
 
-use axum::{Extension, Json, Router, extract::Path, http::StatusCode, routing::get};
-use futures_util::StreamExt;
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, Query},
+    http::StatusCode,
+    routing::{get, post},
+};
+use chrono::SubsecRound;
 use rdkafka::{
-    ClientConfig, Message,
-    consumer::{Consumer, StreamConsumer},
-    producer::{FutureProducer, FutureRecord},
+    error::{KafkaError, RDKafkaErrorCode},
+    message::{Header, OwnedHeaders},
+    producer::FutureRecord,
 };
-use tokio::time::Instant;
+use tokio::time::{Instant, timeout};
+use uuid::Uuid;
 
 use crate::{
+    config::{self, environment::CONFIG},
     dto::{app_error::AppError, app_response::AppResponse},
-    modules::conditions_kafka::{
-        repository,
-        schema::{Conditions, ConditionsRequest},
+    modules::{
+        conditions_kafka::{
+            bus::{BusConsumer, MessageBus, MessageBusKind},
+            bus_kafka::KafkaBus,
+            codec::{HEADER_PAYLOAD_FORMAT, PayloadFormat},
+            ingest::{self, IngestOutcome, SinkConnection, payload_format},
+            repository,
+            schema::{
+                Conditions, ConditionsRequest, ConsumerReport, ConsumerRequest, EndToEndReport,
+                FormatReport, ProducerRequest, ProducerTuning, ProducerTuningReport, Sink,
+            },
+        },
+        conditions_tiberius,
     },
     state::AppState,
-    util,
+    util::{
+        self,
+        statistics::{LatencySummary, per_second},
+    },
 };
 
+const HEADER_PRODUCED_AT: &str = "produced_at_us";
+const HEADER_RUN_ID: &str = "run_id";
+const STATISTICS_INTERVAL_MS: u64 = 100;
+
 pub fn new() -> Router {
     Router::new()
         .route("/producer/{size}", get(producer))
+        .route("/producer/tuning/{size}", get(producer_tuning))
+        .route("/producer/matrix/{size}", post(producer_matrix))
         .route("/consumer", get(consumer))
+        .route("/end-to-end/{size}", get(end_to_end))
+        .route("/formats/{size}", get(formats))
 }
 
 pub async fn producer(
     Path(size): Path<i32>,
+    Query(producer_request): Query<ProducerRequest>,
+    Query(tuning): Query<ProducerTuning>,
     Extension(_state): Extension<Arc<AppState>>,
 ) -> Result<(StatusCode, Json<AppResponse<Vec<Conditions>>>), AppError> {
-    let producer: FutureProducer = ClientConfig::new()
-        .set("bootstrap.servers", "localhost:29092")
-        .set("message.timeout.ms", "5000")
-        .set("message.max.bytes", "10000000")
-        .set("message.copy.max.bytes", "65535")
-        .set("receive.message.max.bytes", "100000000")
-        .create()
-        .expect("Producer creation error");
-
-    let topic = "my-topic";
+    // the shared bus unless this run asks for its own kafka producer settings
+    let overrides = tuning.to_overrides();
+    let tuned_bus: MessageBus;
+    let message_bus = if overrides.is_empty() {
+        &_state.message_bus
+    } else {
+        // the memory bus has no producer settings, tuning it would need a broker
+        if _state.message_bus.kind() == MessageBusKind::Memory {
+            return Err(AppError::Other("producer tuning needs MESSAGE_BUS=kafka".to_string()));
+        }
+        tuned_bus = MessageBus::Kafka(KafkaBus::new(&overrides)?);
+        &tuned_bus
+    };
+
+    let topic = &CONFIG.kafka_topic;
+    let batch_size = CONFIG.kafka_batch_size;
+    let format = producer_request.format.unwrap_or_default();
     let key = "conditions";
 
     let mut durations = String::new();
@@ -54,55 +91,23 @@ pub async fn producer(
 
             let _conditions_request = ConditionsRequest {
                 id: None,
-                location: location,
+                location,
                 temperature: Some(temperature),
                 humidity: Some(humidity),
             };
             let new_conditions = Conditions::from_create_request(_conditions_request);
             conditions_list.push(new_conditions);
 
-            if conditions_list.len() == 5000 {
-                let payload_bytes: Vec<u8> = match serde_json::to_vec(&conditions_list) {
-                    Ok(v) => v,
-                    Err(e) => {
-                        eprintln!("❌ Failed to serialize struct to bytes: {}", e);
-                        continue;
-                    }
-                };
-                let key_final = format!("{}_{}", key, c);
-                // println!("size: {}", payload_bytes.len());
-                let record = FutureRecord::to(topic)
-                    .key(&key_final)
-                    .payload(&payload_bytes);
-
-                let _result = producer.send(record, Duration::from_secs(0)).await;
-                match _result {
-                    Err((e, _)) => eprintln!("Failed to deliver message: {:?}", e),
-                    Ok(_) => {}
-                }
-                conditions_list.clear();
-                continue;
-            }
-            if c < size - 1 {
+            if conditions_list.len() < batch_size && c < size - 1 {
                 continue;
             }
 
-            let payload_bytes: Vec<u8> = match serde_json::to_vec(&conditions_list) {
-                Ok(v) => v,
-                Err(e) => {
-                    eprintln!("❌ Failed to serialize struct to bytes: {}", e);
-                    continue;
-                }
-            };
-            let key_final = format!("{}_{}", key, -1);
-            let record = FutureRecord::to(topic)
-                .key(&key_final)
-                .payload(&payload_bytes);
-
-            let _result = producer.send(record, Duration::from_secs(0)).await;
-            match _result {
-                Err((e, _)) => eprintln!("Failed to deliver message: {:?}", e),
-                Ok(_) => {}
+            let key_final = format!("{}_{}", key, c);
+            let headers = [(HEADER_PAYLOAD_FORMAT, format.name().as_bytes())];
+            for payload_bytes in format.encode(&conditions_list)? {
+                message_bus
+                    .send(topic, Some(key_final.as_bytes()), &payload_bytes, &headers)
+                    .await?;
             }
             conditions_list.clear();
         }
@@ -112,73 +117,406 @@ pub async fn producer(
     }
 
     let status_code = StatusCode::OK;
-    return Ok((
+    Ok((
         status_code,
         Json(AppResponse::ok(
             format!("Time in milliseconds: {} ms", durations),
             None,
         )),
-    ));
+    ))
 }
+
+pub async fn producer_tuning(
+    Path(size): Path<usize>,
+    Query(producer_request): Query<ProducerRequest>,
+    Query(tuning): Query<ProducerTuning>,
+) -> Result<(StatusCode, Json<AppResponse<ProducerTuningReport>>), AppError> {
+    let format = producer_request.format.unwrap_or_default();
+    let report = run_tuned_producer(size, format, tuning).await?;
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", Some(report)))))
+}
+
+// runs every tuning in the body one after another with the same size and format
+pub async fn producer_matrix(
+    Path(size): Path<usize>,
+    Query(producer_request): Query<ProducerRequest>,
+    Json(tunings): Json<Vec<ProducerTuning>>,
+) -> Result<(StatusCode, Json<AppResponse<Vec<ProducerTuningReport>>>), AppError> {
+    let format = producer_request.format.unwrap_or_default();
+    let mut reports = Vec::new();
+    for tuning in tunings {
+        reports.push(run_tuned_producer(size, format, tuning).await?);
+    }
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", Some(reports)))))
+}
+
+// reads until idle on the shared consumer, see ingest::ingest_message for the commit rules
 pub async fn consumer(
+    Query(consumer_request): Query<ConsumerRequest>,
     Extension(_state): Extension<Arc<AppState>>,
-) -> Result<(StatusCode, Json<AppResponse<Vec<Conditions>>>), AppError> {
-    let mut client = _state.tokio_postgres_client.lock().await;
-
-    let consumer: StreamConsumer = ClientConfig::new()
-        .set("bootstrap.servers", "localhost:29092")
-        .set("group.id", "my-consumer-group")
-        // Start consuming from the beginning of the topic if no offset is stored
-        .set("auto.offset.reset", "earliest")
-        .set("fetch.message.max.bytes", "10485760")
-        .set("fetch.max.bytes", "52428800")
-        .set("message.max.bytes", "1000000")
-        .set("message.copy.max.bytes", "65535")
-        .set("receive.message.max.bytes", "100000000")
-        .create()
-        .expect("Consumer creation error");
-
-    consumer
-        .subscribe(&["my-topic"])
-        .expect("Can't subscribe to topic");
-
-    println!(
-        "Consumer started. Waiting for messages on topic '{}'...",
-        "my-topic"
-    );
-
-    // Message stream: use .next() to wait for the next message
-    // If you prefer a loop with explicit polling, use `consumer.recv().await`
-    let mut message_stream = consumer.stream();
-
-    while let Some(message_result) = message_stream.next().await {
-        match message_result {
-            Ok(message) => {
-                let key = match message.key_view::<str>() {
-                    Some(Ok(s)) => s,
-                    _ => "N/A",
-                };
-
-                if !String::from(key).contains("conditions") {
-                    continue;
-                }
+) -> Result<(StatusCode, Json<AppResponse<ConsumerReport>>), AppError> {
+    let sink = consumer_request.sink.unwrap_or_default();
+    let mut postgres_client;
+    let mut tiberius_client;
+    let mut connection = match sink {
+        Sink::Postgres => {
+            postgres_client = _state.tokio_postgres_client.lock().await;
+            SinkConnection::Postgres(&mut postgres_client)
+        }
+        Sink::SqlServer => {
+            tiberius_client = _state
+                .pool_tiberius
+                .get()
+                .await
+                .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+            SinkConnection::SqlServer(&mut tiberius_client)
+        }
+    };
 
-                let payload = message.payload().unwrap_or(&[]);
-                match serde_json::from_slice::<Vec<Conditions>>(payload) {
-                    Ok(conditions) => {
-                        let _ = repository::insert_batch(&mut client, conditions).await;
-                    }
-                    Err(e) => {
-                        eprintln!("❌ Failed to deserialize payload: {}", e);
-                    }
-                }
+    // one request at a time reads from the shared consumer
+    let mut consumer = _state.bus_consumer.lock().await;
+
+    // stop after max_messages or when the topic has been idle for idle_timeout_ms
+    let max_messages = consumer_request.max_messages.unwrap_or(usize::MAX);
+    let idle_timeout = Duration::from_millis(consumer_request.idle_timeout_ms.unwrap_or(5000));
+
+    let start = Instant::now();
+    let mut report = ConsumerReport { sink, ..ConsumerReport::default() };
+
+    while report.messages < max_messages {
+        let message = match timeout(idle_timeout, consumer.recv()).await {
+            Ok(message_result) => message_result?,
+            Err(_) => break,
+        };
+
+        match ingest::ingest_message(&_state.message_bus, &mut connection, &consumer, &message).await? {
+            IngestOutcome::Skipped => continue,
+            IngestOutcome::DeadLettered => report.dead_lettered += 1,
+            IngestOutcome::Inserted { rows, rows_inserted } => {
+                report.rows += rows;
+                report.rows_inserted += rows_inserted;
+                report.rows_duplicated += rows as u64 - rows_inserted;
             }
-            Err(e) => {
-                eprintln!("❌ Kafka error: {}", e);
+        }
+        report.messages += 1;
+    }
+
+    report.elapsed_ms = start.elapsed().as_millis();
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", Some(report)))))
+}
+
+pub async fn end_to_end(
+    Path(size): Path<usize>,
+    Query(producer_request): Query<ProducerRequest>,
+    Query(consumer_request): Query<ConsumerRequest>,
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, Json<AppResponse<EndToEndReport>>), AppError> {
+    let topic = CONFIG.kafka_topic.as_str();
+    let batch_size = CONFIG.kafka_batch_size;
+    let format = producer_request.format.unwrap_or_default();
+    let sink = consumer_request.sink.unwrap_or_default();
+    let run_id = Uuid::new_v4().to_string();
+    let idle_timeout = Duration::from_millis(consumer_request.idle_timeout_ms.unwrap_or(10000));
+
+    // a throwaway group positioned at the current end of every partition, so the shared
+    // consumer's offsets are untouched and only this run's messages are read
+    let group_id = format!("end-to-end-{}", run_id);
+    let consumer =
+        tokio::task::block_in_place(|| _state.message_bus.consumer_at_end(&group_id, topic))?;
+
+    let consumer_state = _state.clone();
+    let consumer_run_id = run_id.clone();
+    let consumer_handle = tokio::spawn(async move {
+        consume_end_to_end(consumer, consumer_state, consumer_run_id, size, sink, idle_timeout).await
+    });
+
+    let start = Instant::now();
+    let mut report = EndToEndReport { sink, ..EndToEndReport::default() };
+    let produced = async {
+        let mut conditions_list: Vec<Conditions> = Vec::new();
+        for c in 0..size {
+            conditions_list.push(generate_conditions());
+
+            if conditions_list.len() < batch_size && c < size - 1 {
+                continue;
             }
+
+            let key = format!("conditions_{}", c);
+            for payload_bytes in format.encode(&conditions_list)? {
+                let produced_at = chrono::Utc::now().timestamp_micros().to_string();
+                let headers = [
+                    (HEADER_PRODUCED_AT, produced_at.as_bytes()),
+                    (HEADER_RUN_ID, run_id.as_bytes()),
+                    (HEADER_PAYLOAD_FORMAT, format.name().as_bytes()),
+                ];
+                _state
+                    .message_bus
+                    .send(topic, Some(key.as_bytes()), &payload_bytes, &headers)
+                    .await?;
+                report.messages_produced += 1;
+            }
+
+            report.rows_produced += conditions_list.len();
+            conditions_list.clear();
         }
+        Ok::<(), AppError>(())
+    }
+    .await;
+    // the consumer would otherwise wait for rows that are never sent until its idle timeout
+    if let Err(error) = produced {
+        consumer_handle.abort();
+        return Err(error);
     }
+    let produce_duration = start.elapsed();
+
+    let consumed = consumer_handle
+        .await
+        .map_err(|error| AppError::Other(format!("consumer task failed: {}", error)))??;
+    let total_duration = consumed.last_inserted_at.unwrap_or_else(Instant::now) - start;
+
+    report.messages_consumed = consumed.messages;
+    report.rows_consumed = consumed.rows;
+    report.produce_ms = produce_duration.as_millis();
+    report.total_ms = total_duration.as_millis();
+    report.produce_rows_per_second = per_second(report.rows_produced as u64, produce_duration);
+    report.end_to_end_rows_per_second = per_second(report.rows_consumed as u64, total_duration);
+    report.consume_latency = LatencySummary::from_durations(&consumed.consume_latencies);
+    report.sink_latency = LatencySummary::from_durations(&consumed.sink_latencies);
 
     let status_code = StatusCode::OK;
-    return Ok((status_code, Json(AppResponse::ok(format!("success"), None))));
+    Ok((status_code, Json(AppResponse::ok("success", Some(report)))))
+}
+
+// encodes and decodes the same generated rows with every payload format, no broker involved
+pub async fn formats(
+    Path(size): Path<usize>,
+) -> Result<(StatusCode, Json<AppResponse<Vec<FormatReport>>>), AppError> {
+    let batch_size = CONFIG.kafka_batch_size.max(1);
+    // payloads carry created_on in microseconds, so the decoded rows can be compared whole
+    let data: Vec<Conditions> = (0..size)
+        .map(|_| {
+            let mut conditions = generate_conditions();
+            conditions.created_on = conditions.created_on.trunc_subsecs(6);
+            conditions
+        })
+        .collect();
+
+    let mut reports = Vec::new();
+    for format in PayloadFormat::ALL {
+        let start = Instant::now();
+        let mut payloads = Vec::new();
+        for batch in data.chunks(batch_size) {
+            payloads.extend(format.encode(batch)?);
+        }
+        let encode_duration = start.elapsed();
+
+        let start = Instant::now();
+        let mut decoded = Vec::with_capacity(size);
+        for payload in &payloads {
+            decoded.extend(format.decode(payload)?);
+        }
+        let decode_duration = start.elapsed();
+
+        let rows = decoded.len();
+        if rows != size {
+            return Err(AppError::Other(format!(
+                "{} decoded {} of {} rows",
+                format.name(),
+                rows,
+                size
+            )));
+        }
+        if let Some(index) = (0..rows).find(|&index| decoded[index] != data[index]) {
+            return Err(AppError::Other(format!(
+                "{} decoded row {} as {:?}, expected {:?}",
+                format.name(),
+                index,
+                decoded[index],
+                data[index]
+            )));
+        }
+
+        let encoded_bytes: usize = payloads.iter().map(Vec::len).sum();
+        reports.push(FormatReport {
+            format,
+            rows,
+            messages: payloads.len(),
+            encoded_bytes,
+            bytes_per_row: encoded_bytes as f64 / rows.max(1) as f64,
+            encode_ms: encode_duration.as_secs_f64() * 1000.0,
+            decode_ms: decode_duration.as_secs_f64() * 1000.0,
+            encode_rows_per_second: per_second(rows as u64, encode_duration),
+            decode_rows_per_second: per_second(rows as u64, decode_duration),
+        });
+    }
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", Some(reports)))))
+}
+
+async fn run_tuned_producer(
+    size: usize,
+    format: PayloadFormat,
+    tuning: ProducerTuning,
+) -> Result<ProducerTuningReport, AppError> {
+    let (kafka_producer, statistics) =
+        config::kafka::get_kafka_statistics_producer(&tuning.to_overrides(), STATISTICS_INTERVAL_MS)
+            .map_err(|error| AppError::Other(format!("producer creation failed: {}", error)))?;
+    let topic = CONFIG.kafka_topic.as_str();
+    let batch_size = CONFIG.kafka_batch_size.max(1);
+
+    // generate and encode up front so only enqueueing and broker acknowledgement are timed
+    // payloads carry created_on in microseconds, so the decoded rows can be compared whole
+    let data: Vec<Conditions> = (0..size)
+        .map(|_| {
+            let mut conditions = generate_conditions();
+            conditions.created_on = conditions.created_on.trunc_subsecs(6);
+            conditions
+        })
+        .collect();
+    let mut payloads = Vec::new();
+    for batch in data.chunks(batch_size) {
+        payloads.extend(format.encode(batch)?);
+    }
+    let payload_bytes: u64 = payloads.iter().map(|payload| payload.len() as u64).sum();
+
+    // enqueue everything before awaiting so linger.ms and batch.size can take effect
+    let start = Instant::now();
+    let mut deliveries = Vec::with_capacity(payloads.len());
+    for (index, payload) in payloads.iter().enumerate() {
+        let key = format!("conditions_{}", index);
+        let headers = OwnedHeaders::new()
+            .insert(Header { key: HEADER_PAYLOAD_FORMAT, value: Some(format.name()) });
+        let mut record = FutureRecord::to(topic).key(&key).payload(payload).headers(headers);
+        loop {
+            match kafka_producer.send_result(record) {
+                Ok(delivery) => {
+                    deliveries.push(delivery);
+                    break;
+                }
+                Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), returned)) => {
+                    record = returned;
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+                Err((error, _)) => {
+                    return Err(AppError::Other(format!("enqueue failed: {}", error)));
+                }
+            }
+        }
+    }
+    for delivery in deliveries {
+        delivery
+            .await
+            .map_err(|error| AppError::Other(format!("delivery canceled: {}", error)))?
+            .map_err(|(error, _)| AppError::Other(format!("delivery failed: {}", error)))?;
+    }
+    let produce_duration = start.elapsed();
+
+    // tx_bytes also counts metadata requests, close enough for runs of a few thousand rows
+    let transmitted_bytes = statistics
+        .statistics_after(
+            std::time::Instant::now(),
+            Duration::from_millis(STATISTICS_INTERVAL_MS * 10),
+        )
+        .await
+        .map(|statistics| statistics.tx_bytes.max(0) as u64);
+    let compression_ratio = transmitted_bytes
+        .filter(|bytes| *bytes > 0)
+        .map(|bytes| payload_bytes as f64 / bytes as f64);
+
+    Ok(ProducerTuningReport {
+        tuning,
+        format,
+        rows: size,
+        messages: payloads.len(),
+        payload_bytes,
+        transmitted_bytes,
+        compression_ratio,
+        produce_ms: produce_duration.as_millis(),
+        acked_rows_per_second: per_second(size as u64, produce_duration),
+        acked_messages_per_second: per_second(payloads.len() as u64, produce_duration),
+        acked_megabytes_per_second: per_second(payload_bytes, produce_duration) / 1_000_000.0,
+    })
+}
+
+fn generate_conditions() -> Conditions {
+    let location = util::generator::generate_word(util::generator::generate_numbers_usize(10, 20));
+    Conditions::from_create_request(ConditionsRequest {
+        id: None,
+        location,
+        temperature: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
+        humidity: Some(util::generator::generate_numbers_f64(0.0, 100.0)),
+    })
+}
+
+#[derive(Default)]
+struct EndToEndConsumed {
+    messages: usize,
+    rows: usize,
+    consume_latencies: Vec<Duration>,
+    sink_latencies: Vec<Duration>,
+    // the throwaway group commits nothing, the run ends when the last batch is in the sink
+    last_inserted_at: Option<Instant>,
+}
+
+fn elapsed_since_micros(micros: i64) -> Duration {
+    let elapsed = chrono::Utc::now().timestamp_micros() - micros;
+    Duration::from_micros(elapsed.max(0) as u64)
+}
+
+async fn consume_end_to_end(
+    mut consumer: BusConsumer,
+    state: Arc<AppState>,
+    run_id: String,
+    expected_rows: usize,
+    sink: Sink,
+    idle_timeout: Duration,
+) -> Result<EndToEndConsumed, AppError> {
+    let mut consumed = EndToEndConsumed::default();
+
+    while consumed.rows < expected_rows {
+        let message = match timeout(idle_timeout, consumer.recv()).await {
+            Ok(message_result) => message_result?,
+            Err(_) => break,
+        };
+
+        if message.header(HEADER_RUN_ID) != Some(run_id.as_str()) {
+            continue;
+        }
+        let produced_at = message.header(HEADER_PRODUCED_AT)
+            .and_then(|value| value.parse::<i64>().ok())
+            .ok_or(AppError::Other("missing produced_at header".to_string()))?;
+        consumed.consume_latencies.push(elapsed_since_micros(produced_at));
+
+        let conditions = payload_format(&message).decode(&message.payload)?;
+        consumed.messages += 1;
+        consumed.rows += conditions.len();
+
+        match sink {
+            Sink::Postgres => {
+                let mut client = state.tokio_postgres_client.lock().await;
+                repository::insert_batch(&mut client, conditions).await?;
+            }
+            Sink::SqlServer => {
+                let mut client = state
+                    .pool_tiberius
+                    .get()
+                    .await
+                    .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+                let conditions = conditions.into_iter().map(Into::into).collect();
+                conditions_tiberius::repository::insert_batch_2(&mut client, conditions).await?;
+            }
+        }
+
+        consumed.sink_latencies.push(elapsed_since_micros(produced_at));
+        consumed.last_inserted_at = Some(Instant::now());
+    }
+
+    Ok(consumed)
 }
@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json, Router,
+    extract::Query,
+    http::StatusCode,
+    routing::{get, post},
+};
+
+use crate::{
+    config::environment::CONFIG,
+    dto::{app_error::AppError, app_response::AppResponse},
+    modules::conditions_kafka::{
+        schema::{IngestStartRequest, IngestStatus},
+        worker,
+    },
+    state::AppState,
+};
+
+pub fn new() -> Router {
+    Router::new()
+        .route("/start", post(start))
+        .route("/stop", post(stop))
+        .route("/status", get(status))
+}
+
+pub async fn start(
+    Query(start_request): Query<IngestStartRequest>,
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, Json<AppResponse<IngestStatus>>), AppError> {
+    let workers = start_request.workers.unwrap_or(CONFIG.kafka_ingest_workers);
+    let sink = start_request.sink.unwrap_or(CONFIG.kafka_ingest_sink);
+    worker::start(&_state, workers, sink).await?;
+    let status = worker::status(&_state).await?;
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", Some(status)))))
+}
+
+pub async fn stop(
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, Json<AppResponse<IngestStatus>>), AppError> {
+    worker::stop(&_state).await?;
+    let status = worker::status(&_state).await?;
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", Some(status)))))
+}
+
+pub async fn status(
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, Json<AppResponse<IngestStatus>>), AppError> {
+    let status = worker::status(&_state).await?;
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", Some(status)))))
+}
@@ -0,0 +1,311 @@
+use crate::{
+    config::environment::CONFIG,
+    dto::app_error::AppError,
+    modules::{
+        conditions_kafka::{
+            bus::{BusConsumer, BusMessage, MessageBus},
+            codec::{HEADER_PAYLOAD_FORMAT, PayloadFormat},
+            repository,
+            schema::{Conditions, Sink},
+        },
+        conditions_tiberius,
+    },
+    state::AppState,
+};
+
+const HEADER_ERROR: &str = "error";
+const HEADER_SOURCE_TOPIC: &str = "source_topic";
+const HEADER_SOURCE_PARTITION: &str = "source_partition";
+const HEADER_SOURCE_OFFSET: &str = "source_offset";
+
+pub enum SinkConnection<'a> {
+    Postgres(&'a mut tokio_postgres::Client),
+    SqlServer(&'a mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>),
+}
+
+pub enum IngestOutcome {
+    // not a conditions message, left for a later commit to cover
+    Skipped,
+    DeadLettered,
+    Inserted { rows: usize, rows_inserted: u64 },
+}
+
+// offsets are committed only after the rows are in the sink database, replays are absorbed
+// by the idempotent insert and undecodable payloads go to the dead-letter topic
+pub async fn ingest_message(
+    bus: &MessageBus,
+    connection: &mut SinkConnection<'_>,
+    consumer: &BusConsumer,
+    message: &BusMessage,
+) -> Result<IngestOutcome, AppError> {
+    ingest(bus, connection, consumer, message).await
+}
+
+// takes a connection from the sink's pool for a single message, used by the ingest worker
+pub async fn ingest_message_pooled(
+    state: &AppState,
+    sink: Sink,
+    consumer: &BusConsumer,
+    message: &BusMessage,
+) -> Result<IngestOutcome, AppError> {
+    let mut writer = PooledWriter { state, sink };
+    ingest(&state.message_bus, &mut writer, consumer, message).await
+}
+
+// where a decoded batch goes, the pooled writer only takes a connection once there is one
+trait BatchWriter {
+    async fn write(&mut self, conditions: Vec<Conditions>) -> Result<u64, AppError>;
+}
+
+struct PooledWriter<'a> {
+    state: &'a AppState,
+    sink: Sink,
+}
+
+impl BatchWriter for SinkConnection<'_> {
+    async fn write(&mut self, conditions: Vec<Conditions>) -> Result<u64, AppError> {
+        write_batch(self, conditions).await
+    }
+}
+
+impl BatchWriter for PooledWriter<'_> {
+    async fn write(&mut self, conditions: Vec<Conditions>) -> Result<u64, AppError> {
+        match self.sink {
+            Sink::Postgres => {
+                let mut client = self
+                    .state
+                    .pool_pg
+                    .get()
+                    .await
+                    .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+                write_batch(&mut SinkConnection::Postgres(&mut client), conditions).await
+            }
+            Sink::SqlServer => {
+                let mut client = self
+                    .state
+                    .pool_tiberius
+                    .get()
+                    .await
+                    .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+                write_batch(&mut SinkConnection::SqlServer(&mut client), conditions).await
+            }
+        }
+    }
+}
+
+// the consumer has already moved past `message`, so every failure rewinds it, otherwise the
+// next successful commit would cover the failed message and it would be lost
+async fn ingest(
+    bus: &MessageBus,
+    writer: &mut impl BatchWriter,
+    consumer: &BusConsumer,
+    message: &BusMessage,
+) -> Result<IngestOutcome, AppError> {
+    let result = ingest_once(bus, writer, consumer, message).await;
+    if result.is_err() {
+        consumer.seek(message)?;
+    }
+    result
+}
+
+async fn ingest_once(
+    bus: &MessageBus,
+    writer: &mut impl BatchWriter,
+    consumer: &BusConsumer,
+    message: &BusMessage,
+) -> Result<IngestOutcome, AppError> {
+    let key = message.key_str().unwrap_or("N/A");
+    if !key.contains("conditions") {
+        return Ok(IngestOutcome::Skipped);
+    }
+
+    let conditions = match payload_format(message).decode(&message.payload) {
+        Ok(conditions) => conditions,
+        Err(error) => {
+            dead_letter(bus, message, &error).await?;
+            consumer.commit(message)?;
+            return Ok(IngestOutcome::DeadLettered);
+        }
+    };
+
+    let rows = conditions.len();
+    let rows_inserted = writer.write(conditions).await?;
+    consumer.commit(message)?;
+    Ok(IngestOutcome::Inserted { rows, rows_inserted })
+}
+
+// returns the rows actually inserted, both sinks skip rows whose (id, created_on) is already
+// stored so a replayed message is harmless
+pub async fn write_batch(
+    connection: &mut SinkConnection<'_>,
+    conditions: Vec<Conditions>,
+) -> Result<u64, AppError> {
+    match connection {
+        SinkConnection::Postgres(client) => {
+            repository::insert_batch_idempotent(client, conditions).await
+        }
+        SinkConnection::SqlServer(client) => {
+            let conditions = conditions.into_iter().map(Into::into).collect();
+            conditions_tiberius::repository::insert_batch_staging(client, conditions).await
+        }
+    }
+}
+
+// messages produced before formats were selectable carry no header and are a JSON array
+pub fn payload_format(message: &BusMessage) -> PayloadFormat {
+    message
+        .header(HEADER_PAYLOAD_FORMAT)
+        .and_then(PayloadFormat::from_name)
+        .unwrap_or_default()
+}
+
+// forwards the original payload and headers with the failure reason and source position
+async fn dead_letter(bus: &MessageBus, message: &BusMessage, error: &AppError) -> Result<(), AppError> {
+    let reason = format!("{:?}", error);
+    let partition = message.partition.to_string();
+    let offset = message.offset.to_string();
+
+    let mut headers: Vec<(&str, &[u8])> = message
+        .headers
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_slice()))
+        .collect();
+    headers.push((HEADER_ERROR, reason.as_bytes()));
+    headers.push((HEADER_SOURCE_TOPIC, message.topic.as_bytes()));
+    headers.push((HEADER_SOURCE_PARTITION, partition.as_bytes()));
+    headers.push((HEADER_SOURCE_OFFSET, offset.as_bytes()));
+
+    bus.send(&CONFIG.kafka_dead_letter_topic, message.key.as_deref(), &message.payload, &headers)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config,
+        modules::conditions_kafka::{bus_memory::MemoryBus, schema::ConditionsRequest},
+    };
+
+    const TOPIC: &str = "conditions-ingest-test";
+
+    fn generate(size: usize) -> Vec<Conditions> {
+        (0..size)
+            .map(|index| {
+                Conditions::from_create_request(ConditionsRequest {
+                    id: None,
+                    location: format!("ingest-test-{}", index),
+                    temperature: Some(30.0),
+                    humidity: Some(50.0),
+                })
+            })
+            .collect()
+    }
+
+    // stands in for the sink on paths that fail before or while writing
+    struct FailingWriter {
+        writes: usize,
+    }
+
+    impl BatchWriter for FailingWriter {
+        async fn write(&mut self, _conditions: Vec<Conditions>) -> Result<u64, AppError> {
+            self.writes += 1;
+            Err(AppError::Other("get connection failed".to_string()))
+        }
+    }
+
+    async fn assert_rewound(bus: &MessageBus, consumer: &mut BusConsumer, message: &BusMessage) {
+        assert_eq!(bus.lag("rewind-test", TOPIC).unwrap()[0].committed_offset, None);
+        let again = consumer.recv().await.unwrap();
+        assert_eq!(again.offset, message.offset);
+        assert_eq!(again.payload, message.payload);
+    }
+
+    #[tokio::test]
+    async fn failed_dead_letter_rewinds() {
+        let memory = MemoryBus::new(1);
+        memory.close_topic(&CONFIG.kafka_dead_letter_topic);
+        let bus = MessageBus::Memory(memory);
+        let headers: [(&str, &[u8]); 1] = [(HEADER_PAYLOAD_FORMAT, PayloadFormat::JsonArray.name().as_bytes())];
+        bus.send(TOPIC, Some(b"conditions"), b"not a conditions payload", &headers).await.unwrap();
+
+        let mut consumer = bus.consumer("rewind-test", TOPIC).unwrap();
+        let message = consumer.recv().await.unwrap();
+        let mut writer = FailingWriter { writes: 0 };
+        assert!(ingest(&bus, &mut writer, &consumer, &message).await.is_err());
+        assert_eq!(writer.writes, 0);
+
+        assert_rewound(&bus, &mut consumer, &message).await;
+    }
+
+    #[tokio::test]
+    async fn failed_write_rewinds() {
+        let bus = MessageBus::Memory(MemoryBus::new(1));
+        let format = PayloadFormat::JsonArray;
+        let payload = format.encode(&generate(2)).unwrap().remove(0);
+        let headers: [(&str, &[u8]); 1] = [(HEADER_PAYLOAD_FORMAT, format.name().as_bytes())];
+        bus.send(TOPIC, Some(b"conditions"), &payload, &headers).await.unwrap();
+
+        let mut consumer = bus.consumer("rewind-test", TOPIC).unwrap();
+        let message = consumer.recv().await.unwrap();
+        let mut writer = FailingWriter { writes: 0 };
+        assert!(ingest(&bus, &mut writer, &consumer, &message).await.is_err());
+        assert_eq!(writer.writes, 1);
+
+        assert_rewound(&bus, &mut consumer, &message).await;
+    }
+
+    // produce -> consume -> insert without a broker, the sink is the postgres database from .env
+    #[tokio::test]
+    #[ignore = "needs the postgres database configured in .env"]
+    async fn memory_bus_to_postgres() {
+        let bus = MessageBus::Memory(MemoryBus::new(1));
+        let mut client = config::database::get_tokio_postgresql().await.unwrap();
+
+        let conditions = generate(3);
+        let ids: Vec<String> = conditions.iter().map(|condition| condition.id.clone()).collect();
+        let format = PayloadFormat::JsonArray;
+        let payload = format.encode(&conditions).unwrap().remove(0);
+        let headers: [(&str, &[u8]); 1] = [(HEADER_PAYLOAD_FORMAT, format.name().as_bytes())];
+        bus.send(TOPIC, Some(b"conditions"), &payload, &headers).await.unwrap();
+        bus.send(TOPIC, Some(b"conditions"), b"not a conditions payload", &headers).await.unwrap();
+        bus.send(TOPIC, Some(b"other"), b"ignored", &[]).await.unwrap();
+
+        let mut consumer = bus.consumer("ingest-test", TOPIC).unwrap();
+        let mut connection = SinkConnection::Postgres(&mut client);
+
+        let message = consumer.recv().await.unwrap();
+        let outcome = ingest_message(&bus, &mut connection, &consumer, &message).await.unwrap();
+        assert!(matches!(outcome, IngestOutcome::Inserted { rows: 3, rows_inserted: 3 }));
+
+        let message = consumer.recv().await.unwrap();
+        let outcome = ingest_message(&bus, &mut connection, &consumer, &message).await.unwrap();
+        assert!(matches!(outcome, IngestOutcome::DeadLettered));
+
+        let message = consumer.recv().await.unwrap();
+        let outcome = ingest_message(&bus, &mut connection, &consumer, &message).await.unwrap();
+        assert!(matches!(outcome, IngestOutcome::Skipped));
+
+        // the skipped message is left uncommitted
+        let lag = bus.lag("ingest-test", TOPIC).unwrap();
+        assert_eq!(lag[0].committed_offset, Some(2));
+
+        let mut dead_letters = bus.consumer("ingest-test", &CONFIG.kafka_dead_letter_topic).unwrap();
+        let dead_letter = dead_letters.recv().await.unwrap();
+        assert_eq!(dead_letter.payload, b"not a conditions payload");
+        assert_eq!(dead_letter.header(HEADER_SOURCE_TOPIC), Some(TOPIC));
+        assert_eq!(dead_letter.header(HEADER_SOURCE_OFFSET), Some("1"));
+        assert!(dead_letter.header(HEADER_ERROR).is_some());
+
+        // a replay, e.g. after a rebalance, inserts nothing twice
+        let mut replay = bus.consumer("ingest-test-replay", TOPIC).unwrap();
+        let message = replay.recv().await.unwrap();
+        let outcome = ingest_message(&bus, &mut connection, &replay, &message).await.unwrap();
+        assert!(matches!(outcome, IngestOutcome::Inserted { rows: 3, rows_inserted: 0 }));
+
+        client
+            .execute("DELETE FROM conditions WHERE id = ANY($1)", &[&ids])
+            .await
+            .unwrap();
+    }
+}
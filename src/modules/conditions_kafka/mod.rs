@@ -1,4 +1,11 @@
+pub mod bus;
+pub mod bus_kafka;
+pub mod bus_memory;
+pub mod codec;
+pub mod ingest;
 pub mod schema;
 pub mod repository;
+pub mod worker;
 pub mod controller;
-pub mod controller_benchmark;
\ No newline at end of file
+pub mod controller_benchmark;
+pub mod controller_ingest;
\ No newline at end of file
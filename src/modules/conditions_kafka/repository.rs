@@ -1,15 +1,16 @@
 use bytes::BytesMut;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDateTime, Utc};
 use csv::WriterBuilder;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use serde::Serialize;
 
 use crate::{
     dto::app_error::AppError,
-    modules::conditions_kafka::schema::Conditions,
+    modules::conditions_kafka::{codec::created_on_utc, schema::Conditions},
 };
 use futures_util::{pin_mut, sink::SinkExt};
 use tempfile::Builder;
-use tokio::{fs::File as AsyncFile, io::AsyncReadExt, time::Instant};
+use tokio::{fs::File as AsyncFile, io::AsyncReadExt};
 
 pub fn map_row_to_condition(row: tokio_postgres::Row) -> Conditions {
     let created_on_datetime: DateTime<Utc> = row.get("created_on");
@@ -22,20 +23,20 @@ pub fn map_row_to_condition(row: tokio_postgres::Row) -> Conditions {
     }
 }
 
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub async fn find_all(
     client: &tokio_postgres::Client,
 ) -> Result<Vec<Conditions>, AppError> {
-    let start = Instant::now();
     let statement = "SELECT id, created_on, location, temperature, humidity FROM conditions";
     let rows = client
         .query(statement, &[])
         .await
         .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
-    let duration = start.elapsed();
-    println!("{}", duration.as_millis());
+    tracing::Span::current().record("rows", rows.len());
     return Ok(rows.into_par_iter().map(map_row_to_condition).collect());
 }
 
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub async fn find_by_id(
     client: &tokio_postgres::Client,
     id: String,
@@ -46,6 +47,7 @@ pub async fn find_by_id(
         .query(statement, &[&id])
         .await
         .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+    tracing::Span::current().record("rows", rows.len());
     let _conditions: Vec<Conditions> = rows.into_iter().map(map_row_to_condition).collect();
     let _condition = _conditions.first();
     if _condition.is_none() {
@@ -54,6 +56,7 @@ pub async fn find_by_id(
     return Ok(_condition.unwrap().clone());
 }
 
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub async fn delete_all(client: &tokio_postgres::Client) -> Result<(), AppError> {
     let statement = "DELETE FROM conditions";
 
@@ -61,6 +64,7 @@ pub async fn delete_all(client: &tokio_postgres::Client) -> Result<(), AppError>
         .execute(statement, &[])
         .await
         .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+    tracing::Span::current().record("rows", rows_affected);
     if rows_affected == 0 {
         return Err(AppError::NotFound);
     }
@@ -68,6 +72,7 @@ pub async fn delete_all(client: &tokio_postgres::Client) -> Result<(), AppError>
     return Ok(());
 }
 
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub async fn delete_by_id(client: &tokio_postgres::Client, id: String) -> Result<(), AppError> {
     let statement = "DELETE FROM conditions WHERE id=$1";
 
@@ -75,6 +80,7 @@ pub async fn delete_by_id(client: &tokio_postgres::Client, id: String) -> Result
         .execute(statement, &[&id])
         .await
         .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+    tracing::Span::current().record("rows", rows_affected);
     if rows_affected == 0 {
         return Err(AppError::NotFound);
     }
@@ -82,6 +88,7 @@ pub async fn delete_by_id(client: &tokio_postgres::Client, id: String) -> Result
     return Ok(());
 }
 
+#[tracing::instrument(skip_all, fields(rows = data.len()))]
 pub async fn insert_all(
     client: &mut tokio_postgres::Client,
     data: Vec<Conditions>,
@@ -123,6 +130,7 @@ pub async fn insert_all(
     return Ok(());
 }
 
+#[tracing::instrument(skip_all, fields(rows = 1))]
 pub async fn insert_one(
     client: &mut tokio_postgres::Client,
     condition: Conditions,
@@ -162,6 +170,7 @@ pub async fn insert_one(
     return Ok(());
 }
 
+#[tracing::instrument(skip_all, fields(rows = data.len()))]
 pub async fn insert_batch(
     client: &mut tokio_postgres::Client,
     data: Vec<Conditions>,
@@ -267,6 +276,85 @@ pub async fn insert_batch(
     Ok(())
 }
 
+// a COPY row of the staging table, created_on is written as the payload codec carries it, UTC
+// with microseconds and an explicit offset, so timestamptz stores exactly the message's instant
+#[derive(Serialize)]
+struct StagingRow<'a> {
+    id: &'a str,
+    #[serde(with = "created_on_utc")]
+    created_on: NaiveDateTime,
+    location: &'a str,
+    temperature: Option<f64>,
+    humidity: Option<f64>,
+}
+
+fn staging_csv(data: &[Conditions]) -> Result<Vec<u8>, AppError> {
+    let mut wtr = WriterBuilder::new().has_headers(false).from_writer(Vec::new());
+    for condition in data {
+        let row = StagingRow {
+            id: &condition.id,
+            created_on: condition.created_on,
+            location: &condition.location,
+            temperature: condition.temperature,
+            humidity: condition.humidity,
+        };
+        wtr.serialize(row)
+            .map_err(|error| AppError::Other(format!("CSV serialization failed: {}", error)))?;
+    }
+    wtr.into_inner()
+        .map_err(|error| AppError::Other(format!("CSV flush failed: {}", error)))
+}
+
+// COPY cannot skip conflicting rows, so the batch goes through a temporary staging table
+// and replayed rows with an existing (id, created_on) are ignored
+#[tracing::instrument(skip_all, fields(rows = data.len()))]
+pub async fn insert_batch_idempotent(
+    client: &mut tokio_postgres::Client,
+    data: Vec<Conditions>,
+) -> Result<u64, AppError> {
+    let transaction = client
+        .transaction()
+        .await
+        .map_err(|error| AppError::Other(format!("transaction failed: {}", error)))?;
+
+    let csv_data = staging_csv(&data)?;
+
+    transaction
+        .batch_execute("CREATE TEMP TABLE conditions_staging (LIKE conditions) ON COMMIT DROP")
+        .await
+        .map_err(|error| AppError::Other(format!("create staging table failed: {}", error)))?;
+
+    let sink = transaction
+        .copy_in("COPY conditions_staging (id, created_on, location, temperature, humidity) FROM STDIN WITH (FORMAT csv)")
+        .await
+        .map_err(|error| AppError::Other(format!("copy_in failed: {}", error)))?;
+    pin_mut!(sink);
+    sink.send(bytes::Bytes::from(csv_data))
+        .await
+        .map_err(|error| AppError::Other(format!("Failed to send data chunk: {}", error)))?;
+    sink.close()
+        .await
+        .map_err(|error| AppError::Other(format!("Failed to close sink: {}", error)))?;
+
+    let rows_inserted = transaction
+        .execute(
+            "INSERT INTO conditions (id, created_on, location, temperature, humidity)
+             SELECT id, created_on, location, temperature, humidity FROM conditions_staging
+             ON CONFLICT (id, created_on) DO NOTHING",
+            &[],
+        )
+        .await
+        .map_err(|error| AppError::Other(format!("insert from staging failed: {}", error)))?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|error| AppError::Other(format!("commit failed: {}", error)))?;
+
+    Ok(rows_inserted)
+}
+
+#[tracing::instrument(skip_all, fields(rows = 1))]
 pub async fn update_one(
     client: &mut tokio_postgres::Client,
     condition: Conditions,
@@ -303,3 +391,28 @@ pub async fn update_one(
 
     return Ok(());
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    #[test]
+    fn staging_csv_keeps_utc_microseconds() {
+        let created_on = NaiveDate::from_ymd_opt(2023, 5, 1)
+            .unwrap()
+            .and_hms_micro_opt(10, 20, 30, 123456)
+            .unwrap();
+        let conditions = Conditions {
+            id: "a".to_string(),
+            created_on,
+            location: "north".to_string(),
+            temperature: Some(31.5),
+            humidity: None,
+        };
+
+        let csv = String::from_utf8(staging_csv(&[conditions]).unwrap()).unwrap();
+        assert_eq!(csv, "a,2023-05-01T10:20:30.123456Z,north,31.5,\n");
+    }
+}
@@ -1,4 +1,7 @@
-use crate::util::serializer::datetime_serializer;
+use crate::{
+    modules::{conditions_kafka::codec::PayloadFormat, conditions_tiberius},
+    util::{serializer::datetime_serializer, statistics::LatencySummary},
+};
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -48,6 +51,202 @@ impl Conditions {
     }
 }
 
+impl From<Conditions> for conditions_tiberius::schema::Conditions {
+    fn from(conditions: Conditions) -> Self {
+        conditions_tiberius::schema::Conditions {
+            id: conditions.id,
+            created_on: conditions.created_on,
+            location: conditions.location,
+            temperature: conditions.temperature,
+            humidity: conditions.humidity,
+        }
+    }
+}
+
+// database the consumed batches are written to
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Sink {
+    #[default]
+    Postgres,
+    SqlServer,
+}
+
 pub struct CountResult {
     pub count: i64,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct ConsumerRequest {
+    pub max_messages: Option<usize>,
+    pub idle_timeout_ms: Option<u64>,
+    pub sink: Option<Sink>,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct ConsumerReport {
+    pub sink: Sink,
+    pub messages: usize,
+    pub rows: usize,
+    pub rows_inserted: u64,
+    // rows already in the table from an earlier delivery of the same message
+    pub rows_duplicated: u64,
+    pub dead_lettered: usize,
+    pub elapsed_ms: u128,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct EndToEndReport {
+    pub sink: Sink,
+    pub messages_produced: usize,
+    pub rows_produced: usize,
+    pub messages_consumed: usize,
+    pub rows_consumed: usize,
+    pub produce_ms: u128,
+    pub total_ms: u128,
+    pub produce_rows_per_second: f64,
+    pub end_to_end_rows_per_second: f64,
+    // produce -> consume
+    pub consume_latency: LatencySummary,
+    // produce -> batch committed in the sink database
+    pub sink_latency: LatencySummary,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProducerRequest {
+    pub format: Option<PayloadFormat>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FormatReport {
+    pub format: PayloadFormat,
+    pub rows: usize,
+    pub messages: usize,
+    pub encoded_bytes: usize,
+    pub bytes_per_row: f64,
+    pub encode_ms: f64,
+    pub decode_ms: f64,
+    pub encode_rows_per_second: f64,
+    pub decode_rows_per_second: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionCodec {
+    None,
+    Gzip,
+    Snappy,
+    Lz4,
+    Zstd,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+pub enum Acks {
+    #[serde(rename = "0")]
+    Zero,
+    #[serde(rename = "1")]
+    One,
+    #[serde(rename = "all")]
+    All,
+}
+
+// librdkafka producer settings for one benchmark run, unset fields keep the defaults
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct ProducerTuning {
+    pub compression: Option<CompressionCodec>,
+    pub linger_ms: Option<u64>,
+    // batch.size in bytes, not the number of rows per message
+    pub batch_size_bytes: Option<u64>,
+    pub acks: Option<Acks>,
+    pub enable_idempotence: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProducerTuningReport {
+    pub tuning: ProducerTuning,
+    pub format: PayloadFormat,
+    pub rows: usize,
+    pub messages: usize,
+    pub payload_bytes: u64,
+    // bytes sent to the brokers as reported by librdkafka, after compression
+    pub transmitted_bytes: Option<u64>,
+    pub compression_ratio: Option<f64>,
+    pub produce_ms: u128,
+    pub acked_rows_per_second: f64,
+    pub acked_messages_per_second: f64,
+    pub acked_megabytes_per_second: f64,
+}
+
+impl CompressionCodec {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CompressionCodec::None => "none",
+            CompressionCodec::Gzip => "gzip",
+            CompressionCodec::Snappy => "snappy",
+            CompressionCodec::Lz4 => "lz4",
+            CompressionCodec::Zstd => "zstd",
+        }
+    }
+}
+
+impl Acks {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Acks::Zero => "0",
+            Acks::One => "1",
+            Acks::All => "all",
+        }
+    }
+}
+
+impl ProducerTuning {
+    pub fn to_overrides(&self) -> Vec<(&'static str, String)> {
+        let mut overrides = Vec::new();
+        if let Some(compression) = self.compression {
+            overrides.push(("compression.type", compression.name().to_string()));
+        }
+        if let Some(linger_ms) = self.linger_ms {
+            overrides.push(("linger.ms", linger_ms.to_string()));
+        }
+        if let Some(batch_size_bytes) = self.batch_size_bytes {
+            overrides.push(("batch.size", batch_size_bytes.to_string()));
+        }
+        if let Some(acks) = self.acks {
+            overrides.push(("acks", acks.name().to_string()));
+        }
+        if let Some(enable_idempotence) = self.enable_idempotence {
+            overrides.push(("enable.idempotence", enable_idempotence.to_string()));
+        }
+        overrides
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IngestStartRequest {
+    pub workers: Option<usize>,
+    pub sink: Option<Sink>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PartitionLag {
+    pub partition: i32,
+    pub committed_offset: Option<i64>,
+    pub high_watermark: i64,
+    pub lag: i64,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct IngestStatus {
+    pub running: bool,
+    pub sink: Option<Sink>,
+    pub workers: usize,
+    pub started_on: Option<NaiveDateTime>,
+    pub messages: u64,
+    pub rows: u64,
+    pub rows_inserted: u64,
+    pub rows_duplicated: u64,
+    pub dead_lettered: u64,
+    pub errors: u64,
+    pub total_lag: i64,
+    pub lag: Vec<PartitionLag>,
+}
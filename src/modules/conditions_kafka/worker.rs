@@ -0,0 +1,187 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use chrono::NaiveDateTime;
+use tokio::{
+    sync::{Mutex, watch},
+    task::JoinHandle,
+};
+
+use crate::{
+    config::environment::CONFIG,
+    dto::app_error::AppError,
+    modules::conditions_kafka::{
+        ingest::{self, IngestOutcome},
+        schema::{IngestStatus, Sink},
+    },
+    state::AppState,
+};
+
+// counters survive stop/start so a status call always shows everything ingested since boot
+#[derive(Default)]
+pub struct IngestCounters {
+    messages: AtomicU64,
+    rows: AtomicU64,
+    rows_inserted: AtomicU64,
+    rows_duplicated: AtomicU64,
+    dead_lettered: AtomicU64,
+    errors: AtomicU64,
+}
+
+struct RunningWorkers {
+    sink: Sink,
+    shutdown: watch::Sender<bool>,
+    tasks: Vec<JoinHandle<()>>,
+    started_on: NaiveDateTime,
+}
+
+#[derive(Default)]
+pub struct IngestWorker {
+    counters: IngestCounters,
+    running: Mutex<Option<RunningWorkers>>,
+}
+
+// every worker is a consumer in the ingest group, kafka spreads the partitions over them,
+// so at most one worker per partition is started, the others would only hold a consumer open
+pub async fn start(state: &Arc<AppState>, workers: usize, sink: Sink) -> Result<(), AppError> {
+    let partitions = tokio::task::block_in_place(|| state.message_bus.partition_count(&CONFIG.kafka_topic))?;
+    // a topic that does not exist yet reports no partitions, it is created with at least one
+    let max_workers = partitions.max(1);
+    if workers == 0 || workers > max_workers {
+        return Err(AppError::invalid_request(
+            "workers",
+            format!("workers must be 1..={}, the partition count of {}", max_workers, CONFIG.kafka_topic),
+        ));
+    }
+
+    let mut running = state.ingest_worker.running.lock().await;
+    if running.is_some() {
+        return Err(AppError::DataExist);
+    }
+
+    let (shutdown, shutdown_receiver) = watch::channel(false);
+    let tasks = (0..workers)
+        .map(|index| {
+            let state = state.clone();
+            let shutdown_receiver = shutdown_receiver.clone();
+            tokio::spawn(async move { run_worker(state, index, sink, shutdown_receiver).await })
+        })
+        .collect();
+
+    *running = Some(RunningWorkers {
+        sink,
+        shutdown,
+        tasks,
+        started_on: chrono::Utc::now().naive_utc(),
+    });
+    tracing::info!(workers, ?sink, "ingest worker started");
+    Ok(())
+}
+
+// lets every worker finish the message it is handling, then waits for all of them
+pub async fn stop(state: &AppState) -> Result<(), AppError> {
+    let running = state.ingest_worker.running.lock().await.take();
+    let Some(running) = running else {
+        return Err(AppError::NotFound);
+    };
+
+    let _ = running.shutdown.send(true);
+    for task in running.tasks {
+        task.await
+            .map_err(|error| AppError::Other(format!("ingest worker failed: {}", error)))?;
+    }
+    tracing::info!("ingest worker stopped");
+    Ok(())
+}
+
+pub async fn status(state: &AppState) -> Result<IngestStatus, AppError> {
+    let counters = &state.ingest_worker.counters;
+    let mut status = IngestStatus {
+        messages: counters.messages.load(Ordering::Relaxed),
+        rows: counters.rows.load(Ordering::Relaxed),
+        rows_inserted: counters.rows_inserted.load(Ordering::Relaxed),
+        rows_duplicated: counters.rows_duplicated.load(Ordering::Relaxed),
+        dead_lettered: counters.dead_lettered.load(Ordering::Relaxed),
+        errors: counters.errors.load(Ordering::Relaxed),
+        ..IngestStatus::default()
+    };
+    if let Some(running) = state.ingest_worker.running.lock().await.as_ref() {
+        status.running = true;
+        status.sink = Some(running.sink);
+        status.workers = running.tasks.len();
+        status.started_on = Some(running.started_on);
+    }
+
+    status.lag = tokio::task::block_in_place(|| {
+        state.message_bus.lag(&CONFIG.kafka_ingest_group_id, &CONFIG.kafka_topic)
+    })?;
+    status.total_lag = status.lag.iter().map(|partition| partition.lag).sum();
+    Ok(status)
+}
+
+// every log line of a worker carries its index and sink
+#[tracing::instrument(name = "ingest_worker", skip(state, shutdown))]
+async fn run_worker(
+    state: Arc<AppState>,
+    index: usize,
+    sink: Sink,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let counters = &state.ingest_worker.counters;
+
+    let mut consumer = match state
+        .message_bus
+        .consumer(&CONFIG.kafka_ingest_group_id, &CONFIG.kafka_topic)
+    {
+        Ok(consumer) => consumer,
+        Err(error) => {
+            tracing::error!(?error, "ingest worker failed to start");
+            counters.errors.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+    };
+
+    loop {
+        // shutdown is only checked between messages, an in-flight batch is always finished
+        let message = tokio::select! {
+            _ = shutdown.changed() => break,
+            message = consumer.recv() => match message {
+                Ok(message) => message,
+                Err(error) => {
+                    // an unreachable broker fails every recv, back off instead of spinning
+                    tracing::error!(?error, "ingest worker bus error");
+                    counters.errors.fetch_add(1, Ordering::Relaxed);
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    continue;
+                }
+            },
+        };
+
+        match ingest::ingest_message_pooled(&state, sink, &consumer, &message).await {
+            Ok(IngestOutcome::Skipped) => {}
+            Ok(IngestOutcome::DeadLettered) => {
+                counters.messages.fetch_add(1, Ordering::Relaxed);
+                counters.dead_lettered.fetch_add(1, Ordering::Relaxed);
+            }
+            Ok(IngestOutcome::Inserted { rows, rows_inserted }) => {
+                counters.messages.fetch_add(1, Ordering::Relaxed);
+                counters.rows.fetch_add(rows as u64, Ordering::Relaxed);
+                counters.rows_inserted.fetch_add(rows_inserted, Ordering::Relaxed);
+                counters
+                    .rows_duplicated
+                    .fetch_add(rows as u64 - rows_inserted, Ordering::Relaxed);
+            }
+            Err(error) => {
+                // the message was not committed and is read again after the backoff
+                tracing::error!(?error, "ingest worker failed");
+                counters.errors.fetch_add(1, Ordering::Relaxed);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        }
+    }
+}
@@ -2,21 +2,26 @@ use std::sync::Arc;
 
 use axum::{
     Extension, Json, Router,
-    extract::Path,
+    extract::{Path, Query},
     http::StatusCode,
     routing::{delete, get},
 };
 use chrono::NaiveDateTime;
-use tokio::{task::JoinHandle, time::Instant};
+use tokio::time::Instant;
+use tracing::Instrument;
 
 use crate::{
-    dto::{app_error::AppError, app_response::AppResponse},
-    modules::conditions_tiberius::{
-        repository,
-        schema::{Conditions, ConditionsRequest},
+    config::environment::CONFIG,
+    dto::{app_error::AppError, app_response::AppResponse, benchmark_report::ReadBenchmarkReport},
+    modules::{
+        conditions_tiberius::{
+            repository,
+            schema::{Conditions, ConditionsRequest, GenerateRequest, InsertStrategy},
+        },
+        query_plan::{self, schema::PlanRequest},
     },
     state::AppState,
-    util,
+    util::{self, parallel_writer::ParallelWriter, resource_usage::ResourceMeter},
 };
 
 pub fn new() -> Router {
@@ -28,26 +33,46 @@ pub fn new() -> Router {
 }
 
 pub async fn find_all(
+    Query(plan_request): Query<PlanRequest>,
     Extension(_state): Extension<Arc<AppState>>,
-) -> Result<(StatusCode, Json<AppResponse<Vec<Conditions>>>), AppError> {
+) -> Result<(StatusCode, Json<AppResponse<ReadBenchmarkReport>>), AppError> {
     // get db connection
     let pool = _state.pool_tiberius.clone();
     let mut client: deadpool_tiberius::deadpool::managed::Object<deadpool_tiberius::Manager> = pool.get().await.unwrap();
 
     let mut durations = String::new();
-    for _ in 0..10 {
+    let mut iterations = Vec::new();
+    for index in 0..10 {
+        let meter = ResourceMeter::start();
         let start = Instant::now();
-        let _result: Vec<Conditions> = repository::find_all_stream(&mut client).await?;
+        let _result: Vec<Conditions> = repository::find_all_stream(&mut client)
+            .instrument(tracing::info_span!("iteration", index))
+            .await?;
         let duration = start.elapsed();
+        iterations.push(meter.finish());
         durations = format!("{},{}", durations, duration.as_millis());
     }
 
+    let plan = match plan_request.plan.unwrap_or(false) {
+        true => Some(
+            query_plan::repository::explain_sql_server(
+                &mut client,
+                &repository::find_all_statement(),
+                &[],
+                &CONFIG.tiberius_conditions_target(),
+            )
+            .await?,
+        ),
+        false => None,
+    };
+
+    let report = ReadBenchmarkReport { iterations, plan };
     let status_code = StatusCode::OK;
     return Ok((
         status_code,
         Json(AppResponse::ok(
             format!("Time in milliseconds: {} ms", durations),
-            None,
+            Some(report),
         )),
     ));
 }
@@ -66,11 +91,21 @@ pub async fn delete_all(
 
 pub async fn generate(
     Path(size): Path<i32>,
+    Query(generate_request): Query<GenerateRequest>,
     Extension(_state): Extension<Arc<AppState>>,
 ) -> Result<(StatusCode, Json<AppResponse<String>>), AppError> {
     let pool = _state.pool_tiberius.clone();
     let mut client: deadpool_tiberius::deadpool::managed::Object<deadpool_tiberius::Manager> = pool.get().await.unwrap();
 
+    let strategy = generate_request.strategy.unwrap_or_default();
+    let mut batch_size = generate_request
+        .batch_size
+        .unwrap_or(strategy.default_batch_size())
+        .max(1);
+    if strategy == InsertStrategy::Values {
+        batch_size = batch_size.min(InsertStrategy::MAX_VALUES_ROWS);
+    }
+
     let mut durations = String::new();
     for _ in 0..1 {
         let mut conditions_list: Vec<Conditions> = Vec::new();
@@ -92,13 +127,13 @@ pub async fn generate(
             new_conditions.created_on = NaiveDateTime::parse_from_str(&date_string, "%Y-%m-%d %H:%M:%S").unwrap();
             conditions_list.push(new_conditions);
 
-            if conditions_list.len() == 400 {
-                let _result = repository::insert_batch(&mut client, conditions_list.clone()).await;
+            if conditions_list.len() == batch_size {
+                repository::insert_batch_with(&mut client, strategy, conditions_list.clone()).await?;
                 conditions_list.clear();
                 continue;
             }
         }
-        let _result = repository::insert_batch(&mut client, conditions_list.clone()).await;
+        repository::insert_batch_with(&mut client, strategy, conditions_list.clone()).await?;
         conditions_list.clear();
 
         let duration = start.elapsed();
@@ -113,7 +148,7 @@ pub async fn generate(
     return Ok((
         status_code,
         Json(AppResponse::ok(
-            format!("Time in milliseconds: {} ms", durations),
+            format!("Time in milliseconds ({:?}, batch of {}): {} ms", strategy, batch_size, durations),
             None,
         )),
     ));
@@ -124,46 +159,60 @@ pub async fn generate(
 
 pub async fn generate2(
     Path(size): Path<i32>,
+    Query(generate_request): Query<GenerateRequest>,
     Extension(_state): Extension<Arc<AppState>>,
 ) -> Result<(StatusCode, Json<AppResponse<String>>), AppError> {
-    let client = _state.pool_tiberius.clone();
+    let strategy = generate_request.strategy.unwrap_or(InsertStrategy::BulkInsert);
+    let mut batch_size = generate_request.batch_size.unwrap_or(100000).max(1);
+    if strategy == InsertStrategy::Values {
+        batch_size = batch_size.min(InsertStrategy::MAX_VALUES_ROWS);
+    }
+    let workers = generate_request.workers.unwrap_or(CONFIG.tiberius_writer_workers).max(1);
 
     let mut durations = String::new();
     for _ in 0..10 {
         let mut conditions_list: Vec<Conditions> = Vec::new();
         let start = Instant::now();
 
-        let mut join_handlers: Vec<JoinHandle<Result<(), AppError>>> = Vec::new();
-
-        for c in 0..size {
-            let location =
-                util::generator::generate_word(util::generator::generate_numbers_usize(10, 20));
-            let temperature = util::generator::generate_numbers_f64(27.0, 60.0);
-            let humidity = util::generator::generate_numbers_f64(0.0, 100.0);
-
-            let _conditions_request = ConditionsRequest {
-                id: None,
-                location: location,
-                temperature: Some(temperature),
-                humidity: Some(humidity),
-            };
-            let mut new_conditions = Conditions::from_create_request(_conditions_request);
-            let date_string = format!("202{}-01-01 00:00:00", c%6);
-            new_conditions.created_on = NaiveDateTime::parse_from_str(&date_string, "%Y-%m-%d %H:%M:%S").unwrap();
-            conditions_list.push(new_conditions);
-
-            if conditions_list.len() == 100000 {
-                let condition_thread = conditions_list.clone();
-                let mut client_thread = client.get().await.unwrap();
-                join_handlers.push(tokio::spawn(async move {
-                    return repository::insert_batch_2(&mut client_thread, condition_thread).await;
-                }));
-                conditions_list.clear();
+        // every writer holds one pooled connection while it writes a batch
+        let pool = _state.pool_tiberius.clone();
+        let writer = ParallelWriter::spawn(workers, workers, move |batch: Vec<Conditions>| {
+            let pool = pool.clone();
+            async move {
+                let mut client = pool
+                    .get()
+                    .await
+                    .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+                repository::insert_batch_with(&mut client, strategy, batch).await
+            }
+        });
+
+        let generated = async {
+            for c in 0..size {
+                let location =
+                    util::generator::generate_word(util::generator::generate_numbers_usize(10, 20));
+                let temperature = util::generator::generate_numbers_f64(27.0, 60.0);
+                let humidity = util::generator::generate_numbers_f64(0.0, 100.0);
+
+                let _conditions_request = ConditionsRequest {
+                    id: None,
+                    location,
+                    temperature: Some(temperature),
+                    humidity: Some(humidity),
+                };
+                let mut new_conditions = Conditions::from_create_request(_conditions_request);
+                let date_string = format!("202{}-01-01 00:00:00", c%6);
+                new_conditions.created_on = NaiveDateTime::parse_from_str(&date_string, "%Y-%m-%d %H:%M:%S").unwrap();
+                conditions_list.push(new_conditions);
+
+                if conditions_list.len() == batch_size {
+                    writer.send(std::mem::take(&mut conditions_list)).await?;
+                }
             }
+            writer.send(conditions_list).await
         }
-        let mut client_thread = client.get().await.unwrap();
-        let _result = repository::insert_batch_2(&mut client_thread, conditions_list.clone()).await?;
-        conditions_list.clear();
+        .await;
+        writer.finish_after(generated).await?;
 
         let duration = start.elapsed();
         if durations.len() == 0 {
@@ -177,9 +226,8 @@ pub async fn generate2(
     return Ok((
         status_code,
         Json(AppResponse::ok(
-            format!("Time in milliseconds: {} ms", durations),
+            format!("Time in milliseconds ({:?}, batch of {}, {} writers): {} ms", strategy, batch_size, workers, durations),
             None,
         )),
     ));
 }
-
@@ -1,14 +1,22 @@
 use futures_util::StreamExt;
 use tiberius::{IntoRow, QueryItem, ToSql};
 
-use crate::{dto::app_error::AppError, modules::conditions_tiberius::schema::Conditions};
+use crate::{
+    config::environment::CONFIG,
+    dto::app_error::AppError,
+    modules::conditions_tiberius::schema::{Conditions, InsertStrategy},
+};
 
+pub fn find_all_statement() -> String {
+    format!("SELECT * FROM {}", table())
+}
+
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub async fn find_all_stream(
     client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
 ) -> Result<Vec<Conditions>, AppError> {
-    let statement = "SELECT * FROM conditions";
     let mut stream = client
-        .query(statement, &[])
+        .query(find_all_statement(), &[])
         .await
         .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
 
@@ -25,21 +33,24 @@ pub async fn find_all_stream(
             conditions.push(condition);
         }
     }
+    tracing::Span::current().record("rows", conditions.len());
 
     Ok(conditions)
 }
 
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub async fn delete_all(
     client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
 ) -> Result<(), AppError> {
     // let start = Instant::now();
-    let statement = "DELETE FROM conditions";
+    let statement = format!("DELETE FROM {}", table());
     let execute_result = client
         .execute(statement, &[])
         .await
         .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
     // let duration = start.elapsed();
     // println!("{}", duration.as_millis());
+    tracing::Span::current().record("rows", execute_result.rows_affected().iter().sum::<u64>());
 
     if execute_result.rows_affected()[0] == 0 {
         return Err(AppError::NotFound);
@@ -48,18 +59,20 @@ pub async fn delete_all(
     return Ok(());
 }
 
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub async fn delete_by_id(
     client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
     id: String,
 ) -> Result<(), AppError> {
     // let start = Instant::now();
-    let statement = "DELETE FROM conditions where @P1";
+    let statement = format!("DELETE FROM {} WHERE id = @P1", table());
     let execute_result = client
         .execute(statement, &[&id])
         .await
         .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
     // let duration = start.elapsed();
     // println!("{}", duration.as_millis());
+    tracing::Span::current().record("rows", execute_result.rows_affected().iter().sum::<u64>());
 
     if execute_result.rows_affected()[0] == 0 {
         return Err(AppError::NotFound);
@@ -68,12 +81,14 @@ pub async fn delete_by_id(
     return Ok(());
 }
 
+#[tracing::instrument(skip_all, fields(rows = data.len()))]
 pub async fn insert_batch(
     client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
     data: Vec<Conditions>,
 ) -> Result<(), AppError> {
-    let mut query = String::from(
-        "INSERT INTO conditions_partition (id, created_on, location, temperature, humidity) VALUES ",
+    let mut query = format!(
+        "INSERT INTO {} (id, created_on, location, temperature, humidity) VALUES ",
+        table()
     );
 
     let mut params: Vec<Box<dyn ToSql>> = Vec::new();
@@ -107,12 +122,14 @@ pub async fn insert_batch(
     return Ok(());
 }
 
+#[tracing::instrument(skip_all, fields(rows = data.len()))]
 pub async fn insert_batch_2(
     client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
     data: Vec<Conditions>,
 ) -> Result<(), AppError> {
+    let table = table();
     let mut bulk_insert = client
-        .bulk_insert("master.dbo.conditions")
+        .bulk_insert(&table)
         .await
         .map_err(|err| AppError::Other(format!("{:?}", err)))?;
 
@@ -127,6 +144,176 @@ pub async fn insert_batch_2(
         .finalize()
         .await
         .map_err(|err| AppError::Other(format!("{:?}", err)))?;
-    println!("Result: {:?}", res);
+    tracing::debug!(result = ?res, "bulk insert finished");
     return Ok(());
 }
+
+pub async fn insert_batch_with(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    strategy: InsertStrategy,
+    data: Vec<Conditions>,
+) -> Result<(), AppError> {
+    if data.is_empty() {
+        return Ok(());
+    }
+
+    match strategy {
+        InsertStrategy::Values => insert_batch(client, data).await,
+        InsertStrategy::BulkInsert => insert_batch_2(client, data).await,
+        InsertStrategy::SingleRow => insert_batch_single_row(client, data).await,
+        InsertStrategy::OpenJson => insert_batch_openjson(client, data).await,
+        InsertStrategy::Staging => insert_batch_staging(client, data).await.map(|_| ()),
+    }
+}
+
+#[tracing::instrument(skip_all, fields(rows = data.len()))]
+pub async fn insert_batch_single_row(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    data: Vec<Conditions>,
+) -> Result<(), AppError> {
+    let statement = format!(
+        "INSERT INTO {} (id, created_on, location, temperature, humidity) VALUES (@P1, @P2, @P3, @P4, @P5)",
+        table()
+    );
+
+    run_batch(client, "BEGIN TRANSACTION").await?;
+    for condition in &data {
+        let result = client
+            .execute(
+                statement.as_str(),
+                &[
+                    &condition.id,
+                    &condition.created_on,
+                    &condition.location,
+                    &condition.temperature,
+                    &condition.humidity,
+                ],
+            )
+            .await;
+        if let Err(err) = result {
+            let _ = run_batch(client, "ROLLBACK TRANSACTION").await;
+            return Err(AppError::Other(format!("{:?}", err)));
+        }
+    }
+    run_batch(client, "COMMIT TRANSACTION").await
+}
+
+#[tracing::instrument(skip_all, fields(rows = data.len()))]
+pub async fn insert_batch_openjson(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    data: Vec<Conditions>,
+) -> Result<(), AppError> {
+    // created_on as ISO 8601 with fractions, the serde format of Conditions is local time to the second
+    let rows: Vec<serde_json::Value> = data
+        .iter()
+        .map(|condition| {
+            serde_json::json!({
+                "id": condition.id,
+                "created_on": condition.created_on.format("%Y-%m-%dT%H:%M:%S%.f").to_string(),
+                "location": condition.location,
+                "temperature": condition.temperature,
+                "humidity": condition.humidity,
+            })
+        })
+        .collect();
+    let payload = serde_json::to_string(&rows).map_err(|err| AppError::Other(format!("{:?}", err)))?;
+
+    let statement = format!(
+        "INSERT INTO {} (id, created_on, location, temperature, humidity)
+        SELECT id, created_on, location, temperature, humidity
+        FROM OPENJSON(@P1) WITH (
+            id VARCHAR(64) '$.id',
+            created_on DATETIME2 '$.created_on',
+            location NVARCHAR(MAX) '$.location',
+            temperature FLOAT '$.temperature',
+            humidity FLOAT '$.humidity'
+        )",
+        table()
+    );
+    client
+        .execute(statement, &[&payload])
+        .await
+        .map_err(|err| AppError::Other(format!("{:?}", err)))?;
+    Ok(())
+}
+
+#[tracing::instrument(skip_all, fields(rows = data.len()))]
+// returns the rows actually inserted, rows already in the table are not counted
+pub async fn insert_batch_staging(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    data: Vec<Conditions>,
+) -> Result<u64, AppError> {
+    if data.is_empty() {
+        return Ok(0);
+    }
+
+    // created as a plain batch, a temp table created through sp_executesql is gone when it returns
+    run_batch(
+        client,
+        "DROP TABLE IF EXISTS #conditions_staging;
+        CREATE TABLE #conditions_staging (
+            id VARCHAR(64) NOT NULL,
+            created_on DATETIME2 NOT NULL,
+            location NVARCHAR(MAX) NOT NULL,
+            temperature FLOAT NULL,
+            humidity FLOAT NULL
+        )",
+    )
+    .await?;
+
+    let mut bulk_insert = client
+        .bulk_insert("#conditions_staging")
+        .await
+        .map_err(|err| AppError::Other(format!("{:?}", err)))?;
+    for condition in &data {
+        bulk_insert
+            .send(Conditions::to_tiberius_row(condition))
+            .await
+            .map_err(|err| AppError::Other(format!("{:?}", err)))?;
+    }
+    bulk_insert
+        .finalize()
+        .await
+        .map_err(|err| AppError::Other(format!("{:?}", err)))?;
+
+    // rows whose key is already in the table are skipped, so a replayed batch is harmless, and
+    // a key repeated inside the batch is inserted once
+    let statement = format!(
+        "INSERT INTO {0} (id, created_on, location, temperature, humidity)
+        SELECT s.id, s.created_on, s.location, s.temperature, s.humidity
+        FROM (
+            SELECT *, ROW_NUMBER() OVER (PARTITION BY id, created_on ORDER BY (SELECT NULL)) AS occurrence
+            FROM #conditions_staging
+        ) s
+        WHERE s.occurrence = 1 AND NOT EXISTS (
+            SELECT 1 FROM {0} c WHERE c.id = s.id AND c.created_on = s.created_on
+        )",
+        table()
+    );
+    // the session temp table is visible to the sp_executesql scope execute runs in
+    let rows_inserted = client
+        .execute(statement, &[])
+        .await
+        .map_err(|err| AppError::Other(format!("{:?}", err)))?
+        .total();
+    run_batch(client, "DROP TABLE #conditions_staging").await?;
+    Ok(rows_inserted)
+}
+
+fn table() -> String {
+    CONFIG.tiberius_conditions_target().qualified()
+}
+
+async fn run_batch(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    statement: &str,
+) -> Result<(), AppError> {
+    client
+        .simple_query(statement)
+        .await
+        .map_err(|err| AppError::Other(format!("{:?}", err)))?
+        .into_results()
+        .await
+        .map_err(|err| AppError::Other(format!("{:?}", err)))?;
+    Ok(())
+}
@@ -88,3 +88,41 @@ impl Conditions {
 pub struct CountResult {
     pub count: i64,
 }
+
+// how the generate benchmark writes a batch to SQL Server
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum InsertStrategy {
+    // one INSERT ... VALUES statement, five parameters per row
+    #[default]
+    Values,
+    BulkInsert,
+    // one parameterized INSERT per row inside a transaction
+    SingleRow,
+    // the batch as a single JSON parameter shredded by OPENJSON, tiberius has no
+    // table-valued parameters so this is the closest equivalent
+    OpenJson,
+    // bulk insert into a session temp table, then one INSERT ... SELECT skipping existing keys
+    Staging,
+}
+
+impl InsertStrategy {
+    pub fn default_batch_size(self) -> usize {
+        match self {
+            InsertStrategy::Values => InsertStrategy::MAX_VALUES_ROWS,
+            InsertStrategy::SingleRow => 1000,
+            InsertStrategy::BulkInsert | InsertStrategy::OpenJson | InsertStrategy::Staging => 10000,
+        }
+    }
+
+    // sql server accepts at most 2100 parameters per statement
+    pub const MAX_VALUES_ROWS: usize = 400;
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GenerateRequest {
+    pub strategy: Option<InsertStrategy>,
+    pub batch_size: Option<usize>,
+    // parallel writers for generate-2, TIBERIUS_WRITER_WORKERS when not set
+    pub workers: Option<usize>,
+}
@@ -2,75 +2,69 @@ use std::sync::Arc;
 
 use axum::{
     Extension, Json, Router,
-    extract::Path,
+    extract::{Path, Query},
     http::StatusCode,
-    routing::{delete, get},
+    routing::{delete, get, post},
 };
-use chrono::{NaiveDate, NaiveDateTime};
-use futures_util::future::join_all;
-use tokio::{task::JoinHandle, time::{Instant, sleep, Duration}};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use futures_util::{StreamExt, future::join_all};
+use tokio::time::Instant;
+use tracing::Instrument;
 
 use crate::{
-    dto::{app_error::AppError, app_response::AppResponse},
-    modules::conditions_tiberius_columns::{
-        repository,
-        schema::{Conditions, ConditionsRequest},
+    config::environment::CONFIG,
+    dto::{app_error::AppError, app_response::AppResponse, benchmark_report::ReadBenchmarkReport},
+    modules::{
+        conditions_tiberius_columns::{
+            repository,
+            schema::{
+                Conditions, ConditionsRequest, GenerateRequest, IndexDefinition, IndexExperimentReport,
+                IndexExperimentRequest, IndexSet, IndexSetReport, IndexSwitch, ParallelReadReport,
+                ParallelReadRequest, ReadStrategy,
+            },
+        },
+        query_plan::{self, schema::{PlanRequest, QueryPlan}},
+        storage,
     },
     state::AppState,
-    util,
+    util::{parallel_writer::ParallelWriter, resource_usage::ResourceMeter},
 };
 
 pub fn new() -> Router {
     Router::new()
         .route("/list_page", get(find_all_by_page))
         .route("/list", get(find_all))
+        .route("/list_parallel", get(find_all_parallel))
         .route("/delete", delete(delete_all))
         .route("/generate/{size}", get(generate))
+        .route("/index_experiment", post(index_experiment))
 }
 
 pub async fn find_all_by_page(
     Extension(_state): Extension<Arc<AppState>>,
 ) -> Result<(StatusCode, Json<AppResponse<Vec<Conditions>>>), AppError> {
     let mut durations = String::new();
-    for c in 0..10 {
+    for index in 0..10 {
         let start = Instant::now();
 
         // get total data
-        let client = _state.pool_tiberius.clone();
-        let mut client_total_data = client.get().await.unwrap();
+        let mut client_total_data = get_client(&_state).await?;
         let total_data = repository::count_data(&mut client_total_data).await?;
+        drop(client_total_data);
 
         let limit = 10000;
-        let mut total_page = total_data / limit;
-        if total_data < limit {
-            total_page = 0;
-        } else if total_data % limit > 0 {
-            total_page = total_page + 1;
-        }
+        let total_page = (total_data + limit - 1) / limit;
 
-        // get the data
+        // get the data, get() waits for a page task to hand its connection back when the pool is exhausted
         let mut handles = Vec::new();
         let mut conditions: Vec<Conditions> = Vec::new();
         for p in 0..total_page {
-            let client = _state.pool_tiberius.clone();
-
-            let mut client_thread_result = client.get().await.map_err(|err| AppError::Other(format!("{:#?}", err)));
-            loop {
-                match client_thread_result {
-                    Ok(d) => {
-                        let mut client_thread: deadpool_tiberius::deadpool::managed::Object<deadpool_tiberius::Manager> = d;
-                        let handle = tokio::spawn(async move {
-                            repository::find_all_stream_pagination(&mut client_thread, p * limit, limit).await
-                        });
-                        handles.push(handle);
-                        break;
-                    },
-                    Err(_) => {
-                        client_thread_result = client.get().await.map_err(|err| AppError::Other(format!("{:#?}", err)));
-                        let _ = sleep(Duration::from_millis(500)).await;
-                    },
-                }
-            };
+            let mut client_thread = get_client(&_state).await?;
+            let handle = tokio::spawn(
+                async move { repository::find_all_stream_pagination(&mut client_thread, p * limit, limit).await }
+                    .in_current_span(),
+            );
+            handles.push(handle);
         }
 
         let result = join_all(handles).await;
@@ -83,8 +77,7 @@ pub async fn find_all_by_page(
         let duration = start.elapsed();
         durations = format!("{},{}", durations, duration.as_millis());
         // println!("{:?}", _result);
-        println!("total data: {}", conditions.len());
-        println!("{:?}", c)
+        tracing::debug!(iteration = index, rows = conditions.len(), "pages read");
     }
 
     let status_code = StatusCode::OK;
@@ -97,28 +90,223 @@ pub async fn find_all_by_page(
     ));
 }
 
+pub async fn find_all_parallel(
+    Query(read_request): Query<ParallelReadRequest>,
+    Query(plan_request): Query<PlanRequest>,
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, Json<AppResponse<ParallelReadReport>>), AppError> {
+    let strategy = read_request.strategy.unwrap_or_default();
+    // every unit in flight holds a pooled connection, more would only queue on the pool
+    let max_dop = _state.pool_tiberius.status().max_size;
+    let dop = match read_request.dop {
+        Some(dop) if dop > max_dop => {
+            return Err(AppError::invalid_request(
+                "dop",
+                format!("dop must be at most {}, the tiberius pool size", max_dop),
+            ));
+        }
+        Some(dop) => dop.max(1),
+        None => CONFIG.tiberius_writer_workers.clamp(1, max_dop),
+    };
+    // the same window as the other reads, BETWEEN '2023-01-01' and '2023-12-31'
+    let from = read_request
+        .from
+        .unwrap_or(NaiveDate::from_ymd_opt(2023, 1, 1).unwrap())
+        .and_time(NaiveTime::MIN);
+    let to = read_request
+        .to
+        .unwrap_or(NaiveDate::from_ymd_opt(2023, 12, 31).unwrap())
+        .and_time(NaiveTime::MIN);
+    if to < from {
+        return Err(AppError::invalid_request("to", "to is before from"));
+    }
+    let page_size = read_request.page_size.unwrap_or(10000).max(1);
+    let partition_function = read_request
+        .partition_function
+        .unwrap_or("PF_conditions_yearly".to_string());
+
+    let mut report = ParallelReadReport { strategy, dop, units: 0, rows: 0, durations_ms: Vec::new(), plan: None };
+    let mut first_unit = None;
+    for _ in 0..10 {
+        let start = Instant::now();
+
+        // planning is timed too, it is part of what each strategy costs
+        let mut client = _state
+            .pool_tiberius
+            .get()
+            .await
+            .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+        let units = match strategy {
+            ReadStrategy::Page => {
+                let total_data = repository::count_range(&mut client, from, to).await?;
+                let total_page = (total_data + page_size - 1) / page_size;
+                (0..total_page)
+                    .map(|page| ReadUnit::Page { offset: page * page_size, limit: page_size })
+                    .collect()
+            }
+            ReadStrategy::Range => range_units(from, to, dop)?,
+            ReadStrategy::Partition => {
+                let (first, last) =
+                    repository::partition_range(&mut client, &partition_function, from, to).await?;
+                (first..=last).map(ReadUnit::Partition).collect()
+            }
+        };
+        drop(client);
+
+        report.units = units.len();
+        first_unit = units.first().copied();
+        report.rows = read_units(&_state, units, dop, from, to, &partition_function).await?;
+        report.durations_ms.push(start.elapsed().as_millis());
+    }
+
+    if plan_request.plan.unwrap_or(false)
+        && let Some(unit) = first_unit
+    {
+        let mut client = get_client(&_state).await?;
+        report.plan = Some(explain_unit(&mut client, unit, from, to, &partition_function).await?);
+    }
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", Some(report)))))
+}
+
+// runs the insert and read benchmarks once per index set and compares every set with the first
+pub async fn index_experiment(
+    Extension(_state): Extension<Arc<AppState>>,
+    Json(experiment_request): Json<IndexExperimentRequest>,
+) -> Result<(StatusCode, Json<AppResponse<IndexExperimentReport>>), AppError> {
+    if experiment_request.index_sets.is_empty() {
+        return Err(AppError::invalid_request("index_sets", "at least one index set is required"));
+    }
+    let indexes = experiment_indexes(&experiment_request.index_sets)?;
+
+    let settings = ExperimentSettings {
+        switch: experiment_request.switch.unwrap_or_default(),
+        size: experiment_request.size.unwrap_or(100000).max(1),
+        runs: experiment_request.runs.unwrap_or(3).max(1),
+        batch_size: experiment_request.batch_size.unwrap_or(10000).max(1),
+        workers: experiment_request.workers.unwrap_or(CONFIG.tiberius_writer_workers).max(1),
+        // the same window as the other reads
+        from: experiment_request
+            .from
+            .unwrap_or(NaiveDate::from_ymd_opt(2023, 1, 1).unwrap())
+            .and_time(NaiveTime::MIN),
+        to: experiment_request
+            .to
+            .unwrap_or(NaiveDate::from_ymd_opt(2023, 12, 31).unwrap())
+            .and_time(NaiveTime::MIN),
+    };
+
+    // cleanup drops every index of the experiment, so none of them may exist beforehand
+    let mut client = get_client(&_state).await?;
+    let existing = repository::index_names(&mut client).await?;
+    drop(client);
+    if let Some(index) = indexes
+        .iter()
+        .find(|index| existing.iter().any(|name| name.eq_ignore_ascii_case(&index.name)))
+    {
+        return Err(AppError::invalid_request(
+            "index_sets",
+            format!("index {} already exists on the table", index.name),
+        ));
+    }
+
+    let mut sets = Vec::new();
+    let mut result = Ok(());
+    if settings.switch == IndexSwitch::DisableRebuild {
+        let mut client = get_client(&_state).await?;
+        for index in &indexes {
+            result = repository::create_index(&mut client, index).await;
+            if result.is_err() {
+                break;
+            }
+        }
+    }
+    if result.is_ok() {
+        for index_set in &experiment_request.index_sets {
+            match run_index_set(&_state, &settings, index_set, &indexes).await {
+                Ok(report) => sets.push(report),
+                Err(error) => {
+                    result = Err(error);
+                    break;
+                }
+            }
+        }
+    }
+
+    // the experiment's indexes never outlive it, even when a run failed
+    let mut client = get_client(&_state).await?;
+    for index in &indexes {
+        let dropped = repository::drop_index(&mut client, &index.name).await;
+        if result.is_ok() {
+            result = dropped;
+        }
+    }
+    result?;
+
+    let baseline_insert = median(&sets[0].insert_ms);
+    let baseline_read = median(&sets[0].read_ms);
+    for report in &mut sets {
+        if baseline_insert > 0.0 {
+            report.insert_overhead_percent =
+                Some((median(&report.insert_ms) - baseline_insert) / baseline_insert * 100.0);
+        }
+        if baseline_read > 0.0 {
+            report.read_gain_percent = Some((baseline_read - median(&report.read_ms)) / baseline_read * 100.0);
+        }
+    }
+
+    let report = IndexExperimentReport {
+        switch: settings.switch,
+        size: settings.size,
+        runs: settings.runs,
+        sets,
+    };
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", Some(report)))))
+}
+
 pub async fn find_all(
+    Query(plan_request): Query<PlanRequest>,
     Extension(_state): Extension<Arc<AppState>>,
-) -> Result<(StatusCode, Json<AppResponse<Vec<Conditions>>>), AppError> {
+) -> Result<(StatusCode, Json<AppResponse<ReadBenchmarkReport>>), AppError> {
     let client = _state.pool_tiberius.clone();
     let mut client_thread = client.get().await.unwrap();
 
     let mut durations = String::new();
-    for c in 0..10 {
+    let mut iterations = Vec::new();
+    for index in 0..10 {
+        let meter = ResourceMeter::start();
         let start = Instant::now();
-        let _result: Vec<Conditions> = repository::find_all_stream(&mut client_thread).await?;
+        let _result: Vec<Conditions> = repository::find_all_stream(&mut client_thread)
+            .instrument(tracing::info_span!("iteration", index))
+            .await?;
         let duration = start.elapsed();
+        iterations.push(meter.finish());
         durations = format!("{},{}", durations, duration.as_millis());
         // println!("{:?}", _result);
-        println!("{:?}", c)
     }
 
+    let plan = match plan_request.plan.unwrap_or(false) {
+        true => Some(
+            query_plan::repository::explain_sql_server(
+                &mut client_thread,
+                &repository::find_all_statement(),
+                &[],
+                &CONFIG.tiberius_columns_target(),
+            )
+            .await?,
+        ),
+        false => None,
+    };
+
+    let report = ReadBenchmarkReport { iterations, plan };
     let status_code = StatusCode::OK;
     return Ok((
         status_code,
         Json(AppResponse::ok(
             format!("Time in milliseconds: {} ms", durations),
-            None,
+            Some(report),
         )),
     ));
 }
@@ -137,17 +325,155 @@ pub async fn delete_all(
 
 pub async fn generate(
     Path(size): Path<i32>,
+    Query(generate_request): Query<GenerateRequest>,
     Extension(_state): Extension<Arc<AppState>>,
 ) -> Result<(StatusCode, Json<AppResponse<String>>), AppError> {
-    let client = _state.pool_tiberius.clone();
+    let batch_size = generate_request.batch_size.unwrap_or(10000).max(1);
+    let workers = generate_request.workers.unwrap_or(CONFIG.tiberius_writer_workers).max(1);
 
     let mut durations = String::new();
     for _ in 0..10 {
-        let mut conditions_list: Vec<Conditions> = Vec::new();
         let start = Instant::now();
+        insert_generated(&_state, size, batch_size, workers).await?;
 
-        let mut join_handlers: Vec<JoinHandle<Result<(), AppError>>> = Vec::new();
+        let duration = start.elapsed();
+        if durations.len() == 0 {
+            durations = format!("{}", duration.as_millis());
+            continue;
+        }
+        durations = format!("{},{}", durations, duration.as_millis());
+    }
 
+    let status_code = StatusCode::OK;
+    return Ok((
+        status_code,
+        Json(AppResponse::ok(
+            format!("Time in milliseconds (batch of {}, {} writers): {} ms", batch_size, workers, durations),
+            None,
+        )),
+    ));
+}
+
+#[derive(Clone, Copy)]
+enum ReadUnit {
+    Page { offset: i32, limit: i32 },
+    Range { from: NaiveDateTime, to: NaiveDateTime, to_inclusive: bool },
+    Partition(i32),
+}
+
+// `dop` equal slices of [from, to], the last one keeps `to` itself
+fn range_units(from: NaiveDateTime, to: NaiveDateTime, dop: usize) -> Result<Vec<ReadUnit>, AppError> {
+    let slices = i32::try_from(dop).map_err(|_| AppError::invalid_request("dop", "dop is out of range"))?;
+    let step = (to - from) / slices;
+    if step.is_zero() {
+        return Ok(vec![ReadUnit::Range { from, to, to_inclusive: true }]);
+    }
+
+    let units = (0..slices)
+        .map(|index| {
+            let unit_from = from + step * index;
+            if index == slices - 1 {
+                ReadUnit::Range { from: unit_from, to, to_inclusive: true }
+            } else {
+                ReadUnit::Range { from: unit_from, to: unit_from + step, to_inclusive: false }
+            }
+        })
+        .collect();
+    Ok(units)
+}
+
+// the statement of one unit with its parameters, as read_units runs it
+async fn explain_unit(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    unit: ReadUnit,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+    partition_function: &str,
+) -> Result<QueryPlan, AppError> {
+    let target = CONFIG.tiberius_columns_target();
+    match unit {
+        ReadUnit::Page { offset, limit } => {
+            let statement = repository::find_page_statement();
+            query_plan::repository::explain_sql_server(client, &statement, &[&from, &to, &offset, &limit], &target)
+                .await
+        }
+        ReadUnit::Range { from, to, to_inclusive } => {
+            let statement = repository::find_range_statement(to_inclusive);
+            query_plan::repository::explain_sql_server(client, &statement, &[&from, &to], &target).await
+        }
+        ReadUnit::Partition(partition) => {
+            let statement = repository::find_partition_statement(partition_function)?;
+            query_plan::repository::explain_sql_server(client, &statement, &[&partition, &from, &to], &target)
+                .await
+        }
+    }
+}
+
+// at most `dop` units are read at once, each on its own task and pooled connection
+async fn read_units(
+    state: &Arc<AppState>,
+    units: Vec<ReadUnit>,
+    dop: usize,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+    partition_function: &str,
+) -> Result<usize, AppError> {
+    let mut reads = futures_util::stream::iter(units)
+        .map(|unit| {
+            let pool = state.pool_tiberius.clone();
+            let partition_function = partition_function.to_string();
+            tokio::spawn(async move {
+                let mut client = pool
+                    .get()
+                    .await
+                    .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+                let rows = match unit {
+                    ReadUnit::Page { offset, limit } => {
+                        repository::find_page(&mut client, from, to, offset, limit).await?
+                    }
+                    ReadUnit::Range { from, to, to_inclusive } => {
+                        repository::find_range(&mut client, from, to, to_inclusive).await?
+                    }
+                    ReadUnit::Partition(partition) => {
+                        repository::find_partition(&mut client, &partition_function, partition, from, to)
+                            .await?
+                    }
+                };
+                Ok::<usize, AppError>(rows.len())
+            })
+        })
+        .buffer_unordered(dop);
+
+    let mut rows = 0;
+    while let Some(result) = reads.next().await {
+        rows += result.map_err(|error| AppError::Other(format!("read task failed: {}", error)))??;
+    }
+    Ok(rows)
+}
+
+// `size` generated rows spread over 2020 to 2025, written by `workers` parallel writers
+async fn insert_generated(
+    state: &AppState,
+    size: i32,
+    batch_size: usize,
+    workers: usize,
+) -> Result<(), AppError> {
+    let mut conditions_list: Vec<Conditions> = Vec::new();
+
+    // pool.get() waits for a free connection, the writer count bounds how many are taken
+    let pool = state.pool_tiberius.clone();
+    let writer = ParallelWriter::spawn(workers, workers, move |batch: Vec<Conditions>| {
+        let pool = pool.clone();
+        async move {
+            let mut client = pool
+                .get()
+                .await
+                .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+            repository::insert_batch(&mut client, batch).await
+        }
+    });
+
+    let generated = async {
         for c in 0..size {
             let mut new_conditions =
                 Conditions::from_create_request(ConditionsRequest::generate_request());
@@ -159,75 +485,185 @@ pub async fn generate(
             new_conditions.modified_on =
                 NaiveDateTime::parse_from_str(&datetime_string, "%Y-%m-%d %H:%M:%S")
                     .map_err(|err| AppError::Other(format!("{:?}", err)))?;
-            conditions_list.push(new_conditions.clone());
-            // println!("{:?}", new_conditions);
-
-            if conditions_list.len() == 10000 {
-                let condition_thread = conditions_list.clone();
-                let mut client_thread_result = client.get().await.map_err(|err| AppError::Other(format!("{:#?}", err)));
-                loop {
-                    match client_thread_result {
-                        Ok(d) => {
-                            let mut client_thread: deadpool_tiberius::deadpool::managed::Object<deadpool_tiberius::Manager> = d;
-                            join_handlers.push(tokio::spawn(async move {
-                                return repository::insert_batch(&mut client_thread, condition_thread).await;
-                            }));
-                            conditions_list.clear();
-                            break;
-                        },
-                        Err(_) => {
-                            client_thread_result = client.get().await.map_err(|err| AppError::Other(format!("{:#?}", err)));
-                            let _ = sleep(Duration::from_millis(500)).await;
-                        },
-                    }
-                };
+            conditions_list.push(new_conditions);
+
+            if conditions_list.len() == batch_size {
+                writer.send(std::mem::take(&mut conditions_list)).await?;
             }
         }
+        writer.send(conditions_list).await
+    }
+    .await;
+    writer.finish_after(generated).await?;
+    Ok(())
+}
 
-        let condition_thread = conditions_list.clone();
-        let mut client_thread_result = client.get().await.map_err(|err| AppError::Other(format!("{:#?}", err)));
-        loop {
-            match client_thread_result {
-                Ok(d) => {
-                    let mut client_thread: deadpool_tiberius::deadpool::managed::Object<deadpool_tiberius::Manager> = d;
-                    join_handlers.push(tokio::spawn(async move {
-                        return repository::insert_batch(&mut client_thread, condition_thread).await;
-                    }));
-                    conditions_list.clear();
-                    break;
-                },
-                Err(_) => {
-                    client_thread_result = client.get().await.map_err(|err| AppError::Other(format!("{:#?}", err)));
-                    let _ = sleep(Duration::from_millis(500)).await;
-                },
+// every index of every set once, the same name must mean the same definition in all sets
+fn experiment_indexes(index_sets: &[IndexSet]) -> Result<Vec<IndexDefinition>, AppError> {
+    let mut indexes: Vec<IndexDefinition> = Vec::new();
+    for index in index_sets.iter().flat_map(|index_set| index_set.indexes.iter()) {
+        index.validate()?;
+        match indexes.iter().find(|existing| existing.name == index.name) {
+            Some(existing) if existing.columns != index.columns || existing.include != index.include => {
+                return Err(AppError::invalid_request(
+                    "index_sets",
+                    format!("index {} is defined differently in two sets", index.name),
+                ));
             }
-        };
+            Some(_) => {}
+            None => indexes.push(index.clone()),
+        }
+    }
+    Ok(indexes)
+}
 
-        let result = join_all(join_handlers).await;
+// the settings shared by every index set of an experiment
+struct ExperimentSettings {
+    switch: IndexSwitch,
+    size: i32,
+    runs: usize,
+    batch_size: usize,
+    workers: usize,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+}
 
-        for r in result {
-            match r {
-                Err(err) => {
-                    println!("{:?}", err)
-                }
-                Ok(_) => {}
+async fn run_index_set(
+    state: &AppState,
+    settings: &ExperimentSettings,
+    index_set: &IndexSet,
+    indexes: &[IndexDefinition],
+) -> Result<IndexSetReport, AppError> {
+    let mut client = get_client(state).await?;
+    // indexes are switched on an empty table, so setup_ms is the ddl cost and not a rebuild of old rows
+    clear_table(&mut client).await?;
+
+    let start = Instant::now();
+    match settings.switch {
+        IndexSwitch::CreateDrop => {
+            for index in &index_set.indexes {
+                repository::create_index(&mut client, index).await?;
+            }
+        }
+        IndexSwitch::DisableRebuild => {
+            for index in indexes {
+                let enabled = index_set.indexes.iter().any(|set_index| set_index.name == index.name);
+                repository::alter_index(&mut client, &index.name, enabled).await?;
             }
         }
+    }
+    let setup_ms = start.elapsed().as_millis();
+    drop(client);
 
-        let duration = start.elapsed();
-        if durations.len() == 0 {
-            durations = format!("{}", duration.as_millis());
-            continue;
+    let mut report = IndexSetReport {
+        name: index_set.name.clone(),
+        indexes: index_set.indexes.iter().map(|index| index.name.clone()).collect(),
+        setup_ms,
+        insert_ms: Vec::new(),
+        read_ms: Vec::new(),
+        rows_read: 0,
+        index_bytes: None,
+        insert_overhead_percent: None,
+        read_gain_percent: None,
+    };
+    for run in 0..settings.runs {
+        if run > 0 {
+            let mut client = get_client(state).await?;
+            clear_table(&mut client).await?;
         }
-        durations = format!("{},{}", durations, duration.as_millis());
+
+        let start = Instant::now();
+        insert_generated(state, settings.size, settings.batch_size, settings.workers).await?;
+        report.insert_ms.push(start.elapsed().as_millis());
+
+        let mut client = get_client(state).await?;
+        let start = Instant::now();
+        report.rows_read = repository::find_range(&mut client, settings.from, settings.to, true).await?.len();
+        report.read_ms.push(start.elapsed().as_millis());
     }
 
-    let status_code = StatusCode::OK;
-    return Ok((
-        status_code,
-        Json(AppResponse::ok(
-            format!("Time in milliseconds: {} ms", durations),
-            None,
-        )),
-    ));
+    let mut client = get_client(state).await?;
+    let stats = storage::repository::sql_server_stats(&mut client, &CONFIG.tiberius_columns_target()).await?;
+    report.index_bytes = Some(stats.index_bytes);
+
+    if settings.switch == IndexSwitch::CreateDrop {
+        for index in &index_set.indexes {
+            repository::drop_index(&mut client, &index.name).await?;
+        }
+    }
+    Ok(report)
+}
+
+async fn get_client(
+    state: &AppState,
+) -> Result<deadpool_tiberius::deadpool::managed::Object<deadpool_tiberius::Manager>, AppError> {
+    state
+        .pool_tiberius
+        .get()
+        .await
+        .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))
+}
+
+// delete_all reports an already empty table as not found
+async fn clear_table(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+) -> Result<(), AppError> {
+    match repository::delete_all(client).await {
+        Err(AppError::NotFound) => Ok(()),
+        result => result,
+    }
+}
+
+fn median(durations: &[u128]) -> f64 {
+    let mut sorted = durations.to_vec();
+    sorted.sort_unstable();
+    match sorted.len() {
+        0 => 0.0,
+        len if len % 2 == 1 => sorted[len / 2] as f64,
+        len => (sorted[len / 2 - 1] + sorted[len / 2]) as f64 / 2.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_units_cover_the_window() {
+        let from = NaiveDate::from_ymd_opt(2023, 1, 1).unwrap().and_time(NaiveTime::MIN);
+        let to = NaiveDate::from_ymd_opt(2023, 12, 31).unwrap().and_time(NaiveTime::MIN);
+
+        let units = range_units(from, to, 4).unwrap();
+        assert_eq!(units.len(), 4);
+        assert!(matches!(units[0], ReadUnit::Range { from: start, to_inclusive: false, .. } if start == from));
+        assert!(matches!(units[3], ReadUnit::Range { to: end, to_inclusive: true, .. } if end == to));
+
+        // a window shorter than dop is read as one unit
+        assert_eq!(range_units(from, from, 4).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn range_units_reject_a_dop_beyond_i32() {
+        let from = NaiveDate::from_ymd_opt(2023, 1, 1).unwrap().and_time(NaiveTime::MIN);
+        let to = NaiveDate::from_ymd_opt(2023, 12, 31).unwrap().and_time(NaiveTime::MIN);
+
+        let result = range_units(from, to, 1 << 32);
+        assert!(matches!(result, Err(AppError::InvalidRequest(_))));
+    }
+
+    #[test]
+    fn conflicting_index_definitions_are_request_errors() {
+        let index_set = |include: &[&str]| IndexSet {
+            name: "set".to_string(),
+            indexes: vec![IndexDefinition {
+                name: "ix_location_1".to_string(),
+                columns: vec!["location_1".to_string()],
+                include: include.iter().map(|column| column.to_string()).collect(),
+            }],
+        };
+
+        assert_eq!(experiment_indexes(&[index_set(&[]), index_set(&[])]).unwrap().len(), 1);
+        let result = experiment_indexes(&[index_set(&[]), index_set(&["humidity_1"])]);
+        assert!(matches!(result, Err(AppError::InvalidRequest(_))));
+    }
 }
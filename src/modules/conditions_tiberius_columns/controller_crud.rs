@@ -1,40 +1,154 @@
 use std::sync::Arc;
 
-use axum::{Extension, Json, Router, http::StatusCode, routing::get};
-use tokio::time::Instant;
-
-use crate::{dto::{app_error::AppError, app_response::AppResponse}, modules::conditions_tiberius_columns::{repository, schema::Conditions}, state::AppState};
-
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, Query},
+    http::StatusCode,
+    routing::{delete, get, patch, post, put},
+};
+use validator::{Validate, ValidationError, ValidationErrors};
 
+use crate::{
+    dto::{app_error::AppError, app_response::AppResponse},
+    modules::conditions_tiberius_columns::{
+        repository,
+        schema::{Conditions, ConditionsPatchRequest, ConditionsRequest, PageRequest},
+    },
+    state::AppState,
+};
 
 pub fn new() -> Router {
     Router::new()
         .route("/list", get(find_all))
-        // .route("/delete", delete(delete_all))
-        // .route("/generate/{size}", get(generate))
+        .route("/", post(create))
+        .route("/", put(update))
+        .route("/{id}", patch(patch_by_id))
+        .route("/{id}", delete(delete_by_id))
+        .route("/{id}", get(find_by_id))
 }
 
 pub async fn find_all(
+    Query(page_request): Query<PageRequest>,
     Extension(_state): Extension<Arc<AppState>>,
 ) -> Result<(StatusCode, Json<AppResponse<Vec<Conditions>>>), AppError> {
-    let client = _state.pool_tiberius.clone();
-    let mut client_thread: deadpool_tiberius::deadpool::managed::Object<deadpool_tiberius::Manager> = client.get().await.unwrap();
-
-    let mut durations = String::new();
-    for _ in 0..10 {
-        let start = Instant::now();
-        let _result: Vec<Conditions> = repository::find_all_stream(&mut client_thread).await?;
-        let duration = start.elapsed();
-        durations = format!("{},{}", durations, duration.as_millis());
-        println!("{:?}", _result);
+    let page = page_request.page.unwrap_or(0).max(0);
+    let size = page_request.size.unwrap_or(10).clamp(1, 1000);
+    let offset = page.checked_mul(size).ok_or_else(|| {
+        let mut errors = ValidationErrors::new();
+        errors.add("page", ValidationError::new("range").with_message("page * size is out of range".into()));
+        AppError::InvalidRequest(errors)
+    })?;
+
+    let mut client = _state
+        .pool_tiberius
+        .get()
+        .await
+        .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+
+    let _result: Vec<Conditions> = repository::find_all(&mut client, offset, size).await?;
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", Some(_result)))))
+}
+
+pub async fn find_by_id(
+    Path(id): Path<String>,
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, Json<AppResponse<Conditions>>), AppError> {
+    let mut client = _state
+        .pool_tiberius
+        .get()
+        .await
+        .map_err(|error| AppError::Other(format!("get connection failed {:?}, id: {}", error, id)))?;
+
+    let _result: Conditions = repository::find_by_id(&mut client, id).await?;
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", Some(_result)))))
+}
+
+pub async fn delete_by_id(
+    Path(id): Path<String>,
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, Json<AppResponse<String>>), AppError> {
+    let mut client = _state
+        .pool_tiberius
+        .get()
+        .await
+        .map_err(|error| AppError::Other(format!("get connection failed {:?}, id: {}", error, id)))?;
+
+    repository::delete_by_id(&mut client, id).await?;
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", None))))
+}
+
+pub async fn create(
+    Extension(_state): Extension<Arc<AppState>>,
+    Json(conditions_request): Json<ConditionsRequest>,
+) -> Result<(StatusCode, Json<AppResponse<String>>), AppError> {
+    conditions_request.validate().map_err(AppError::InvalidRequest)?;
+
+    let mut client = _state
+        .pool_tiberius
+        .get()
+        .await
+        .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+
+    let new_conditions = Conditions::from_create_request(conditions_request);
+
+    let _result = repository::find_by_id(&mut client, new_conditions.id.to_owned()).await;
+    if _result.is_ok() {
+        return Err(AppError::DataExist);
     }
 
+    repository::insert_one(&mut client, &new_conditions).await?;
+
     let status_code = StatusCode::OK;
-    return Ok((
-        status_code,
-        Json(AppResponse::ok(
-            format!("Time in milliseconds: {} ms", durations),
-            None,
-        )),
-    ));
-}
\ No newline at end of file
+    Ok((status_code, Json(AppResponse::ok("success", None))))
+}
+
+pub async fn update(
+    Extension(_state): Extension<Arc<AppState>>,
+    Json(conditions_request): Json<ConditionsRequest>,
+) -> Result<(StatusCode, Json<AppResponse<String>>), AppError> {
+    conditions_request.validate().map_err(AppError::InvalidRequest)?;
+
+    let id = match conditions_request.id.clone() {
+        Some(value) => value,
+        None => return Err(AppError::invalid_request("id", "id is required to update")),
+    };
+
+    let mut client = _state
+        .pool_tiberius
+        .get()
+        .await
+        .map_err(|error| AppError::Other(format!("get connection failed {:?}, id: {}", error, id)))?;
+
+    let existing = repository::find_by_id(&mut client, id).await?;
+    let new_conditions = Conditions::from_update_request(conditions_request, existing);
+
+    repository::update_one(&mut client, &new_conditions).await?;
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", None))))
+}
+
+pub async fn patch_by_id(
+    Path(id): Path<String>,
+    Extension(_state): Extension<Arc<AppState>>,
+    Json(patch_request): Json<ConditionsPatchRequest>,
+) -> Result<(StatusCode, Json<AppResponse<String>>), AppError> {
+    patch_request.validate().map_err(AppError::InvalidRequest)?;
+
+    let mut client = _state
+        .pool_tiberius
+        .get()
+        .await
+        .map_err(|error| AppError::Other(format!("get connection failed {:?}, id: {}", error, id)))?;
+
+    repository::patch_by_id(&mut client, &id, &patch_request).await?;
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", None))))
+}
@@ -1,19 +1,21 @@
-use std::thread;
+use chrono::NaiveDateTime;
+use futures_util::StreamExt;
+use tiberius::{QueryItem, ToSql};
 
-use futures_util::{StreamExt, future::join_all};
-use tiberius::{IntoRow, QueryItem};
-
-use crate::{dto::app_error::AppError, modules::{conditions, conditions_tiberius_columns::schema::Conditions}};
+use crate::{config::environment::CONFIG, dto::app_error::AppError, modules::conditions_tiberius_columns::schema::{Conditions, ConditionsPatchRequest, IndexDefinition}};
 
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub async fn delete_all(
     client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
 ) -> Result<(), AppError> {
-    let statement = "DELETE FROM conditions";
+    let statement = format!("DELETE FROM {}", table());
     let execute_result = client
         .execute(statement, &[])
         .await
         .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
 
+    tracing::Span::current().record("rows", execute_result.rows_affected().iter().sum::<u64>());
+
     if execute_result.rows_affected()[0] == 0 {
         return Err(AppError::NotFound);
     }
@@ -21,9 +23,10 @@ pub async fn delete_all(
     return Ok(());
 }
 
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub async fn count_data(client: &mut deadpool_tiberius::deadpool::managed::Object<deadpool_tiberius::Manager>,) -> Result<i32, AppError> {
     let where_condition = "created_on BETWEEN '2023-01-01' and '2023-12-31'";
-    let statement_count = format!("select count(id) as count_data from conditions where {}", where_condition);
+    let statement_count = format!("select count(id) as count_data from {} where {}", table(), where_condition);
 
     let mut total_data = 0;
     let mut stream = client
@@ -47,11 +50,12 @@ pub async fn count_data(client: &mut deadpool_tiberius::deadpool::managed::Objec
 }
 
 
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub async fn find_all_stream_pagination(
     client: &mut deadpool_tiberius::deadpool::managed::Object<deadpool_tiberius::Manager>,
     offset: i32, limit: i32
 ) -> Result<Vec<Conditions>, AppError> {
-    let statement = format!("SELECT * FROM conditions where created_on BETWEEN '2023-01-01' and '2023-12-31' order by created_on OFFSET {} ROWS FETCH NEXT {} ROWS ONLY", offset, limit);
+    let statement = format!("SELECT * FROM {} where created_on BETWEEN '2023-01-01' and '2023-12-31' order by created_on OFFSET {} ROWS FETCH NEXT {} ROWS ONLY", table(), offset, limit);
     let mut stream = client
         .query(statement, &[])
         .await
@@ -70,16 +74,21 @@ pub async fn find_all_stream_pagination(
             conditions.push(condition);
         }
     }
+    tracing::Span::current().record("rows", conditions.len());
 
     Ok(conditions)
 }
 
+pub fn find_all_statement() -> String {
+    format!("SELECT * FROM {} where created_on BETWEEN '2023-01-01' and '2023-12-31'", table())
+}
+
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
 pub async fn find_all_stream(
     client: &mut deadpool_tiberius::deadpool::managed::Object<deadpool_tiberius::Manager>,
 ) -> Result<Vec<Conditions>, AppError> {
-    let statement = "SELECT * FROM conditions where created_on BETWEEN '2023-01-01' and '2023-12-31'";
     let mut stream = client
-        .query(statement, &[])
+        .query(find_all_statement(), &[])
         .await
         .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
 
@@ -96,10 +105,133 @@ pub async fn find_all_stream(
             conditions.push(condition);
         }
     }
+    tracing::Span::current().record("rows", conditions.len());
 
     Ok(conditions)
 }
 
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
+pub async fn count_range(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+) -> Result<i32, AppError> {
+    let statement = format!(
+        "SELECT COUNT(id) AS count_data FROM {} WHERE created_on >= @P1 AND created_on <= @P2",
+        table()
+    );
+    let row = client
+        .query(statement, &[&from, &to])
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?
+        .into_row()
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+
+    Ok(row.and_then(|row| row.get("count_data")).unwrap_or(0))
+}
+
+// @P1 and @P2 are the window, @P3 the offset and @P4 the limit
+pub fn find_page_statement() -> String {
+    format!(
+        "SELECT * FROM {} WHERE created_on >= @P1 AND created_on <= @P2 ORDER BY created_on, id OFFSET @P3 ROWS FETCH NEXT @P4 ROWS ONLY",
+        table()
+    )
+}
+
+// @P1 and @P2 are the sub-range
+pub fn find_range_statement(to_inclusive: bool) -> String {
+    let upper = if to_inclusive { "<=" } else { "<" };
+    format!("SELECT * FROM {} WHERE created_on >= @P1 AND created_on {} @P2", table(), upper)
+}
+
+// @P1 is the partition number, @P2 and @P3 the window
+pub fn find_partition_statement(partition_function: &str) -> Result<String, AppError> {
+    let partition_function = checked_identifier(partition_function)?;
+    Ok(format!(
+        "SELECT * FROM {} WHERE $PARTITION.{}(created_on) = @P1 AND created_on >= @P2 AND created_on <= @P3",
+        table(),
+        partition_function
+    ))
+}
+
+// ordered by the full primary key so pages neither overlap nor skip rows with the same created_on
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
+pub async fn find_page(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+    offset: i32,
+    limit: i32,
+) -> Result<Vec<Conditions>, AppError> {
+    let stream = client
+        .query(find_page_statement(), &[&from, &to, &offset, &limit])
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+
+    collect_rows(stream).await
+}
+
+// rows in [from, to), or [from, to] for the last sub-range of a window
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
+pub async fn find_range(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+    to_inclusive: bool,
+) -> Result<Vec<Conditions>, AppError> {
+    let stream = client
+        .query(find_range_statement(to_inclusive), &[&from, &to])
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+
+    collect_rows(stream).await
+}
+
+// first and last partition number the window falls in
+pub async fn partition_range(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    partition_function: &str,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+) -> Result<(i32, i32), AppError> {
+    let partition_function = checked_identifier(partition_function)?;
+    let statement = format!(
+        "SELECT $PARTITION.{0}(@P1) AS first_partition, $PARTITION.{0}(@P2) AS last_partition",
+        partition_function
+    );
+    let row = client
+        .query(statement, &[&from, &to])
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?
+        .into_row()
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?
+        .ok_or(AppError::NotFound)?;
+
+    let first: i32 = row.get("first_partition").unwrap_or(1);
+    let last: i32 = row.get("last_partition").unwrap_or(first);
+    Ok((first, last))
+}
+
+// the $PARTITION predicate lets sql server eliminate every other partition
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
+pub async fn find_partition(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    partition_function: &str,
+    partition: i32,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+) -> Result<Vec<Conditions>, AppError> {
+    let stream = client
+        .query(find_partition_statement(partition_function)?, &[&partition, &from, &to])
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+
+    collect_rows(stream).await
+}
+
+#[tracing::instrument(skip_all, fields(rows = data.len()))]
 pub async fn insert_batch(
     client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
     data: Vec<Conditions>,
@@ -107,8 +239,9 @@ pub async fn insert_batch(
     if data.is_empty() {
         return Ok(());
     }
+    let table = table();
     let mut bulk_insert = client
-        .bulk_insert("tiberius.dbo.conditions")
+        .bulk_insert(&table)
         .await
         .map_err(|err| AppError::Other(format!("{:?}", err)))?;
 
@@ -123,6 +256,272 @@ pub async fn insert_batch(
         .finalize()
         .await
         .map_err(|err| AppError::Other(format!("{:?}", err)))?;
-    println!("Result: {:?}", res);
+    tracing::debug!(result = ?res, "bulk insert finished");
     return Ok(());
 }
+
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
+pub async fn find_all(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    offset: i32,
+    limit: i32,
+) -> Result<Vec<Conditions>, AppError> {
+    let statement = format!("SELECT * FROM {} ORDER BY created_on, id OFFSET @P1 ROWS FETCH NEXT @P2 ROWS ONLY", table());
+    let mut stream = client
+        .query(statement, &[&offset, &limit])
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+
+    let mut conditions = Vec::new();
+
+    while let Some(item) = stream
+        .next()
+        .await
+        .transpose()
+        .map_err(|err| AppError::Other(format!("{:?}", err)))?
+    {
+        if let QueryItem::Row(row) = item {
+            conditions.push(Conditions::from_row_tiberius(&row));
+        }
+    }
+    tracing::Span::current().record("rows", conditions.len());
+
+    Ok(conditions)
+}
+
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
+pub async fn find_by_id(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    id: String,
+) -> Result<Conditions, AppError> {
+    let statement = format!("SELECT TOP 1 * FROM {} WHERE id = @P1", table());
+    let row = client
+        .query(statement, &[&id])
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?
+        .into_row()
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+
+    tracing::Span::current().record("rows", row.iter().len());
+    match row {
+        Some(row) => Ok(Conditions::from_row_tiberius(&row)),
+        None => Err(AppError::NotFound),
+    }
+}
+
+#[tracing::instrument(skip_all, fields(rows = 1))]
+pub async fn insert_one(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    condition: &Conditions,
+) -> Result<(), AppError> {
+    let params = condition.to_params_tiberius();
+
+    let columns: Vec<&str> = params.iter().map(|(column, _)| *column).collect();
+    let placeholders: Vec<String> = (1..=params.len()).map(|p| format!("@P{}", p)).collect();
+    let statement = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        table(),
+        columns.join(", "),
+        placeholders.join(", ")
+    );
+
+    let values: Vec<&dyn ToSql> = params.iter().map(|(_, value)| *value).collect();
+    client
+        .execute(statement, &values)
+        .await
+        .map_err(|error| AppError::Other(format!("execute failed: {}", error)))?;
+
+    Ok(())
+}
+
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
+pub async fn update_one(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    condition: &Conditions,
+) -> Result<(), AppError> {
+    // id and created_on form the primary key, the rest of the columns are rewritten
+    let params: Vec<(&str, &dyn ToSql)> = condition
+        .to_params_tiberius()
+        .into_iter()
+        .filter(|(column, _)| *column != "id" && *column != "created_on")
+        .collect();
+
+    update_columns(client, &condition.id, params).await
+}
+
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
+pub async fn patch_by_id(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    id: &str,
+    request: &ConditionsPatchRequest,
+) -> Result<(), AppError> {
+    let modified_on = chrono::Utc::now().naive_utc();
+
+    let mut params = request.to_params_tiberius();
+    if params.is_empty() {
+        return Err(AppError::invalid_request("body", "no column to update"));
+    }
+    params.push(("modified_on", &modified_on));
+
+    update_columns(client, id, params).await
+}
+
+async fn update_columns(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    id: &str,
+    params: Vec<(&str, &dyn ToSql)>,
+) -> Result<(), AppError> {
+    let assignments: Vec<String> = params
+        .iter()
+        .enumerate()
+        .map(|(index, (column, _))| format!("{} = @P{}", column, index + 2))
+        .collect();
+    let statement = format!(
+        "UPDATE {} SET {} WHERE id = @P1",
+        table(),
+        assignments.join(", ")
+    );
+
+    let mut values: Vec<&dyn ToSql> = vec![&id];
+    values.extend(params.iter().map(|(_, value)| *value));
+
+    let execute_result = client
+        .execute(statement, &values)
+        .await
+        .map_err(|error| AppError::Other(format!("execute failed: {}", error)))?;
+
+    let rows_affected = execute_result.total();
+    tracing::Span::current().record("rows", rows_affected);
+    if rows_affected == 0 {
+        return Err(AppError::NotFound);
+    }
+
+    Ok(())
+}
+
+#[tracing::instrument(skip_all, fields(rows = tracing::field::Empty))]
+pub async fn delete_by_id(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    id: String,
+) -> Result<(), AppError> {
+    let statement = format!("DELETE FROM {} WHERE id = @P1", table());
+    let execute_result = client
+        .execute(statement, &[&id])
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+
+    let rows_affected = execute_result.total();
+    tracing::Span::current().record("rows", rows_affected);
+    if rows_affected == 0 {
+        return Err(AppError::NotFound);
+    }
+
+    Ok(())
+}
+
+// index ddl, names and columns are checked by IndexDefinition::validate before they get here
+
+pub async fn create_index(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    index: &IndexDefinition,
+) -> Result<(), AppError> {
+    let include = if index.include.is_empty() {
+        String::new()
+    } else {
+        format!(" INCLUDE ({})", index.include.join(", "))
+    };
+    let statement = format!(
+        "CREATE NONCLUSTERED INDEX {} ON {} ({}){}",
+        index.name,
+        table(),
+        index.columns.join(", "),
+        include
+    );
+    run_statement(client, statement).await
+}
+
+// names of the indexes already on the table, sys.indexes of the target's database
+pub async fn index_names(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+) -> Result<Vec<String>, AppError> {
+    let target = CONFIG.tiberius_columns_target();
+    let statement = format!(
+        "SELECT name FROM {}.sys.indexes WHERE object_id = OBJECT_ID(@P1) AND name IS NOT NULL",
+        target.quoted_database()
+    );
+    let rows = client
+        .query(statement, &[&target.qualified()])
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?
+        .into_first_result()
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+
+    Ok(rows
+        .iter()
+        .filter_map(|row| row.get::<&str, _>("name").map(str::to_string))
+        .collect())
+}
+
+pub async fn drop_index(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    name: &str,
+) -> Result<(), AppError> {
+    let statement = format!("DROP INDEX IF EXISTS {} ON {}", name, table());
+    run_statement(client, statement).await
+}
+
+// DISABLE keeps the definition but stops maintaining the index, REBUILD builds it again
+pub async fn alter_index(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    name: &str,
+    enabled: bool,
+) -> Result<(), AppError> {
+    let action = if enabled { "REBUILD" } else { "DISABLE" };
+    let statement = format!("ALTER INDEX {} ON {} {}", name, table(), action);
+    run_statement(client, statement).await
+}
+
+async fn run_statement(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    statement: String,
+) -> Result<(), AppError> {
+    client
+        .simple_query(statement.as_str())
+        .await
+        .map_err(|error| AppError::Other(format!("{} failed: {}", statement, error)))?
+        .into_results()
+        .await
+        .map_err(|error| AppError::Other(format!("{} failed: {}", statement, error)))?;
+    Ok(())
+}
+
+fn table() -> String {
+    CONFIG.tiberius_columns_target().qualified()
+}
+
+async fn collect_rows(mut stream: tiberius::QueryStream<'_>) -> Result<Vec<Conditions>, AppError> {
+    let mut conditions = Vec::new();
+    while let Some(item) = stream
+        .next()
+        .await
+        .transpose()
+        .map_err(|err| AppError::Other(format!("{:?}", err)))?
+    {
+        if let QueryItem::Row(row) = item {
+            conditions.push(Conditions::from_row_tiberius(&row));
+        }
+    }
+    // recorded on the span of the calling repository function
+    tracing::Span::current().record("rows", conditions.len());
+    Ok(conditions)
+}
+
+// partition function names go into the statement text, so only plain identifiers are accepted
+fn checked_identifier(name: &str) -> Result<&str, AppError> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(AppError::Other(format!("invalid identifier: {}", name)));
+    }
+    Ok(name)
+}
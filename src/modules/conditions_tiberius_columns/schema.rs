@@ -1,1259 +1,203 @@
-use crate::util::{self, serializer::datetime_serializer};
-use chrono::NaiveDateTime;
+use crate::{dto::app_error::AppError, modules::query_plan::schema::QueryPlan, util::serializer::{datetime_serializer, double_option}};
+use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
+use tiberius::{IntoSql, ToSql, TokenRow};
 use uuid::Uuid;
 use validator::Validate;
-use chrono::Utc;
-use chrono::DateTime;
-use chrono::FixedOffset;
-use tiberius::{IntoRow, IntoSql, ToSql, TokenRow, numeric::Decimal};
 
-#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
-pub struct Conditions {
-	pub id: String,
-	#[serde(with = "datetime_serializer")]
-	pub created_on: chrono::NaiveDateTime,
-	#[serde(with = "datetime_serializer")]
-	pub modified_on: chrono::NaiveDateTime,
-	pub location_1: String,
-	pub location_2: String,
-	pub location_3: String,
-	pub location_4: String,
-	pub location_5: String,
-	pub location_6: String,
-	pub location_7: String,
-	pub location_8: String,
-	pub location_9: String,
-	pub location_10: String,
-	pub location_11: String,
-	pub location_12: String,
-	pub location_13: String,
-	pub location_14: String,
-	pub location_15: String,
-	pub location_16: String,
-	pub location_17: String,
-	pub location_18: String,
-	pub location_19: String,
-	pub location_20: String,
-	pub location_21: String,
-	pub location_22: String,
-	pub location_23: String,
-	pub location_24: String,
-	pub location_25: String,
-	pub location_26: String,
-	pub location_27: String,
-	pub location_28: String,
-	pub location_29: String,
-	pub location_30: String,
-	pub location_31: String,
-	pub location_32: String,
-	pub location_33: String,
-	pub location_34: String,
-	pub location_35: String,
-	pub location_36: String,
-	pub location_37: String,
-	pub location_38: String,
-	pub location_39: String,
-	pub location_40: String,
-	pub location_41: String,
-	pub location_42: String,
-	pub location_43: String,
-	pub location_44: String,
-	pub location_45: String,
-	pub location_46: String,
-	pub location_47: String,
-	pub location_48: String,
-	pub location_49: String,
-	pub location_50: String,
-	pub temperature_1: Option<f64>,
-	pub temperature_2: Option<f64>,
-	pub temperature_3: Option<f64>,
-	pub temperature_4: Option<f64>,
-	pub temperature_5: Option<f64>,
-	pub temperature_6: Option<f64>,
-	pub temperature_7: Option<f64>,
-	pub temperature_8: Option<f64>,
-	pub temperature_9: Option<f64>,
-	pub temperature_10: Option<f64>,
-	pub temperature_11: Option<f64>,
-	pub temperature_12: Option<f64>,
-	pub temperature_13: Option<f64>,
-	pub temperature_14: Option<f64>,
-	pub temperature_15: Option<f64>,
-	pub temperature_16: Option<f64>,
-	pub temperature_17: Option<f64>,
-	pub temperature_18: Option<f64>,
-	pub temperature_19: Option<f64>,
-	pub temperature_20: Option<f64>,
-	pub temperature_21: Option<f64>,
-	pub temperature_22: Option<f64>,
-	pub temperature_23: Option<f64>,
-	pub temperature_24: Option<f64>,
-	pub temperature_25: Option<f64>,
-	pub temperature_26: Option<f64>,
-	pub temperature_27: Option<f64>,
-	pub temperature_28: Option<f64>,
-	pub temperature_29: Option<f64>,
-	pub temperature_30: Option<f64>,
-	pub temperature_31: Option<f64>,
-	pub temperature_32: Option<f64>,
-	pub temperature_33: Option<f64>,
-	pub temperature_34: Option<f64>,
-	pub temperature_35: Option<f64>,
-	pub temperature_36: Option<f64>,
-	pub temperature_37: Option<f64>,
-	pub temperature_38: Option<f64>,
-	pub temperature_39: Option<f64>,
-	pub temperature_40: Option<f64>,
-	pub temperature_41: Option<f64>,
-	pub temperature_42: Option<f64>,
-	pub temperature_43: Option<f64>,
-	pub temperature_44: Option<f64>,
-	pub temperature_45: Option<f64>,
-	pub temperature_46: Option<f64>,
-	pub temperature_47: Option<f64>,
-	pub temperature_48: Option<f64>,
-	pub temperature_49: Option<f64>,
-	pub temperature_50: Option<f64>,
-	pub humidity_1: Option<f64>,
-	pub humidity_2: Option<f64>,
-	pub humidity_3: Option<f64>,
-	pub humidity_4: Option<f64>,
-	pub humidity_5: Option<f64>,
-	pub humidity_6: Option<f64>,
-	pub humidity_7: Option<f64>,
-	pub humidity_8: Option<f64>,
-	pub humidity_9: Option<f64>,
-	pub humidity_10: Option<f64>,
-	pub humidity_11: Option<f64>,
-	pub humidity_12: Option<f64>,
-	pub humidity_13: Option<f64>,
-	pub humidity_14: Option<f64>,
-	pub humidity_15: Option<f64>,
-	pub humidity_16: Option<f64>,
-	pub humidity_17: Option<f64>,
-	pub humidity_18: Option<f64>,
-	pub humidity_19: Option<f64>,
-	pub humidity_20: Option<f64>,
-	pub humidity_21: Option<f64>,
-	pub humidity_22: Option<f64>,
-	pub humidity_23: Option<f64>,
-	pub humidity_24: Option<f64>,
-	pub humidity_25: Option<f64>,
-	pub humidity_26: Option<f64>,
-	pub humidity_27: Option<f64>,
-	pub humidity_28: Option<f64>,
-	pub humidity_29: Option<f64>,
-	pub humidity_30: Option<f64>,
-	pub humidity_31: Option<f64>,
-	pub humidity_32: Option<f64>,
-	pub humidity_33: Option<f64>,
-	pub humidity_34: Option<f64>,
-	pub humidity_35: Option<f64>,
-	pub humidity_36: Option<f64>,
-	pub humidity_37: Option<f64>,
-	pub humidity_38: Option<f64>,
-	pub humidity_39: Option<f64>,
-	pub humidity_40: Option<f64>,
-	pub humidity_41: Option<f64>,
-	pub humidity_42: Option<f64>,
-	pub humidity_43: Option<f64>,
-	pub humidity_44: Option<f64>,
-	pub humidity_45: Option<f64>,
-	pub humidity_46: Option<f64>,
-	pub humidity_47: Option<f64>,
-	pub humidity_48: Option<f64>,
-	pub humidity_49: Option<f64>,
-	pub humidity_50: Option<f64>,
-	pub sensor_numeric_1: Option<Decimal>,
-	pub sensor_numeric_2: Option<Decimal>,
-	pub sensor_numeric_3: Option<Decimal>,
-	pub sensor_numeric_4: Option<Decimal>,
-	pub sensor_numeric_5: Option<Decimal>,
-	pub sensor_numeric_6: Option<Decimal>,
-	pub sensor_numeric_7: Option<Decimal>,
-	pub sensor_numeric_8: Option<Decimal>,
-	pub sensor_numeric_9: Option<Decimal>,
-	pub sensor_numeric_10: Option<Decimal>,
-	pub sensor_numeric_11: Option<Decimal>,
-	pub sensor_numeric_12: Option<Decimal>,
-	pub sensor_numeric_13: Option<Decimal>,
-	pub sensor_numeric_14: Option<Decimal>,
-	pub sensor_numeric_15: Option<Decimal>,
-	pub sensor_numeric_16: Option<Decimal>,
-	pub sensor_numeric_17: Option<Decimal>,
-	pub sensor_numeric_18: Option<Decimal>,
-	pub sensor_numeric_19: Option<Decimal>,
-	pub sensor_numeric_20: Option<Decimal>,
-	pub sensor_numeric_21: Option<Decimal>,
-	pub sensor_numeric_22: Option<Decimal>,
-	pub sensor_numeric_23: Option<Decimal>,
-	pub sensor_numeric_24: Option<Decimal>,
-	pub sensor_numeric_25: Option<Decimal>,
-	pub sensor_decimal_1: Option<Decimal>,
-	pub sensor_decimal_2: Option<Decimal>,
-	pub sensor_decimal_3: Option<Decimal>,
-	pub sensor_decimal_4: Option<Decimal>,
-	pub sensor_decimal_5: Option<Decimal>,
-	pub sensor_decimal_6: Option<Decimal>,
-	pub sensor_decimal_7: Option<Decimal>,
-	pub sensor_decimal_8: Option<Decimal>,
-	pub sensor_decimal_9: Option<Decimal>,
-	pub sensor_decimal_10: Option<Decimal>,
-	pub sensor_decimal_11: Option<Decimal>,
-	pub sensor_decimal_12: Option<Decimal>,
-	pub sensor_decimal_13: Option<Decimal>,
-	pub sensor_decimal_14: Option<Decimal>,
-	pub sensor_decimal_15: Option<Decimal>,
-	pub sensor_decimal_16: Option<Decimal>,
-	pub sensor_decimal_17: Option<Decimal>,
-	pub sensor_decimal_18: Option<Decimal>,
-	pub sensor_decimal_19: Option<Decimal>,
-	pub sensor_decimal_20: Option<Decimal>,
-	pub sensor_decimal_21: Option<Decimal>,
-	pub sensor_decimal_22: Option<Decimal>,
-	pub sensor_decimal_23: Option<Decimal>,
-	pub sensor_decimal_24: Option<Decimal>,
-	pub sensor_decimal_25: Option<Decimal>,
+// Conditions, ConditionsRequest and ConditionsPatchRequest are generated by build.rs
+// from table.json, change the column counts there to benchmark a narrower or wider table
+include!(concat!(env!("OUT_DIR"), "/conditions_tiberius_columns_schema.rs"));
+
+#[derive(Debug, Deserialize)]
+pub struct PageRequest {
+	pub page: Option<i32>,
+	pub size: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GenerateRequest {
+	pub batch_size: Option<usize>,
+	// parallel writers, TIBERIUS_WRITER_WORKERS when not set
+	pub workers: Option<usize>,
+}
+
+// how a parallel read splits the rows between connections
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadStrategy {
+	// OFFSET ... FETCH NEXT pages, every page rescans the rows before it
+	Page,
+	// equal created_on sub-ranges of the window
+	#[default]
+	Range,
+	// one query per partition of the window, by $PARTITION number
+	Partition,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ParallelReadRequest {
+	pub strategy: Option<ReadStrategy>,
+	// connections reading at the same time, TIBERIUS_WRITER_WORKERS when not set
+	pub dop: Option<usize>,
+	// inclusive window on created_on, 2023-01-01 to 2023-12-31 when not set
+	pub from: Option<NaiveDate>,
+	pub to: Option<NaiveDate>,
+	// rows per page for the page strategy
+	pub page_size: Option<i32>,
+	// partition function of the table for the partition strategy, PF_conditions_yearly when not set
+	pub partition_function: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Validate)]
-pub struct ConditionsRequest{
+#[derive(Debug, Serialize)]
+pub struct ParallelReadReport {
+	pub strategy: ReadStrategy,
+	pub dop: usize,
+	// pages, sub-ranges or partitions read per run
+	pub units: usize,
+	pub rows: usize,
+	pub durations_ms: Vec<u128>,
+	// plan of the first unit, with the partitions sql server eliminated
 	#[serde(skip_serializing_if = "Option::is_none")]
-	pub id: Option<String>,
-	pub location_1: String,
-	pub location_2: String,
-	pub location_3: String,
-	pub location_4: String,
-	pub location_5: String,
-	pub location_6: String,
-	pub location_7: String,
-	pub location_8: String,
-	pub location_9: String,
-	pub location_10: String,
-	pub location_11: String,
-	pub location_12: String,
-	pub location_13: String,
-	pub location_14: String,
-	pub location_15: String,
-	pub location_16: String,
-	pub location_17: String,
-	pub location_18: String,
-	pub location_19: String,
-	pub location_20: String,
-	pub location_21: String,
-	pub location_22: String,
-	pub location_23: String,
-	pub location_24: String,
-	pub location_25: String,
-	pub location_26: String,
-	pub location_27: String,
-	pub location_28: String,
-	pub location_29: String,
-	pub location_30: String,
-	pub location_31: String,
-	pub location_32: String,
-	pub location_33: String,
-	pub location_34: String,
-	pub location_35: String,
-	pub location_36: String,
-	pub location_37: String,
-	pub location_38: String,
-	pub location_39: String,
-	pub location_40: String,
-	pub location_41: String,
-	pub location_42: String,
-	pub location_43: String,
-	pub location_44: String,
-	pub location_45: String,
-	pub location_46: String,
-	pub location_47: String,
-	pub location_48: String,
-	pub location_49: String,
-	pub location_50: String,
-	pub temperature_1: Option<f64>,
-	pub temperature_2: Option<f64>,
-	pub temperature_3: Option<f64>,
-	pub temperature_4: Option<f64>,
-	pub temperature_5: Option<f64>,
-	pub temperature_6: Option<f64>,
-	pub temperature_7: Option<f64>,
-	pub temperature_8: Option<f64>,
-	pub temperature_9: Option<f64>,
-	pub temperature_10: Option<f64>,
-	pub temperature_11: Option<f64>,
-	pub temperature_12: Option<f64>,
-	pub temperature_13: Option<f64>,
-	pub temperature_14: Option<f64>,
-	pub temperature_15: Option<f64>,
-	pub temperature_16: Option<f64>,
-	pub temperature_17: Option<f64>,
-	pub temperature_18: Option<f64>,
-	pub temperature_19: Option<f64>,
-	pub temperature_20: Option<f64>,
-	pub temperature_21: Option<f64>,
-	pub temperature_22: Option<f64>,
-	pub temperature_23: Option<f64>,
-	pub temperature_24: Option<f64>,
-	pub temperature_25: Option<f64>,
-	pub temperature_26: Option<f64>,
-	pub temperature_27: Option<f64>,
-	pub temperature_28: Option<f64>,
-	pub temperature_29: Option<f64>,
-	pub temperature_30: Option<f64>,
-	pub temperature_31: Option<f64>,
-	pub temperature_32: Option<f64>,
-	pub temperature_33: Option<f64>,
-	pub temperature_34: Option<f64>,
-	pub temperature_35: Option<f64>,
-	pub temperature_36: Option<f64>,
-	pub temperature_37: Option<f64>,
-	pub temperature_38: Option<f64>,
-	pub temperature_39: Option<f64>,
-	pub temperature_40: Option<f64>,
-	pub temperature_41: Option<f64>,
-	pub temperature_42: Option<f64>,
-	pub temperature_43: Option<f64>,
-	pub temperature_44: Option<f64>,
-	pub temperature_45: Option<f64>,
-	pub temperature_46: Option<f64>,
-	pub temperature_47: Option<f64>,
-	pub temperature_48: Option<f64>,
-	pub temperature_49: Option<f64>,
-	pub temperature_50: Option<f64>,
-	pub humidity_1: Option<f64>,
-	pub humidity_2: Option<f64>,
-	pub humidity_3: Option<f64>,
-	pub humidity_4: Option<f64>,
-	pub humidity_5: Option<f64>,
-	pub humidity_6: Option<f64>,
-	pub humidity_7: Option<f64>,
-	pub humidity_8: Option<f64>,
-	pub humidity_9: Option<f64>,
-	pub humidity_10: Option<f64>,
-	pub humidity_11: Option<f64>,
-	pub humidity_12: Option<f64>,
-	pub humidity_13: Option<f64>,
-	pub humidity_14: Option<f64>,
-	pub humidity_15: Option<f64>,
-	pub humidity_16: Option<f64>,
-	pub humidity_17: Option<f64>,
-	pub humidity_18: Option<f64>,
-	pub humidity_19: Option<f64>,
-	pub humidity_20: Option<f64>,
-	pub humidity_21: Option<f64>,
-	pub humidity_22: Option<f64>,
-	pub humidity_23: Option<f64>,
-	pub humidity_24: Option<f64>,
-	pub humidity_25: Option<f64>,
-	pub humidity_26: Option<f64>,
-	pub humidity_27: Option<f64>,
-	pub humidity_28: Option<f64>,
-	pub humidity_29: Option<f64>,
-	pub humidity_30: Option<f64>,
-	pub humidity_31: Option<f64>,
-	pub humidity_32: Option<f64>,
-	pub humidity_33: Option<f64>,
-	pub humidity_34: Option<f64>,
-	pub humidity_35: Option<f64>,
-	pub humidity_36: Option<f64>,
-	pub humidity_37: Option<f64>,
-	pub humidity_38: Option<f64>,
-	pub humidity_39: Option<f64>,
-	pub humidity_40: Option<f64>,
-	pub humidity_41: Option<f64>,
-	pub humidity_42: Option<f64>,
-	pub humidity_43: Option<f64>,
-	pub humidity_44: Option<f64>,
-	pub humidity_45: Option<f64>,
-	pub humidity_46: Option<f64>,
-	pub humidity_47: Option<f64>,
-	pub humidity_48: Option<f64>,
-	pub humidity_49: Option<f64>,
-	pub humidity_50: Option<f64>,
-	pub sensor_numeric_1: Option<Decimal>,
-	pub sensor_numeric_2: Option<Decimal>,
-	pub sensor_numeric_3: Option<Decimal>,
-	pub sensor_numeric_4: Option<Decimal>,
-	pub sensor_numeric_5: Option<Decimal>,
-	pub sensor_numeric_6: Option<Decimal>,
-	pub sensor_numeric_7: Option<Decimal>,
-	pub sensor_numeric_8: Option<Decimal>,
-	pub sensor_numeric_9: Option<Decimal>,
-	pub sensor_numeric_10: Option<Decimal>,
-	pub sensor_numeric_11: Option<Decimal>,
-	pub sensor_numeric_12: Option<Decimal>,
-	pub sensor_numeric_13: Option<Decimal>,
-	pub sensor_numeric_14: Option<Decimal>,
-	pub sensor_numeric_15: Option<Decimal>,
-	pub sensor_numeric_16: Option<Decimal>,
-	pub sensor_numeric_17: Option<Decimal>,
-	pub sensor_numeric_18: Option<Decimal>,
-	pub sensor_numeric_19: Option<Decimal>,
-	pub sensor_numeric_20: Option<Decimal>,
-	pub sensor_numeric_21: Option<Decimal>,
-	pub sensor_numeric_22: Option<Decimal>,
-	pub sensor_numeric_23: Option<Decimal>,
-	pub sensor_numeric_24: Option<Decimal>,
-	pub sensor_numeric_25: Option<Decimal>,
-	pub sensor_decimal_1: Option<Decimal>,
-	pub sensor_decimal_2: Option<Decimal>,
-	pub sensor_decimal_3: Option<Decimal>,
-	pub sensor_decimal_4: Option<Decimal>,
-	pub sensor_decimal_5: Option<Decimal>,
-	pub sensor_decimal_6: Option<Decimal>,
-	pub sensor_decimal_7: Option<Decimal>,
-	pub sensor_decimal_8: Option<Decimal>,
-	pub sensor_decimal_9: Option<Decimal>,
-	pub sensor_decimal_10: Option<Decimal>,
-	pub sensor_decimal_11: Option<Decimal>,
-	pub sensor_decimal_12: Option<Decimal>,
-	pub sensor_decimal_13: Option<Decimal>,
-	pub sensor_decimal_14: Option<Decimal>,
-	pub sensor_decimal_15: Option<Decimal>,
-	pub sensor_decimal_16: Option<Decimal>,
-	pub sensor_decimal_17: Option<Decimal>,
-	pub sensor_decimal_18: Option<Decimal>,
-	pub sensor_decimal_19: Option<Decimal>,
-	pub sensor_decimal_20: Option<Decimal>,
-	pub sensor_decimal_21: Option<Decimal>,
-	pub sensor_decimal_22: Option<Decimal>,
-	pub sensor_decimal_23: Option<Decimal>,
-	pub sensor_decimal_24: Option<Decimal>,
-	pub sensor_decimal_25: Option<Decimal>,
+	pub plan: Option<QueryPlan>,
 }
 
-impl Conditions {
-	
-	pub fn from_create_request(request: ConditionsRequest) -> Self {
-		Conditions {
-			id: request.id.unwrap_or(Uuid::new_v4().to_string()),
-			created_on: chrono::Utc::now().naive_utc(),
-			modified_on: chrono::Utc::now().naive_utc().into(),
-			location_1: request.location_1,
-			location_2: request.location_2,
-			location_3: request.location_3,
-			location_4: request.location_4,
-			location_5: request.location_5,
-			location_6: request.location_6,
-			location_7: request.location_7,
-			location_8: request.location_8,
-			location_9: request.location_9,
-			location_10: request.location_10,
-			location_11: request.location_11,
-			location_12: request.location_12,
-			location_13: request.location_13,
-			location_14: request.location_14,
-			location_15: request.location_15,
-			location_16: request.location_16,
-			location_17: request.location_17,
-			location_18: request.location_18,
-			location_19: request.location_19,
-			location_20: request.location_20,
-			location_21: request.location_21,
-			location_22: request.location_22,
-			location_23: request.location_23,
-			location_24: request.location_24,
-			location_25: request.location_25,
-			location_26: request.location_26,
-			location_27: request.location_27,
-			location_28: request.location_28,
-			location_29: request.location_29,
-			location_30: request.location_30,
-			location_31: request.location_31,
-			location_32: request.location_32,
-			location_33: request.location_33,
-			location_34: request.location_34,
-			location_35: request.location_35,
-			location_36: request.location_36,
-			location_37: request.location_37,
-			location_38: request.location_38,
-			location_39: request.location_39,
-			location_40: request.location_40,
-			location_41: request.location_41,
-			location_42: request.location_42,
-			location_43: request.location_43,
-			location_44: request.location_44,
-			location_45: request.location_45,
-			location_46: request.location_46,
-			location_47: request.location_47,
-			location_48: request.location_48,
-			location_49: request.location_49,
-			location_50: request.location_50,
-			temperature_1: request.temperature_1,
-			temperature_2: request.temperature_2,
-			temperature_3: request.temperature_3,
-			temperature_4: request.temperature_4,
-			temperature_5: request.temperature_5,
-			temperature_6: request.temperature_6,
-			temperature_7: request.temperature_7,
-			temperature_8: request.temperature_8,
-			temperature_9: request.temperature_9,
-			temperature_10: request.temperature_10,
-			temperature_11: request.temperature_11,
-			temperature_12: request.temperature_12,
-			temperature_13: request.temperature_13,
-			temperature_14: request.temperature_14,
-			temperature_15: request.temperature_15,
-			temperature_16: request.temperature_16,
-			temperature_17: request.temperature_17,
-			temperature_18: request.temperature_18,
-			temperature_19: request.temperature_19,
-			temperature_20: request.temperature_20,
-			temperature_21: request.temperature_21,
-			temperature_22: request.temperature_22,
-			temperature_23: request.temperature_23,
-			temperature_24: request.temperature_24,
-			temperature_25: request.temperature_25,
-			temperature_26: request.temperature_26,
-			temperature_27: request.temperature_27,
-			temperature_28: request.temperature_28,
-			temperature_29: request.temperature_29,
-			temperature_30: request.temperature_30,
-			temperature_31: request.temperature_31,
-			temperature_32: request.temperature_32,
-			temperature_33: request.temperature_33,
-			temperature_34: request.temperature_34,
-			temperature_35: request.temperature_35,
-			temperature_36: request.temperature_36,
-			temperature_37: request.temperature_37,
-			temperature_38: request.temperature_38,
-			temperature_39: request.temperature_39,
-			temperature_40: request.temperature_40,
-			temperature_41: request.temperature_41,
-			temperature_42: request.temperature_42,
-			temperature_43: request.temperature_43,
-			temperature_44: request.temperature_44,
-			temperature_45: request.temperature_45,
-			temperature_46: request.temperature_46,
-			temperature_47: request.temperature_47,
-			temperature_48: request.temperature_48,
-			temperature_49: request.temperature_49,
-			temperature_50: request.temperature_50,
-			humidity_1: request.humidity_1,
-			humidity_2: request.humidity_2,
-			humidity_3: request.humidity_3,
-			humidity_4: request.humidity_4,
-			humidity_5: request.humidity_5,
-			humidity_6: request.humidity_6,
-			humidity_7: request.humidity_7,
-			humidity_8: request.humidity_8,
-			humidity_9: request.humidity_9,
-			humidity_10: request.humidity_10,
-			humidity_11: request.humidity_11,
-			humidity_12: request.humidity_12,
-			humidity_13: request.humidity_13,
-			humidity_14: request.humidity_14,
-			humidity_15: request.humidity_15,
-			humidity_16: request.humidity_16,
-			humidity_17: request.humidity_17,
-			humidity_18: request.humidity_18,
-			humidity_19: request.humidity_19,
-			humidity_20: request.humidity_20,
-			humidity_21: request.humidity_21,
-			humidity_22: request.humidity_22,
-			humidity_23: request.humidity_23,
-			humidity_24: request.humidity_24,
-			humidity_25: request.humidity_25,
-			humidity_26: request.humidity_26,
-			humidity_27: request.humidity_27,
-			humidity_28: request.humidity_28,
-			humidity_29: request.humidity_29,
-			humidity_30: request.humidity_30,
-			humidity_31: request.humidity_31,
-			humidity_32: request.humidity_32,
-			humidity_33: request.humidity_33,
-			humidity_34: request.humidity_34,
-			humidity_35: request.humidity_35,
-			humidity_36: request.humidity_36,
-			humidity_37: request.humidity_37,
-			humidity_38: request.humidity_38,
-			humidity_39: request.humidity_39,
-			humidity_40: request.humidity_40,
-			humidity_41: request.humidity_41,
-			humidity_42: request.humidity_42,
-			humidity_43: request.humidity_43,
-			humidity_44: request.humidity_44,
-			humidity_45: request.humidity_45,
-			humidity_46: request.humidity_46,
-			humidity_47: request.humidity_47,
-			humidity_48: request.humidity_48,
-			humidity_49: request.humidity_49,
-			humidity_50: request.humidity_50,
-			sensor_numeric_1: request.sensor_numeric_1,
-			sensor_numeric_2: request.sensor_numeric_2,
-			sensor_numeric_3: request.sensor_numeric_3,
-			sensor_numeric_4: request.sensor_numeric_4,
-			sensor_numeric_5: request.sensor_numeric_5,
-			sensor_numeric_6: request.sensor_numeric_6,
-			sensor_numeric_7: request.sensor_numeric_7,
-			sensor_numeric_8: request.sensor_numeric_8,
-			sensor_numeric_9: request.sensor_numeric_9,
-			sensor_numeric_10: request.sensor_numeric_10,
-			sensor_numeric_11: request.sensor_numeric_11,
-			sensor_numeric_12: request.sensor_numeric_12,
-			sensor_numeric_13: request.sensor_numeric_13,
-			sensor_numeric_14: request.sensor_numeric_14,
-			sensor_numeric_15: request.sensor_numeric_15,
-			sensor_numeric_16: request.sensor_numeric_16,
-			sensor_numeric_17: request.sensor_numeric_17,
-			sensor_numeric_18: request.sensor_numeric_18,
-			sensor_numeric_19: request.sensor_numeric_19,
-			sensor_numeric_20: request.sensor_numeric_20,
-			sensor_numeric_21: request.sensor_numeric_21,
-			sensor_numeric_22: request.sensor_numeric_22,
-			sensor_numeric_23: request.sensor_numeric_23,
-			sensor_numeric_24: request.sensor_numeric_24,
-			sensor_numeric_25: request.sensor_numeric_25,
-			sensor_decimal_1: request.sensor_decimal_1,
-			sensor_decimal_2: request.sensor_decimal_2,
-			sensor_decimal_3: request.sensor_decimal_3,
-			sensor_decimal_4: request.sensor_decimal_4,
-			sensor_decimal_5: request.sensor_decimal_5,
-			sensor_decimal_6: request.sensor_decimal_6,
-			sensor_decimal_7: request.sensor_decimal_7,
-			sensor_decimal_8: request.sensor_decimal_8,
-			sensor_decimal_9: request.sensor_decimal_9,
-			sensor_decimal_10: request.sensor_decimal_10,
-			sensor_decimal_11: request.sensor_decimal_11,
-			sensor_decimal_12: request.sensor_decimal_12,
-			sensor_decimal_13: request.sensor_decimal_13,
-			sensor_decimal_14: request.sensor_decimal_14,
-			sensor_decimal_15: request.sensor_decimal_15,
-			sensor_decimal_16: request.sensor_decimal_16,
-			sensor_decimal_17: request.sensor_decimal_17,
-			sensor_decimal_18: request.sensor_decimal_18,
-			sensor_decimal_19: request.sensor_decimal_19,
-			sensor_decimal_20: request.sensor_decimal_20,
-			sensor_decimal_21: request.sensor_decimal_21,
-			sensor_decimal_22: request.sensor_decimal_22,
-			sensor_decimal_23: request.sensor_decimal_23,
-			sensor_decimal_24: request.sensor_decimal_24,
-			sensor_decimal_25: request.sensor_decimal_25,
+// how an index set is switched on between the runs of an index experiment
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexSwitch {
+	// create the set's indexes before its runs and drop them afterwards
+	#[default]
+	CreateDrop,
+	// create every index once, then ALTER INDEX ... DISABLE the ones outside the set and REBUILD the
+	// ones in it, disabled indexes are not maintained by inserts
+	DisableRebuild,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct IndexDefinition {
+	pub name: String,
+	pub columns: Vec<String>,
+	#[serde(default)]
+	pub include: Vec<String>,
+}
+
+impl IndexDefinition {
+	// sql server error 1904, the 203 column table cannot have an index over every column
+	pub const MAX_KEY_COLUMNS: usize = 32;
+
+	pub fn validate(&self) -> Result<(), AppError> {
+		if self.name.is_empty() || !self.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+			return Err(AppError::invalid_request("name", format!("invalid index name {}", self.name)));
 		}
-	}
-	pub fn from_row_tiberius(row: &tiberius::Row) -> Self {
-		Conditions {
-			id: row.get::<&str, _>("id").unwrap_or_default().to_owned(),
-			created_on: row.get("created_on").unwrap(),
-			modified_on: row.get("modified_on").unwrap(),
-			location_1: row.get::<&str, _>("location_1").unwrap_or_default().to_owned(),
-			location_2: row.get::<&str, _>("location_2").unwrap_or_default().to_owned(),
-			location_3: row.get::<&str, _>("location_3").unwrap_or_default().to_owned(),
-			location_4: row.get::<&str, _>("location_4").unwrap_or_default().to_owned(),
-			location_5: row.get::<&str, _>("location_5").unwrap_or_default().to_owned(),
-			location_6: row.get::<&str, _>("location_6").unwrap_or_default().to_owned(),
-			location_7: row.get::<&str, _>("location_7").unwrap_or_default().to_owned(),
-			location_8: row.get::<&str, _>("location_8").unwrap_or_default().to_owned(),
-			location_9: row.get::<&str, _>("location_9").unwrap_or_default().to_owned(),
-			location_10: row.get::<&str, _>("location_10").unwrap_or_default().to_owned(),
-			location_11: row.get::<&str, _>("location_11").unwrap_or_default().to_owned(),
-			location_12: row.get::<&str, _>("location_12").unwrap_or_default().to_owned(),
-			location_13: row.get::<&str, _>("location_13").unwrap_or_default().to_owned(),
-			location_14: row.get::<&str, _>("location_14").unwrap_or_default().to_owned(),
-			location_15: row.get::<&str, _>("location_15").unwrap_or_default().to_owned(),
-			location_16: row.get::<&str, _>("location_16").unwrap_or_default().to_owned(),
-			location_17: row.get::<&str, _>("location_17").unwrap_or_default().to_owned(),
-			location_18: row.get::<&str, _>("location_18").unwrap_or_default().to_owned(),
-			location_19: row.get::<&str, _>("location_19").unwrap_or_default().to_owned(),
-			location_20: row.get::<&str, _>("location_20").unwrap_or_default().to_owned(),
-			location_21: row.get::<&str, _>("location_21").unwrap_or_default().to_owned(),
-			location_22: row.get::<&str, _>("location_22").unwrap_or_default().to_owned(),
-			location_23: row.get::<&str, _>("location_23").unwrap_or_default().to_owned(),
-			location_24: row.get::<&str, _>("location_24").unwrap_or_default().to_owned(),
-			location_25: row.get::<&str, _>("location_25").unwrap_or_default().to_owned(),
-			location_26: row.get::<&str, _>("location_26").unwrap_or_default().to_owned(),
-			location_27: row.get::<&str, _>("location_27").unwrap_or_default().to_owned(),
-			location_28: row.get::<&str, _>("location_28").unwrap_or_default().to_owned(),
-			location_29: row.get::<&str, _>("location_29").unwrap_or_default().to_owned(),
-			location_30: row.get::<&str, _>("location_30").unwrap_or_default().to_owned(),
-			location_31: row.get::<&str, _>("location_31").unwrap_or_default().to_owned(),
-			location_32: row.get::<&str, _>("location_32").unwrap_or_default().to_owned(),
-			location_33: row.get::<&str, _>("location_33").unwrap_or_default().to_owned(),
-			location_34: row.get::<&str, _>("location_34").unwrap_or_default().to_owned(),
-			location_35: row.get::<&str, _>("location_35").unwrap_or_default().to_owned(),
-			location_36: row.get::<&str, _>("location_36").unwrap_or_default().to_owned(),
-			location_37: row.get::<&str, _>("location_37").unwrap_or_default().to_owned(),
-			location_38: row.get::<&str, _>("location_38").unwrap_or_default().to_owned(),
-			location_39: row.get::<&str, _>("location_39").unwrap_or_default().to_owned(),
-			location_40: row.get::<&str, _>("location_40").unwrap_or_default().to_owned(),
-			location_41: row.get::<&str, _>("location_41").unwrap_or_default().to_owned(),
-			location_42: row.get::<&str, _>("location_42").unwrap_or_default().to_owned(),
-			location_43: row.get::<&str, _>("location_43").unwrap_or_default().to_owned(),
-			location_44: row.get::<&str, _>("location_44").unwrap_or_default().to_owned(),
-			location_45: row.get::<&str, _>("location_45").unwrap_or_default().to_owned(),
-			location_46: row.get::<&str, _>("location_46").unwrap_or_default().to_owned(),
-			location_47: row.get::<&str, _>("location_47").unwrap_or_default().to_owned(),
-			location_48: row.get::<&str, _>("location_48").unwrap_or_default().to_owned(),
-			location_49: row.get::<&str, _>("location_49").unwrap_or_default().to_owned(),
-			location_50: row.get::<&str, _>("location_50").unwrap_or_default().to_owned(),
-			temperature_1: Some(row.get::<f64, _>("temperature_1").unwrap_or_default().to_owned()),
-			temperature_2: Some(row.get::<f64, _>("temperature_2").unwrap_or_default().to_owned()),
-			temperature_3: Some(row.get::<f64, _>("temperature_3").unwrap_or_default().to_owned()),
-			temperature_4: Some(row.get::<f64, _>("temperature_4").unwrap_or_default().to_owned()),
-			temperature_5: Some(row.get::<f64, _>("temperature_5").unwrap_or_default().to_owned()),
-			temperature_6: Some(row.get::<f64, _>("temperature_6").unwrap_or_default().to_owned()),
-			temperature_7: Some(row.get::<f64, _>("temperature_7").unwrap_or_default().to_owned()),
-			temperature_8: Some(row.get::<f64, _>("temperature_8").unwrap_or_default().to_owned()),
-			temperature_9: Some(row.get::<f64, _>("temperature_9").unwrap_or_default().to_owned()),
-			temperature_10: Some(row.get::<f64, _>("temperature_10").unwrap_or_default().to_owned()),
-			temperature_11: Some(row.get::<f64, _>("temperature_11").unwrap_or_default().to_owned()),
-			temperature_12: Some(row.get::<f64, _>("temperature_12").unwrap_or_default().to_owned()),
-			temperature_13: Some(row.get::<f64, _>("temperature_13").unwrap_or_default().to_owned()),
-			temperature_14: Some(row.get::<f64, _>("temperature_14").unwrap_or_default().to_owned()),
-			temperature_15: Some(row.get::<f64, _>("temperature_15").unwrap_or_default().to_owned()),
-			temperature_16: Some(row.get::<f64, _>("temperature_16").unwrap_or_default().to_owned()),
-			temperature_17: Some(row.get::<f64, _>("temperature_17").unwrap_or_default().to_owned()),
-			temperature_18: Some(row.get::<f64, _>("temperature_18").unwrap_or_default().to_owned()),
-			temperature_19: Some(row.get::<f64, _>("temperature_19").unwrap_or_default().to_owned()),
-			temperature_20: Some(row.get::<f64, _>("temperature_20").unwrap_or_default().to_owned()),
-			temperature_21: Some(row.get::<f64, _>("temperature_21").unwrap_or_default().to_owned()),
-			temperature_22: Some(row.get::<f64, _>("temperature_22").unwrap_or_default().to_owned()),
-			temperature_23: Some(row.get::<f64, _>("temperature_23").unwrap_or_default().to_owned()),
-			temperature_24: Some(row.get::<f64, _>("temperature_24").unwrap_or_default().to_owned()),
-			temperature_25: Some(row.get::<f64, _>("temperature_25").unwrap_or_default().to_owned()),
-			temperature_26: Some(row.get::<f64, _>("temperature_26").unwrap_or_default().to_owned()),
-			temperature_27: Some(row.get::<f64, _>("temperature_27").unwrap_or_default().to_owned()),
-			temperature_28: Some(row.get::<f64, _>("temperature_28").unwrap_or_default().to_owned()),
-			temperature_29: Some(row.get::<f64, _>("temperature_29").unwrap_or_default().to_owned()),
-			temperature_30: Some(row.get::<f64, _>("temperature_30").unwrap_or_default().to_owned()),
-			temperature_31: Some(row.get::<f64, _>("temperature_31").unwrap_or_default().to_owned()),
-			temperature_32: Some(row.get::<f64, _>("temperature_32").unwrap_or_default().to_owned()),
-			temperature_33: Some(row.get::<f64, _>("temperature_33").unwrap_or_default().to_owned()),
-			temperature_34: Some(row.get::<f64, _>("temperature_34").unwrap_or_default().to_owned()),
-			temperature_35: Some(row.get::<f64, _>("temperature_35").unwrap_or_default().to_owned()),
-			temperature_36: Some(row.get::<f64, _>("temperature_36").unwrap_or_default().to_owned()),
-			temperature_37: Some(row.get::<f64, _>("temperature_37").unwrap_or_default().to_owned()),
-			temperature_38: Some(row.get::<f64, _>("temperature_38").unwrap_or_default().to_owned()),
-			temperature_39: Some(row.get::<f64, _>("temperature_39").unwrap_or_default().to_owned()),
-			temperature_40: Some(row.get::<f64, _>("temperature_40").unwrap_or_default().to_owned()),
-			temperature_41: Some(row.get::<f64, _>("temperature_41").unwrap_or_default().to_owned()),
-			temperature_42: Some(row.get::<f64, _>("temperature_42").unwrap_or_default().to_owned()),
-			temperature_43: Some(row.get::<f64, _>("temperature_43").unwrap_or_default().to_owned()),
-			temperature_44: Some(row.get::<f64, _>("temperature_44").unwrap_or_default().to_owned()),
-			temperature_45: Some(row.get::<f64, _>("temperature_45").unwrap_or_default().to_owned()),
-			temperature_46: Some(row.get::<f64, _>("temperature_46").unwrap_or_default().to_owned()),
-			temperature_47: Some(row.get::<f64, _>("temperature_47").unwrap_or_default().to_owned()),
-			temperature_48: Some(row.get::<f64, _>("temperature_48").unwrap_or_default().to_owned()),
-			temperature_49: Some(row.get::<f64, _>("temperature_49").unwrap_or_default().to_owned()),
-			temperature_50: Some(row.get::<f64, _>("temperature_50").unwrap_or_default().to_owned()),
-			humidity_1: Some(row.get::<f64, _>("humidity_1").unwrap_or_default().to_owned()),
-			humidity_2: Some(row.get::<f64, _>("humidity_2").unwrap_or_default().to_owned()),
-			humidity_3: Some(row.get::<f64, _>("humidity_3").unwrap_or_default().to_owned()),
-			humidity_4: Some(row.get::<f64, _>("humidity_4").unwrap_or_default().to_owned()),
-			humidity_5: Some(row.get::<f64, _>("humidity_5").unwrap_or_default().to_owned()),
-			humidity_6: Some(row.get::<f64, _>("humidity_6").unwrap_or_default().to_owned()),
-			humidity_7: Some(row.get::<f64, _>("humidity_7").unwrap_or_default().to_owned()),
-			humidity_8: Some(row.get::<f64, _>("humidity_8").unwrap_or_default().to_owned()),
-			humidity_9: Some(row.get::<f64, _>("humidity_9").unwrap_or_default().to_owned()),
-			humidity_10: Some(row.get::<f64, _>("humidity_10").unwrap_or_default().to_owned()),
-			humidity_11: Some(row.get::<f64, _>("humidity_11").unwrap_or_default().to_owned()),
-			humidity_12: Some(row.get::<f64, _>("humidity_12").unwrap_or_default().to_owned()),
-			humidity_13: Some(row.get::<f64, _>("humidity_13").unwrap_or_default().to_owned()),
-			humidity_14: Some(row.get::<f64, _>("humidity_14").unwrap_or_default().to_owned()),
-			humidity_15: Some(row.get::<f64, _>("humidity_15").unwrap_or_default().to_owned()),
-			humidity_16: Some(row.get::<f64, _>("humidity_16").unwrap_or_default().to_owned()),
-			humidity_17: Some(row.get::<f64, _>("humidity_17").unwrap_or_default().to_owned()),
-			humidity_18: Some(row.get::<f64, _>("humidity_18").unwrap_or_default().to_owned()),
-			humidity_19: Some(row.get::<f64, _>("humidity_19").unwrap_or_default().to_owned()),
-			humidity_20: Some(row.get::<f64, _>("humidity_20").unwrap_or_default().to_owned()),
-			humidity_21: Some(row.get::<f64, _>("humidity_21").unwrap_or_default().to_owned()),
-			humidity_22: Some(row.get::<f64, _>("humidity_22").unwrap_or_default().to_owned()),
-			humidity_23: Some(row.get::<f64, _>("humidity_23").unwrap_or_default().to_owned()),
-			humidity_24: Some(row.get::<f64, _>("humidity_24").unwrap_or_default().to_owned()),
-			humidity_25: Some(row.get::<f64, _>("humidity_25").unwrap_or_default().to_owned()),
-			humidity_26: Some(row.get::<f64, _>("humidity_26").unwrap_or_default().to_owned()),
-			humidity_27: Some(row.get::<f64, _>("humidity_27").unwrap_or_default().to_owned()),
-			humidity_28: Some(row.get::<f64, _>("humidity_28").unwrap_or_default().to_owned()),
-			humidity_29: Some(row.get::<f64, _>("humidity_29").unwrap_or_default().to_owned()),
-			humidity_30: Some(row.get::<f64, _>("humidity_30").unwrap_or_default().to_owned()),
-			humidity_31: Some(row.get::<f64, _>("humidity_31").unwrap_or_default().to_owned()),
-			humidity_32: Some(row.get::<f64, _>("humidity_32").unwrap_or_default().to_owned()),
-			humidity_33: Some(row.get::<f64, _>("humidity_33").unwrap_or_default().to_owned()),
-			humidity_34: Some(row.get::<f64, _>("humidity_34").unwrap_or_default().to_owned()),
-			humidity_35: Some(row.get::<f64, _>("humidity_35").unwrap_or_default().to_owned()),
-			humidity_36: Some(row.get::<f64, _>("humidity_36").unwrap_or_default().to_owned()),
-			humidity_37: Some(row.get::<f64, _>("humidity_37").unwrap_or_default().to_owned()),
-			humidity_38: Some(row.get::<f64, _>("humidity_38").unwrap_or_default().to_owned()),
-			humidity_39: Some(row.get::<f64, _>("humidity_39").unwrap_or_default().to_owned()),
-			humidity_40: Some(row.get::<f64, _>("humidity_40").unwrap_or_default().to_owned()),
-			humidity_41: Some(row.get::<f64, _>("humidity_41").unwrap_or_default().to_owned()),
-			humidity_42: Some(row.get::<f64, _>("humidity_42").unwrap_or_default().to_owned()),
-			humidity_43: Some(row.get::<f64, _>("humidity_43").unwrap_or_default().to_owned()),
-			humidity_44: Some(row.get::<f64, _>("humidity_44").unwrap_or_default().to_owned()),
-			humidity_45: Some(row.get::<f64, _>("humidity_45").unwrap_or_default().to_owned()),
-			humidity_46: Some(row.get::<f64, _>("humidity_46").unwrap_or_default().to_owned()),
-			humidity_47: Some(row.get::<f64, _>("humidity_47").unwrap_or_default().to_owned()),
-			humidity_48: Some(row.get::<f64, _>("humidity_48").unwrap_or_default().to_owned()),
-			humidity_49: Some(row.get::<f64, _>("humidity_49").unwrap_or_default().to_owned()),
-			humidity_50: Some(row.get::<f64, _>("humidity_50").unwrap_or_default().to_owned()),
-			sensor_numeric_1: Some(row.get::<Decimal, _>("sensor_numeric_1").unwrap_or_default().to_owned()),
-			sensor_numeric_2: Some(row.get::<Decimal, _>("sensor_numeric_2").unwrap_or_default().to_owned()),
-			sensor_numeric_3: Some(row.get::<Decimal, _>("sensor_numeric_3").unwrap_or_default().to_owned()),
-			sensor_numeric_4: Some(row.get::<Decimal, _>("sensor_numeric_4").unwrap_or_default().to_owned()),
-			sensor_numeric_5: Some(row.get::<Decimal, _>("sensor_numeric_5").unwrap_or_default().to_owned()),
-			sensor_numeric_6: Some(row.get::<Decimal, _>("sensor_numeric_6").unwrap_or_default().to_owned()),
-			sensor_numeric_7: Some(row.get::<Decimal, _>("sensor_numeric_7").unwrap_or_default().to_owned()),
-			sensor_numeric_8: Some(row.get::<Decimal, _>("sensor_numeric_8").unwrap_or_default().to_owned()),
-			sensor_numeric_9: Some(row.get::<Decimal, _>("sensor_numeric_9").unwrap_or_default().to_owned()),
-			sensor_numeric_10: Some(row.get::<Decimal, _>("sensor_numeric_10").unwrap_or_default().to_owned()),
-			sensor_numeric_11: Some(row.get::<Decimal, _>("sensor_numeric_11").unwrap_or_default().to_owned()),
-			sensor_numeric_12: Some(row.get::<Decimal, _>("sensor_numeric_12").unwrap_or_default().to_owned()),
-			sensor_numeric_13: Some(row.get::<Decimal, _>("sensor_numeric_13").unwrap_or_default().to_owned()),
-			sensor_numeric_14: Some(row.get::<Decimal, _>("sensor_numeric_14").unwrap_or_default().to_owned()),
-			sensor_numeric_15: Some(row.get::<Decimal, _>("sensor_numeric_15").unwrap_or_default().to_owned()),
-			sensor_numeric_16: Some(row.get::<Decimal, _>("sensor_numeric_16").unwrap_or_default().to_owned()),
-			sensor_numeric_17: Some(row.get::<Decimal, _>("sensor_numeric_17").unwrap_or_default().to_owned()),
-			sensor_numeric_18: Some(row.get::<Decimal, _>("sensor_numeric_18").unwrap_or_default().to_owned()),
-			sensor_numeric_19: Some(row.get::<Decimal, _>("sensor_numeric_19").unwrap_or_default().to_owned()),
-			sensor_numeric_20: Some(row.get::<Decimal, _>("sensor_numeric_20").unwrap_or_default().to_owned()),
-			sensor_numeric_21: Some(row.get::<Decimal, _>("sensor_numeric_21").unwrap_or_default().to_owned()),
-			sensor_numeric_22: Some(row.get::<Decimal, _>("sensor_numeric_22").unwrap_or_default().to_owned()),
-			sensor_numeric_23: Some(row.get::<Decimal, _>("sensor_numeric_23").unwrap_or_default().to_owned()),
-			sensor_numeric_24: Some(row.get::<Decimal, _>("sensor_numeric_24").unwrap_or_default().to_owned()),
-			sensor_numeric_25: Some(row.get::<Decimal, _>("sensor_numeric_25").unwrap_or_default().to_owned()),
-			sensor_decimal_1: Some(row.get::<Decimal, _>("sensor_decimal_1").unwrap_or_default().to_owned()),
-			sensor_decimal_2: Some(row.get::<Decimal, _>("sensor_decimal_2").unwrap_or_default().to_owned()),
-			sensor_decimal_3: Some(row.get::<Decimal, _>("sensor_decimal_3").unwrap_or_default().to_owned()),
-			sensor_decimal_4: Some(row.get::<Decimal, _>("sensor_decimal_4").unwrap_or_default().to_owned()),
-			sensor_decimal_5: Some(row.get::<Decimal, _>("sensor_decimal_5").unwrap_or_default().to_owned()),
-			sensor_decimal_6: Some(row.get::<Decimal, _>("sensor_decimal_6").unwrap_or_default().to_owned()),
-			sensor_decimal_7: Some(row.get::<Decimal, _>("sensor_decimal_7").unwrap_or_default().to_owned()),
-			sensor_decimal_8: Some(row.get::<Decimal, _>("sensor_decimal_8").unwrap_or_default().to_owned()),
-			sensor_decimal_9: Some(row.get::<Decimal, _>("sensor_decimal_9").unwrap_or_default().to_owned()),
-			sensor_decimal_10: Some(row.get::<Decimal, _>("sensor_decimal_10").unwrap_or_default().to_owned()),
-			sensor_decimal_11: Some(row.get::<Decimal, _>("sensor_decimal_11").unwrap_or_default().to_owned()),
-			sensor_decimal_12: Some(row.get::<Decimal, _>("sensor_decimal_12").unwrap_or_default().to_owned()),
-			sensor_decimal_13: Some(row.get::<Decimal, _>("sensor_decimal_13").unwrap_or_default().to_owned()),
-			sensor_decimal_14: Some(row.get::<Decimal, _>("sensor_decimal_14").unwrap_or_default().to_owned()),
-			sensor_decimal_15: Some(row.get::<Decimal, _>("sensor_decimal_15").unwrap_or_default().to_owned()),
-			sensor_decimal_16: Some(row.get::<Decimal, _>("sensor_decimal_16").unwrap_or_default().to_owned()),
-			sensor_decimal_17: Some(row.get::<Decimal, _>("sensor_decimal_17").unwrap_or_default().to_owned()),
-			sensor_decimal_18: Some(row.get::<Decimal, _>("sensor_decimal_18").unwrap_or_default().to_owned()),
-			sensor_decimal_19: Some(row.get::<Decimal, _>("sensor_decimal_19").unwrap_or_default().to_owned()),
-			sensor_decimal_20: Some(row.get::<Decimal, _>("sensor_decimal_20").unwrap_or_default().to_owned()),
-			sensor_decimal_21: Some(row.get::<Decimal, _>("sensor_decimal_21").unwrap_or_default().to_owned()),
-			sensor_decimal_22: Some(row.get::<Decimal, _>("sensor_decimal_22").unwrap_or_default().to_owned()),
-			sensor_decimal_23: Some(row.get::<Decimal, _>("sensor_decimal_23").unwrap_or_default().to_owned()),
-			sensor_decimal_24: Some(row.get::<Decimal, _>("sensor_decimal_24").unwrap_or_default().to_owned()),
-			sensor_decimal_25: Some(row.get::<Decimal, _>("sensor_decimal_25").unwrap_or_default().to_owned()),
+		if self.columns.is_empty() {
+			return Err(AppError::invalid_request("columns", format!("index {} has no key columns", self.name)));
 		}
+		if self.columns.len() > Self::MAX_KEY_COLUMNS {
+			return Err(AppError::invalid_request(
+				"columns",
+				format!(
+					"index {} has {} columns in the key list, the maximum is {}",
+					self.name,
+					self.columns.len(),
+					Self::MAX_KEY_COLUMNS
+				),
+			));
+		}
+		for column in self.columns.iter().chain(self.include.iter()) {
+			if !COLUMNS.contains(&column.as_str()) {
+				return Err(AppError::invalid_request(
+					"columns",
+					format!("index {} uses unknown column {}", self.name, column),
+				));
+			}
+		}
+		Ok(())
 	}
-	pub fn to_row_tiberius(data: &Self) -> tiberius::TokenRow<'_> {
-		let mut row = TokenRow::new();
-		row.push(Some(data.id.clone()).into_sql());
-		row.push(Some(data.created_on).into_sql());
-		row.push(Some(data.modified_on).into_sql());
-		row.push(Some(data.location_1.clone()).into_sql());
-		row.push(Some(data.location_2.clone()).into_sql());
-		row.push(Some(data.location_3.clone()).into_sql());
-		row.push(Some(data.location_4.clone()).into_sql());
-		row.push(Some(data.location_5.clone()).into_sql());
-		row.push(Some(data.location_6.clone()).into_sql());
-		row.push(Some(data.location_7.clone()).into_sql());
-		row.push(Some(data.location_8.clone()).into_sql());
-		row.push(Some(data.location_9.clone()).into_sql());
-		row.push(Some(data.location_10.clone()).into_sql());
-		row.push(Some(data.location_11.clone()).into_sql());
-		row.push(Some(data.location_12.clone()).into_sql());
-		row.push(Some(data.location_13.clone()).into_sql());
-		row.push(Some(data.location_14.clone()).into_sql());
-		row.push(Some(data.location_15.clone()).into_sql());
-		row.push(Some(data.location_16.clone()).into_sql());
-		row.push(Some(data.location_17.clone()).into_sql());
-		row.push(Some(data.location_18.clone()).into_sql());
-		row.push(Some(data.location_19.clone()).into_sql());
-		row.push(Some(data.location_20.clone()).into_sql());
-		row.push(Some(data.location_21.clone()).into_sql());
-		row.push(Some(data.location_22.clone()).into_sql());
-		row.push(Some(data.location_23.clone()).into_sql());
-		row.push(Some(data.location_24.clone()).into_sql());
-		row.push(Some(data.location_25.clone()).into_sql());
-		row.push(Some(data.location_26.clone()).into_sql());
-		row.push(Some(data.location_27.clone()).into_sql());
-		row.push(Some(data.location_28.clone()).into_sql());
-		row.push(Some(data.location_29.clone()).into_sql());
-		row.push(Some(data.location_30.clone()).into_sql());
-		row.push(Some(data.location_31.clone()).into_sql());
-		row.push(Some(data.location_32.clone()).into_sql());
-		row.push(Some(data.location_33.clone()).into_sql());
-		row.push(Some(data.location_34.clone()).into_sql());
-		row.push(Some(data.location_35.clone()).into_sql());
-		row.push(Some(data.location_36.clone()).into_sql());
-		row.push(Some(data.location_37.clone()).into_sql());
-		row.push(Some(data.location_38.clone()).into_sql());
-		row.push(Some(data.location_39.clone()).into_sql());
-		row.push(Some(data.location_40.clone()).into_sql());
-		row.push(Some(data.location_41.clone()).into_sql());
-		row.push(Some(data.location_42.clone()).into_sql());
-		row.push(Some(data.location_43.clone()).into_sql());
-		row.push(Some(data.location_44.clone()).into_sql());
-		row.push(Some(data.location_45.clone()).into_sql());
-		row.push(Some(data.location_46.clone()).into_sql());
-		row.push(Some(data.location_47.clone()).into_sql());
-		row.push(Some(data.location_48.clone()).into_sql());
-		row.push(Some(data.location_49.clone()).into_sql());
-		row.push(Some(data.location_50.clone()).into_sql());
-		row.push(data.temperature_1.into_sql());
-		row.push(data.temperature_2.into_sql());
-		row.push(data.temperature_3.into_sql());
-		row.push(data.temperature_4.into_sql());
-		row.push(data.temperature_5.into_sql());
-		row.push(data.temperature_6.into_sql());
-		row.push(data.temperature_7.into_sql());
-		row.push(data.temperature_8.into_sql());
-		row.push(data.temperature_9.into_sql());
-		row.push(data.temperature_10.into_sql());
-		row.push(data.temperature_11.into_sql());
-		row.push(data.temperature_12.into_sql());
-		row.push(data.temperature_13.into_sql());
-		row.push(data.temperature_14.into_sql());
-		row.push(data.temperature_15.into_sql());
-		row.push(data.temperature_16.into_sql());
-		row.push(data.temperature_17.into_sql());
-		row.push(data.temperature_18.into_sql());
-		row.push(data.temperature_19.into_sql());
-		row.push(data.temperature_20.into_sql());
-		row.push(data.temperature_21.into_sql());
-		row.push(data.temperature_22.into_sql());
-		row.push(data.temperature_23.into_sql());
-		row.push(data.temperature_24.into_sql());
-		row.push(data.temperature_25.into_sql());
-		row.push(data.temperature_26.into_sql());
-		row.push(data.temperature_27.into_sql());
-		row.push(data.temperature_28.into_sql());
-		row.push(data.temperature_29.into_sql());
-		row.push(data.temperature_30.into_sql());
-		row.push(data.temperature_31.into_sql());
-		row.push(data.temperature_32.into_sql());
-		row.push(data.temperature_33.into_sql());
-		row.push(data.temperature_34.into_sql());
-		row.push(data.temperature_35.into_sql());
-		row.push(data.temperature_36.into_sql());
-		row.push(data.temperature_37.into_sql());
-		row.push(data.temperature_38.into_sql());
-		row.push(data.temperature_39.into_sql());
-		row.push(data.temperature_40.into_sql());
-		row.push(data.temperature_41.into_sql());
-		row.push(data.temperature_42.into_sql());
-		row.push(data.temperature_43.into_sql());
-		row.push(data.temperature_44.into_sql());
-		row.push(data.temperature_45.into_sql());
-		row.push(data.temperature_46.into_sql());
-		row.push(data.temperature_47.into_sql());
-		row.push(data.temperature_48.into_sql());
-		row.push(data.temperature_49.into_sql());
-		row.push(data.temperature_50.into_sql());
-		row.push(data.humidity_1.into_sql());
-		row.push(data.humidity_2.into_sql());
-		row.push(data.humidity_3.into_sql());
-		row.push(data.humidity_4.into_sql());
-		row.push(data.humidity_5.into_sql());
-		row.push(data.humidity_6.into_sql());
-		row.push(data.humidity_7.into_sql());
-		row.push(data.humidity_8.into_sql());
-		row.push(data.humidity_9.into_sql());
-		row.push(data.humidity_10.into_sql());
-		row.push(data.humidity_11.into_sql());
-		row.push(data.humidity_12.into_sql());
-		row.push(data.humidity_13.into_sql());
-		row.push(data.humidity_14.into_sql());
-		row.push(data.humidity_15.into_sql());
-		row.push(data.humidity_16.into_sql());
-		row.push(data.humidity_17.into_sql());
-		row.push(data.humidity_18.into_sql());
-		row.push(data.humidity_19.into_sql());
-		row.push(data.humidity_20.into_sql());
-		row.push(data.humidity_21.into_sql());
-		row.push(data.humidity_22.into_sql());
-		row.push(data.humidity_23.into_sql());
-		row.push(data.humidity_24.into_sql());
-		row.push(data.humidity_25.into_sql());
-		row.push(data.humidity_26.into_sql());
-		row.push(data.humidity_27.into_sql());
-		row.push(data.humidity_28.into_sql());
-		row.push(data.humidity_29.into_sql());
-		row.push(data.humidity_30.into_sql());
-		row.push(data.humidity_31.into_sql());
-		row.push(data.humidity_32.into_sql());
-		row.push(data.humidity_33.into_sql());
-		row.push(data.humidity_34.into_sql());
-		row.push(data.humidity_35.into_sql());
-		row.push(data.humidity_36.into_sql());
-		row.push(data.humidity_37.into_sql());
-		row.push(data.humidity_38.into_sql());
-		row.push(data.humidity_39.into_sql());
-		row.push(data.humidity_40.into_sql());
-		row.push(data.humidity_41.into_sql());
-		row.push(data.humidity_42.into_sql());
-		row.push(data.humidity_43.into_sql());
-		row.push(data.humidity_44.into_sql());
-		row.push(data.humidity_45.into_sql());
-		row.push(data.humidity_46.into_sql());
-		row.push(data.humidity_47.into_sql());
-		row.push(data.humidity_48.into_sql());
-		row.push(data.humidity_49.into_sql());
-		row.push(data.humidity_50.into_sql());
-		row.push(data.sensor_numeric_1.to_sql());
-		row.push(data.sensor_numeric_2.to_sql());
-		row.push(data.sensor_numeric_3.to_sql());
-		row.push(data.sensor_numeric_4.to_sql());
-		row.push(data.sensor_numeric_5.to_sql());
-		row.push(data.sensor_numeric_6.to_sql());
-		row.push(data.sensor_numeric_7.to_sql());
-		row.push(data.sensor_numeric_8.to_sql());
-		row.push(data.sensor_numeric_9.to_sql());
-		row.push(data.sensor_numeric_10.to_sql());
-		row.push(data.sensor_numeric_11.to_sql());
-		row.push(data.sensor_numeric_12.to_sql());
-		row.push(data.sensor_numeric_13.to_sql());
-		row.push(data.sensor_numeric_14.to_sql());
-		row.push(data.sensor_numeric_15.to_sql());
-		row.push(data.sensor_numeric_16.to_sql());
-		row.push(data.sensor_numeric_17.to_sql());
-		row.push(data.sensor_numeric_18.to_sql());
-		row.push(data.sensor_numeric_19.to_sql());
-		row.push(data.sensor_numeric_20.to_sql());
-		row.push(data.sensor_numeric_21.to_sql());
-		row.push(data.sensor_numeric_22.to_sql());
-		row.push(data.sensor_numeric_23.to_sql());
-		row.push(data.sensor_numeric_24.to_sql());
-		row.push(data.sensor_numeric_25.to_sql());
-		row.push(data.sensor_decimal_1.to_sql());
-		row.push(data.sensor_decimal_2.to_sql());
-		row.push(data.sensor_decimal_3.to_sql());
-		row.push(data.sensor_decimal_4.to_sql());
-		row.push(data.sensor_decimal_5.to_sql());
-		row.push(data.sensor_decimal_6.to_sql());
-		row.push(data.sensor_decimal_7.to_sql());
-		row.push(data.sensor_decimal_8.to_sql());
-		row.push(data.sensor_decimal_9.to_sql());
-		row.push(data.sensor_decimal_10.to_sql());
-		row.push(data.sensor_decimal_11.to_sql());
-		row.push(data.sensor_decimal_12.to_sql());
-		row.push(data.sensor_decimal_13.to_sql());
-		row.push(data.sensor_decimal_14.to_sql());
-		row.push(data.sensor_decimal_15.to_sql());
-		row.push(data.sensor_decimal_16.to_sql());
-		row.push(data.sensor_decimal_17.to_sql());
-		row.push(data.sensor_decimal_18.to_sql());
-		row.push(data.sensor_decimal_19.to_sql());
-		row.push(data.sensor_decimal_20.to_sql());
-		row.push(data.sensor_decimal_21.to_sql());
-		row.push(data.sensor_decimal_22.to_sql());
-		row.push(data.sensor_decimal_23.to_sql());
-		row.push(data.sensor_decimal_24.to_sql());
-		row.push(data.sensor_decimal_25.to_sql());
-		return row;
-	}
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct IndexSet {
+	pub name: String,
+	// an empty set measures the table with only its primary key
+	#[serde(default)]
+	pub indexes: Vec<IndexDefinition>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IndexExperimentRequest {
+	// the first set is the baseline the others are compared with
+	pub index_sets: Vec<IndexSet>,
+	pub switch: Option<IndexSwitch>,
+	// rows inserted per run
+	pub size: Option<i32>,
+	pub runs: Option<usize>,
+	pub batch_size: Option<usize>,
+	pub workers: Option<usize>,
+	// inclusive read window on created_on, 2023-01-01 to 2023-12-31 when not set
+	pub from: Option<NaiveDate>,
+	pub to: Option<NaiveDate>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IndexSetReport {
+	pub name: String,
+	pub indexes: Vec<String>,
+	// create or rebuild time of the set's indexes
+	pub setup_ms: u128,
+	pub insert_ms: Vec<u128>,
+	pub read_ms: Vec<u128>,
+	pub rows_read: usize,
+	// non clustered index size after the last insert
+	pub index_bytes: Option<i64>,
+	// median insert time above the baseline, the write cost of the set
+	pub insert_overhead_percent: Option<f64>,
+	// median read time below the baseline, the read gain of the set
+	pub read_gain_percent: Option<f64>,
+}
 
+#[derive(Debug, Serialize)]
+pub struct IndexExperimentReport {
+	pub switch: IndexSwitch,
+	pub size: i32,
+	pub runs: usize,
+	pub sets: Vec<IndexSetReport>,
 }
 
-impl ConditionsRequest {
-	
-	pub fn generate_request() -> Self {
-		ConditionsRequest {
-			id: None,
-			location_1: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_2: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_3: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_4: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_5: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_6: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_7: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_8: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_9: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_10: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_11: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_12: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_13: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_14: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_15: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_16: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_17: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_18: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_19: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_20: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_21: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_22: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_23: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_24: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_25: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_26: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_27: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_28: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_29: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_30: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_31: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_32: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_33: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_34: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_35: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_36: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_37: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_38: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_39: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_40: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_41: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_42: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_43: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_44: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_45: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_46: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_47: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_48: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_49: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			location_50: util::generator::generate_word(util::generator::generate_numbers_usize(10, 20)),
-			temperature_1: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_2: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_3: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_4: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_5: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_6: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_7: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_8: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_9: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_10: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_11: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_12: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_13: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_14: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_15: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_16: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_17: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_18: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_19: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_20: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_21: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_22: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_23: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_24: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_25: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_26: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_27: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_28: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_29: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_30: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_31: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_32: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_33: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_34: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_35: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_36: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_37: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_38: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_39: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_40: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_41: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_42: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_43: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_44: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_45: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_46: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_47: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_48: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_49: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			temperature_50: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_1: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_2: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_3: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_4: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_5: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_6: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_7: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_8: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_9: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_10: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_11: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_12: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_13: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_14: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_15: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_16: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_17: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_18: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_19: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_20: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_21: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_22: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_23: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_24: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_25: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_26: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_27: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_28: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_29: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_30: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_31: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_32: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_33: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_34: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_35: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_36: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_37: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_38: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_39: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_40: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_41: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_42: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_43: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_44: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_45: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_46: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_47: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_48: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_49: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			humidity_50: Some(util::generator::generate_numbers_f64(27.0, 60.0)),
-			sensor_numeric_1: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_2: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_3: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_4: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_5: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_6: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_7: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_8: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_9: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_10: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_11: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_12: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_13: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_14: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_15: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_16: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_17: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_18: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_19: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_20: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_21: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_22: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_23: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_24: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_numeric_25: Some(util::generator::generate_numbers_decimal(27, 60, 0)),
-			sensor_decimal_1: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_2: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_3: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_4: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_5: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_6: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_7: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_8: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_9: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_10: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_11: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_12: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_13: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_14: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_15: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_16: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_17: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_18: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_19: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_20: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_21: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_22: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_23: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_24: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
-			sensor_decimal_25: Some(util::generator::generate_numbers_decimal(27, 60, 4)),
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn patch_request_tells_null_from_missing() {
+		let request: ConditionsPatchRequest =
+			serde_json::from_str(r#"{"location_1": "north", "temperature_1": null, "humidity_1": 31.5}"#).unwrap();
+
+		assert_eq!(request.location_1.as_deref(), Some("north"));
+		assert_eq!(request.temperature_1, Some(None));
+		assert_eq!(request.humidity_1, Some(Some(31.5)));
+		assert_eq!(request.temperature_2, None);
+
+		let columns: Vec<&str> = request.to_params_tiberius().into_iter().map(|(column, _)| column).collect();
+		assert_eq!(columns, ["location_1", "temperature_1", "humidity_1"]);
+	}
+
+	#[test]
+	fn invalid_index_definitions_are_request_errors() {
+		let index = |name: &str, columns: &[&str]| IndexDefinition {
+			name: name.to_string(),
+			columns: columns.iter().map(|column| column.to_string()).collect(),
+			include: Vec::new(),
+		};
+
+		assert!(index("ix_location_1", &["location_1"]).validate().is_ok());
+		for invalid in [
+			index("ix location", &["location_1"]),
+			index("ix_empty", &[]),
+			index("ix_unknown", &["location_0"]),
+			index("ix_wide", &COLUMNS[..IndexDefinition::MAX_KEY_COLUMNS + 1]),
+		] {
+			assert!(matches!(invalid.validate(), Err(AppError::InvalidRequest(_))), "{}", invalid.name);
 		}
 	}
-}
\ No newline at end of file
+}
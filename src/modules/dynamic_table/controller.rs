@@ -0,0 +1,212 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, Query},
+    http::StatusCode,
+    routing::{delete, get, post},
+};
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::{
+    dto::{app_error::AppError, app_response::AppResponse},
+    modules::dynamic_table::{
+        repository,
+        schema::{Backend, DynamicRow, TableDefinition},
+    },
+    state::AppState,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct RowsRequest {
+    pub size: Option<i64>,
+}
+
+pub fn new() -> Router {
+    Router::new()
+        .route("/", post(create))
+        .route("/list", get(find_all))
+        .route("/{name}", get(find_by_name))
+        .route("/{name}", delete(delete_by_name))
+        .route("/{name}/rows", get(find_rows))
+}
+
+pub async fn find_all(
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, Json<AppResponse<Vec<TableDefinition>>>), AppError> {
+    let tables = _state.dynamic_tables.read().await;
+    let _result: Vec<TableDefinition> = tables.values().flatten().cloned().collect();
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", Some(_result)))))
+}
+
+pub async fn find_by_name(
+    Path(name): Path<String>,
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, Json<AppResponse<TableDefinition>>), AppError> {
+    let definition = get_definition(&_state, &name).await?;
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", Some(definition)))))
+}
+
+pub async fn create(
+    Extension(_state): Extension<Arc<AppState>>,
+    Json(definition): Json<TableDefinition>,
+) -> Result<(StatusCode, Json<AppResponse<String>>), AppError> {
+    definition.validate().map_err(AppError::InvalidRequest)?;
+
+    // the name is reserved while the table is created, the lock is not held across the ddl
+    {
+        let mut tables = _state.dynamic_tables.write().await;
+        if tables.contains_key(&definition.name) {
+            return Err(AppError::DataExist);
+        }
+        tables.insert(definition.name.clone(), None);
+    }
+
+    let result = create_table(&_state, &definition).await;
+    let mut tables = _state.dynamic_tables.write().await;
+    match result {
+        Ok(()) => {
+            tables.insert(definition.name.clone(), Some(definition));
+        }
+        Err(error) => {
+            tables.remove(&definition.name);
+            return Err(error);
+        }
+    }
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", None))))
+}
+
+pub async fn delete_by_name(
+    Path(name): Path<String>,
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, Json<AppResponse<String>>), AppError> {
+    // taken out of the registry while it is dropped, so benchmarks no longer find it
+    let definition = {
+        let mut tables = _state.dynamic_tables.write().await;
+        match tables.get_mut(&name) {
+            Some(entry) => entry.take().ok_or(AppError::DataExist)?,
+            None => return Err(AppError::NotFound),
+        }
+    };
+
+    let result = drop_table(&_state, &definition).await;
+    let mut tables = _state.dynamic_tables.write().await;
+    match result {
+        Ok(()) => {
+            tables.remove(&name);
+        }
+        Err(error) => {
+            tables.insert(name, Some(definition));
+            return Err(error);
+        }
+    }
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", None))))
+}
+
+// the table is dropped again when its definition can not be saved, a table nobody knows about
+// would make the name unusable
+async fn create_table(state: &AppState, definition: &TableDefinition) -> Result<(), AppError> {
+    let registry = state
+        .pool_pg
+        .get()
+        .await
+        .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+
+    match definition.backend {
+        Backend::Postgres => repository::create_table_postgres(&registry, definition).await?,
+        Backend::SqlServer => {
+            let mut client = state
+                .pool_tiberius
+                .get()
+                .await
+                .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+            repository::create_table_tiberius(&mut client, definition).await?;
+        }
+    }
+
+    if let Err(error) = repository::save_definition(&registry, definition).await {
+        if let Err(drop_error) = drop_table_only(state, definition).await {
+            tracing::error!(?drop_error, table = %definition.name, "dropping the unregistered table failed");
+        }
+        return Err(error);
+    }
+    Ok(())
+}
+
+async fn drop_table(state: &AppState, definition: &TableDefinition) -> Result<(), AppError> {
+    drop_table_only(state, definition).await?;
+
+    let registry = state
+        .pool_pg
+        .get()
+        .await
+        .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+    repository::delete_definition(&registry, &definition.name).await
+}
+
+async fn drop_table_only(state: &AppState, definition: &TableDefinition) -> Result<(), AppError> {
+    match definition.backend {
+        Backend::Postgres => {
+            let client = state
+                .pool_pg
+                .get()
+                .await
+                .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+            repository::drop_table_postgres(&client, definition).await
+        }
+        Backend::SqlServer => {
+            let mut client = state
+                .pool_tiberius
+                .get()
+                .await
+                .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+            repository::drop_table_tiberius(&mut client, definition).await
+        }
+    }
+}
+
+pub async fn find_rows(
+    Path(name): Path<String>,
+    Query(rows_request): Query<RowsRequest>,
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, Json<AppResponse<Vec<DynamicRow>>>), AppError> {
+    let definition = get_definition(&_state, &name).await?;
+    let size = rows_request.size.unwrap_or(10).clamp(1, 1000);
+
+    let _result = match definition.backend {
+        Backend::Postgres => {
+            let client = _state
+                .pool_pg
+                .get()
+                .await
+                .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+            repository::find_all_postgres(&client, &definition, Some(size)).await?
+        }
+        Backend::SqlServer => {
+            let mut client = _state
+                .pool_tiberius
+                .get()
+                .await
+                .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+            repository::find_all_tiberius(&mut client, &definition, Some(size)).await?
+        }
+    };
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", Some(_result)))))
+}
+
+// a table whose create or drop is still running is not found
+pub async fn get_definition(state: &AppState, name: &str) -> Result<TableDefinition, AppError> {
+    let tables = state.dynamic_tables.read().await;
+    tables.get(name).cloned().flatten().ok_or(AppError::NotFound)
+}
@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, Router, extract::Path, http::StatusCode, routing::get};
+use tokio::time::Instant;
+
+use crate::{
+    dto::{app_error::AppError, app_response::AppResponse},
+    modules::dynamic_table::{
+        controller::get_definition,
+        repository,
+        schema::{Backend, DynamicRow, TableDefinition},
+    },
+    state::AppState,
+};
+
+const BATCH_SIZE: usize = 10000;
+
+pub fn new() -> Router {
+    Router::new()
+        .route("/{name}/list", get(find_all))
+        .route("/{name}/generate/{size}", get(generate))
+}
+
+pub async fn find_all(
+    Path(name): Path<String>,
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, Json<AppResponse<String>>), AppError> {
+    let definition = get_definition(&_state, &name).await?;
+
+    let mut durations = String::new();
+    for _ in 0..10 {
+        let start = Instant::now();
+        let _result: Vec<DynamicRow> = match definition.backend {
+            Backend::Postgres => {
+                let client = _state
+                    .pool_pg
+                    .get()
+                    .await
+                    .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+                repository::find_all_postgres(&client, &definition, None).await?
+            }
+            Backend::SqlServer => {
+                let mut client = _state
+                    .pool_tiberius
+                    .get()
+                    .await
+                    .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+                repository::find_all_tiberius(&mut client, &definition, None).await?
+            }
+        };
+        let duration = start.elapsed();
+        if durations.is_empty() {
+            durations = format!("{}", duration.as_millis());
+            continue;
+        }
+        durations = format!("{},{}", durations, duration.as_millis());
+    }
+
+    let status_code = StatusCode::OK;
+    Ok((
+        status_code,
+        Json(AppResponse::ok(
+            format!("Time in milliseconds: {} ms", durations),
+            None,
+        )),
+    ))
+}
+
+pub async fn generate(
+    Path((name, size)): Path<(String, usize)>,
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, Json<AppResponse<String>>), AppError> {
+    let definition = get_definition(&_state, &name).await?;
+
+    let mut durations = String::new();
+    for _ in 0..10 {
+        let start = Instant::now();
+
+        let mut remaining = size;
+        while remaining > 0 {
+            let batch_size = remaining.min(BATCH_SIZE);
+            let rows: Vec<DynamicRow> = (0..batch_size).map(|_| definition.generate_row()).collect();
+            insert_batch(&_state, &definition, &rows).await?;
+            remaining -= batch_size;
+        }
+
+        let duration = start.elapsed();
+        if durations.is_empty() {
+            durations = format!("{}", duration.as_millis());
+            continue;
+        }
+        durations = format!("{},{}", durations, duration.as_millis());
+    }
+
+    let status_code = StatusCode::OK;
+    Ok((
+        status_code,
+        Json(AppResponse::ok(
+            format!("Time in milliseconds: {} ms", durations),
+            None,
+        )),
+    ))
+}
+
+async fn insert_batch(
+    state: &AppState,
+    definition: &TableDefinition,
+    rows: &[DynamicRow],
+) -> Result<u64, AppError> {
+    match definition.backend {
+        Backend::Postgres => {
+            let mut client = state
+                .pool_pg
+                .get()
+                .await
+                .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+            repository::insert_batch_postgres(&mut client, definition, rows).await
+        }
+        Backend::SqlServer => {
+            let mut client = state
+                .pool_tiberius
+                .get()
+                .await
+                .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+            repository::insert_batch_tiberius(&mut client, definition, rows).await
+        }
+    }
+}
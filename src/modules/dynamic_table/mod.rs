@@ -0,0 +1,4 @@
+pub mod schema;
+pub mod repository;
+pub mod controller;
+pub mod controller_benchmark;
@@ -0,0 +1,279 @@
+use futures_util::{StreamExt, pin_mut};
+use rust_decimal::Decimal;
+use tiberius::{QueryItem, TokenRow};
+use tokio_postgres::{binary_copy::BinaryCopyInWriter, types::Type};
+
+use crate::{
+    dto::app_error::AppError,
+    modules::dynamic_table::schema::{ColumnType, DynamicRow, TableDefinition, Value},
+};
+
+// registry, the definitions of both backends are kept in postgres so a restart finds them again,
+// the table comes from the 2025-11-28-000004_create_dynamic_table_definitions migration
+
+const REGISTRY_TABLE: &str = "dynamic_table_definitions";
+
+pub async fn load_definitions(client: &tokio_postgres::Client) -> Result<Vec<TableDefinition>, AppError> {
+    let statement = format!("SELECT definition FROM {} ORDER BY name", REGISTRY_TABLE);
+    let rows = client
+        .query(&statement, &[])
+        .await
+        .map_err(|error| AppError::Other(format!("load definitions failed: {}", error)))?;
+    rows.iter()
+        .map(|row| {
+            serde_json::from_str(row.get("definition"))
+                .map_err(|error| AppError::Other(format!("invalid table definition: {}", error)))
+        })
+        .collect()
+}
+
+pub async fn save_definition(client: &tokio_postgres::Client, definition: &TableDefinition) -> Result<(), AppError> {
+    let json = serde_json::to_string(definition)
+        .map_err(|error| AppError::Other(format!("serialize failed: {}", error)))?;
+    let statement = format!("INSERT INTO {} (name, definition) VALUES ($1, $2)", REGISTRY_TABLE);
+    client
+        .execute(&statement, &[&definition.name, &json])
+        .await
+        .map_err(|error| AppError::Other(format!("save definition failed: {}", error)))?;
+    Ok(())
+}
+
+pub async fn delete_definition(client: &tokio_postgres::Client, name: &str) -> Result<(), AppError> {
+    let statement = format!("DELETE FROM {} WHERE name = $1", REGISTRY_TABLE);
+    client
+        .execute(&statement, &[&name])
+        .await
+        .map_err(|error| AppError::Other(format!("delete definition failed: {}", error)))?;
+    Ok(())
+}
+
+// postgres
+
+pub async fn create_table_postgres(
+    client: &tokio_postgres::Client,
+    definition: &TableDefinition,
+) -> Result<(), AppError> {
+    client
+        .batch_execute(&definition.create_table_statement())
+        .await
+        .map_err(|error| AppError::Other(format!("create table failed: {}", error)))?;
+    Ok(())
+}
+
+pub async fn drop_table_postgres(
+    client: &tokio_postgres::Client,
+    definition: &TableDefinition,
+) -> Result<(), AppError> {
+    let statement = format!("DROP TABLE IF EXISTS {}", definition.quoted_name());
+    client
+        .batch_execute(&statement)
+        .await
+        .map_err(|error| AppError::Other(format!("drop table failed: {}", error)))?;
+    Ok(())
+}
+
+pub async fn insert_batch_postgres(
+    client: &mut tokio_postgres::Client,
+    definition: &TableDefinition,
+    data: &[DynamicRow],
+) -> Result<u64, AppError> {
+    let transaction = client
+        .transaction()
+        .await
+        .map_err(|error| AppError::Other(format!("transaction failed: {}", error)))?;
+
+    let copy_statement = format!(
+        "COPY {} ({}) FROM STDIN (FORMAT binary)",
+        definition.quoted_name(),
+        definition.quoted_column_list()
+    );
+    let sink = transaction
+        .copy_in(&copy_statement)
+        .await
+        .map_err(|error| AppError::Other(format!("copy_in failed: {}", error)))?;
+
+    let types: Vec<Type> = definition
+        .columns
+        .iter()
+        .map(|column| column.column_type.postgres_type())
+        .collect();
+    let writer = BinaryCopyInWriter::new(sink, &types);
+    pin_mut!(writer);
+
+    for row in data {
+        writer
+            .as_mut()
+            .write_raw(row.iter())
+            .await
+            .map_err(|error| AppError::Other(format!("copy write failed: {}", error)))?;
+    }
+
+    let rows_written = writer
+        .finish()
+        .await
+        .map_err(|error| AppError::Other(format!("copy finish failed: {}", error)))?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|error| AppError::Other(format!("commit failed: {}", error)))?;
+
+    Ok(rows_written)
+}
+
+pub async fn find_all_postgres(
+    client: &tokio_postgres::Client,
+    definition: &TableDefinition,
+    limit: Option<i64>,
+) -> Result<Vec<DynamicRow>, AppError> {
+    let mut statement = format!(
+        "SELECT {} FROM {}",
+        definition.quoted_column_list(),
+        definition.quoted_name()
+    );
+    if let Some(limit) = limit {
+        statement = format!("{} LIMIT {}", statement, limit);
+    }
+
+    let rows = client
+        .query(&statement, &[])
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            definition
+                .columns
+                .iter()
+                .enumerate()
+                .map(|(index, column)| match column.column_type {
+                    ColumnType::Varchar { .. } | ColumnType::Text => Value::Text(row.get(index)),
+                    ColumnType::Integer => Value::Integer(row.get(index)),
+                    ColumnType::BigInt => Value::BigInt(row.get(index)),
+                    ColumnType::Float => Value::Float(row.get(index)),
+                    ColumnType::Decimal { .. } => Value::Decimal(row.get(index)),
+                    ColumnType::Timestamp => Value::Timestamp(row.get(index)),
+                    ColumnType::Boolean => Value::Boolean(row.get(index)),
+                })
+                .collect()
+        })
+        .collect())
+}
+
+// sql server
+
+pub async fn create_table_tiberius(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    definition: &TableDefinition,
+) -> Result<(), AppError> {
+    client
+        .simple_query(definition.create_table_statement())
+        .await
+        .map_err(|error| AppError::Other(format!("create table failed: {}", error)))?
+        .into_results()
+        .await
+        .map_err(|error| AppError::Other(format!("create table failed: {}", error)))?;
+    Ok(())
+}
+
+pub async fn drop_table_tiberius(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    definition: &TableDefinition,
+) -> Result<(), AppError> {
+    let statement = format!("DROP TABLE IF EXISTS {}", definition.quoted_name());
+    client
+        .simple_query(statement)
+        .await
+        .map_err(|error| AppError::Other(format!("drop table failed: {}", error)))?
+        .into_results()
+        .await
+        .map_err(|error| AppError::Other(format!("drop table failed: {}", error)))?;
+    Ok(())
+}
+
+pub async fn insert_batch_tiberius(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    definition: &TableDefinition,
+    data: &[DynamicRow],
+) -> Result<u64, AppError> {
+    if data.is_empty() {
+        return Ok(0);
+    }
+
+    let table = definition.quoted_name();
+    let mut bulk_insert = client
+        .bulk_insert(&table)
+        .await
+        .map_err(|err| AppError::Other(format!("{:?}", err)))?;
+
+    for row in data {
+        let mut token_row = TokenRow::new();
+        for value in row {
+            token_row.push(tiberius::ToSql::to_sql(value));
+        }
+        bulk_insert
+            .send(token_row)
+            .await
+            .map_err(|err| AppError::Other(format!("{:?}", err)))?;
+    }
+
+    let result = bulk_insert
+        .finalize()
+        .await
+        .map_err(|err| AppError::Other(format!("{:?}", err)))?;
+
+    Ok(result.total())
+}
+
+pub async fn find_all_tiberius(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    definition: &TableDefinition,
+    limit: Option<i64>,
+) -> Result<Vec<DynamicRow>, AppError> {
+    let top = match limit {
+        Some(limit) => format!("TOP {} ", limit),
+        None => String::new(),
+    };
+    let statement = format!(
+        "SELECT {}{} FROM {}",
+        top,
+        definition.quoted_column_list(),
+        definition.quoted_name()
+    );
+    let mut stream = client
+        .simple_query(statement)
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+
+    let mut rows = Vec::new();
+
+    while let Some(item) = stream
+        .next()
+        .await
+        .transpose()
+        .map_err(|err| AppError::Other(format!("{:?}", err)))?
+    {
+        if let QueryItem::Row(row) = item {
+            let values: DynamicRow = definition
+                .columns
+                .iter()
+                .enumerate()
+                .map(|(index, column)| match column.column_type {
+                    ColumnType::Varchar { .. } | ColumnType::Text => {
+                        Value::Text(row.get::<&str, _>(index).map(str::to_owned))
+                    }
+                    ColumnType::Integer => Value::Integer(row.get::<i32, _>(index)),
+                    ColumnType::BigInt => Value::BigInt(row.get::<i64, _>(index)),
+                    ColumnType::Float => Value::Float(row.get::<f64, _>(index)),
+                    ColumnType::Decimal { .. } => Value::Decimal(row.get::<Decimal, _>(index)),
+                    ColumnType::Timestamp => Value::Timestamp(row.get(index)),
+                    ColumnType::Boolean => Value::Boolean(row.get::<bool, _>(index)),
+                })
+                .collect();
+            rows.push(values);
+        }
+    }
+
+    Ok(rows)
+}
@@ -0,0 +1,478 @@
+use std::error::Error;
+
+use bytes::BytesMut;
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tiberius::ColumnData;
+use tokio_postgres::types::{IsNull, ToSql, Type, to_sql_checked};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::util;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    Postgres,
+    SqlServer,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ColumnType {
+    Varchar { length: u32 },
+    Text,
+    Integer,
+    BigInt,
+    Float,
+    Decimal { precision: u32, scale: u32 },
+    Timestamp,
+    Boolean,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum GeneratorSpec {
+    Uuid,
+    Word { min_length: usize, max_length: usize },
+    Integer { min: i64, max: i64 },
+    Float { min: f64, max: f64 },
+    Decimal { min: i32, max: i32 },
+    Date { min_year: i32, max_year: i32 },
+    Now,
+    Boolean,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[validate(schema(function = "validate_generator"))]
+pub struct ColumnDefinition {
+    #[validate(custom(function = "validate_identifier"))]
+    pub name: String,
+    #[serde(flatten)]
+    pub column_type: ColumnType,
+    #[serde(default)]
+    pub nullable: bool,
+    pub generator: GeneratorSpec,
+    // share of generated values that are NULL, only used for nullable columns
+    #[serde(default)]
+    #[validate(range(min = 0.0, max = 1.0))]
+    pub null_ratio: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[validate(schema(function = "validate_table"))]
+pub struct TableDefinition {
+    #[validate(custom(function = "validate_identifier"))]
+    pub name: String,
+    pub backend: Backend,
+    #[validate(length(min = 1, max = 1024), nested)]
+    pub columns: Vec<ColumnDefinition>,
+    #[serde(default)]
+    pub primary_key: Vec<String>,
+}
+
+// a single generated or fetched cell, typed so NULLs can be sent to both drivers
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Value {
+    Text(Option<String>),
+    Integer(Option<i32>),
+    BigInt(Option<i64>),
+    Float(Option<f64>),
+    Decimal(Option<Decimal>),
+    Timestamp(Option<NaiveDateTime>),
+    Boolean(Option<bool>),
+}
+
+pub type DynamicRow = Vec<Value>;
+
+fn validate_identifier(value: &str) -> Result<(), ValidationError> {
+    let mut chars = value.chars();
+    let valid_start = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+    if !valid_start || value.len() > 128 || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(ValidationError::new("invalid identifier"));
+    }
+    Ok(())
+}
+
+// sql server DECIMAL goes up to 38 digits, rust_decimal keeps at most 28 after the point
+const MAX_DECIMAL_PRECISION: u32 = 38;
+const MAX_DECIMAL_SCALE: u32 = 28;
+// the longest VARCHAR postgres accepts, NVARCHAR takes at most 4000 characters before MAX
+const MAX_VARCHAR_LENGTH: u32 = 10485760;
+const MAX_NVARCHAR_LENGTH: u32 = 4000;
+// DATETIME2 starts at year 1, both backends end at 9999 or later
+const MIN_YEAR: i32 = 1;
+const MAX_YEAR: i32 = 9999;
+
+fn invalid(code: &'static str, message: String) -> ValidationError {
+    ValidationError::new(code).with_message(message.into())
+}
+
+// the generators panic on empty ranges or values the column can not hold, so the spec is
+// checked before the table is registered
+fn validate_generator(column: &ColumnDefinition) -> Result<(), ValidationError> {
+    if let ColumnType::Varchar { length } = column.column_type
+        && (length == 0 || length > MAX_VARCHAR_LENGTH)
+    {
+        return Err(invalid(
+            "varchar",
+            format!("{}: length must be 1..={}", column.name, MAX_VARCHAR_LENGTH),
+        ));
+    }
+    if let ColumnType::Decimal { precision, scale } = column.column_type
+        && (precision == 0 || precision > MAX_DECIMAL_PRECISION || scale > precision || scale > MAX_DECIMAL_SCALE)
+    {
+        return Err(invalid(
+            "decimal",
+            format!(
+                "{}: precision must be 1..={} and scale at most the precision and {}",
+                column.name, MAX_DECIMAL_PRECISION, MAX_DECIMAL_SCALE
+            ),
+        ));
+    }
+
+    if !column.is_generator_compatible() {
+        return Err(invalid(
+            "generator",
+            format!("{}: generator {:?} can not fill the column", column.name, column.generator),
+        ));
+    }
+
+    match (&column.generator, column.column_type) {
+        (GeneratorSpec::Word { min_length, max_length }, _) if min_length > max_length => Err(invalid(
+            "range",
+            format!("{}: min_length is greater than max_length", column.name),
+        )),
+        (GeneratorSpec::Integer { min, max }, _) if min > max => {
+            Err(invalid("range", format!("{}: min is greater than max", column.name)))
+        }
+        (GeneratorSpec::Integer { min, max }, ColumnType::Integer)
+            if i32::try_from(*min).is_err() || i32::try_from(*max).is_err() =>
+        {
+            Err(invalid("range", format!("{}: min and max must fit in an INTEGER column", column.name)))
+        }
+        (GeneratorSpec::Float { min, max }, _) if !min.is_finite() || !max.is_finite() || min > max => Err(invalid(
+            "range",
+            format!("{}: min and max must be finite and min at most max", column.name),
+        )),
+        (GeneratorSpec::Decimal { min, max }, _) if min > max => {
+            Err(invalid("range", format!("{}: min is greater than max", column.name)))
+        }
+        (GeneratorSpec::Decimal { min, max }, ColumnType::Decimal { precision, .. })
+            if digits(*min).max(digits(*max)) > precision =>
+        {
+            Err(invalid(
+                "range",
+                format!("{}: min and max need more than {} digits", column.name, precision),
+            ))
+        }
+        (GeneratorSpec::Date { min_year, max_year }, _)
+            if min_year > max_year || *min_year < MIN_YEAR || *max_year > MAX_YEAR =>
+        {
+            Err(invalid(
+                "range",
+                format!("{}: years must be {}..={} and min_year at most max_year", column.name, MIN_YEAR, MAX_YEAR),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+// checks across the columns, the primary key and limits that depend on the backend
+fn validate_table(definition: &TableDefinition) -> Result<(), ValidationError> {
+    for key in &definition.primary_key {
+        match definition.columns.iter().find(|column| &column.name == key) {
+            None => return Err(invalid("primary_key", format!("{}: unknown primary key column", key))),
+            Some(column) if column.nullable => {
+                return Err(invalid("primary_key", format!("{}: a primary key column can not be nullable", key)));
+            }
+            Some(_) => {}
+        }
+    }
+
+    if definition.backend != Backend::SqlServer {
+        return Ok(());
+    }
+    match definition
+        .columns
+        .iter()
+        .find(|column| matches!(column.column_type, ColumnType::Varchar { length } if length > MAX_NVARCHAR_LENGTH))
+    {
+        Some(column) => Err(invalid(
+            "varchar",
+            format!("{}: NVARCHAR length must be at most {}, use text instead", column.name, MAX_NVARCHAR_LENGTH),
+        )),
+        None => Ok(()),
+    }
+}
+
+// digits of the generated integer part, the scale moves them behind the point
+fn digits(value: i32) -> u32 {
+    value.unsigned_abs().checked_ilog10().map_or(1, |log| log + 1)
+}
+
+impl ColumnType {
+    pub fn to_postgres_sql(&self) -> String {
+        match self {
+            ColumnType::Varchar { length } => format!("VARCHAR({})", length),
+            ColumnType::Text => "TEXT".to_string(),
+            ColumnType::Integer => "INTEGER".to_string(),
+            ColumnType::BigInt => "BIGINT".to_string(),
+            ColumnType::Float => "DOUBLE PRECISION".to_string(),
+            ColumnType::Decimal { precision, scale } => format!("NUMERIC({}, {})", precision, scale),
+            ColumnType::Timestamp => "TIMESTAMP".to_string(),
+            ColumnType::Boolean => "BOOLEAN".to_string(),
+        }
+    }
+
+    pub fn to_sql_server_sql(&self) -> String {
+        match self {
+            ColumnType::Varchar { length } => format!("NVARCHAR({})", length),
+            ColumnType::Text => "NVARCHAR(MAX)".to_string(),
+            ColumnType::Integer => "INT".to_string(),
+            ColumnType::BigInt => "BIGINT".to_string(),
+            ColumnType::Float => "FLOAT".to_string(),
+            ColumnType::Decimal { precision, scale } => format!("DECIMAL({}, {})", precision, scale),
+            ColumnType::Timestamp => "DATETIME2".to_string(),
+            ColumnType::Boolean => "BIT".to_string(),
+        }
+    }
+
+    pub fn postgres_type(&self) -> Type {
+        match self {
+            ColumnType::Varchar { .. } => Type::VARCHAR,
+            ColumnType::Text => Type::TEXT,
+            ColumnType::Integer => Type::INT4,
+            ColumnType::BigInt => Type::INT8,
+            ColumnType::Float => Type::FLOAT8,
+            ColumnType::Decimal { .. } => Type::NUMERIC,
+            ColumnType::Timestamp => Type::TIMESTAMP,
+            ColumnType::Boolean => Type::BOOL,
+        }
+    }
+
+    pub fn null_value(&self) -> Value {
+        match self {
+            ColumnType::Varchar { .. } | ColumnType::Text => Value::Text(None),
+            ColumnType::Integer => Value::Integer(None),
+            ColumnType::BigInt => Value::BigInt(None),
+            ColumnType::Float => Value::Float(None),
+            ColumnType::Decimal { .. } => Value::Decimal(None),
+            ColumnType::Timestamp => Value::Timestamp(None),
+            ColumnType::Boolean => Value::Boolean(None),
+        }
+    }
+}
+
+impl ColumnDefinition {
+    pub fn generate(&self) -> Value {
+        if self.nullable
+            && self.null_ratio > 0.0
+            && util::generator::generate_numbers_f64(0.0, 1.0) < self.null_ratio
+        {
+            return self.column_type.null_value();
+        }
+
+        match (&self.generator, self.column_type) {
+            (GeneratorSpec::Uuid, _) => Value::Text(Some(Uuid::new_v4().to_string())),
+            (GeneratorSpec::Word { min_length, max_length }, _) => Value::Text(Some(
+                util::generator::generate_word(util::generator::generate_numbers_usize(*min_length, *max_length)),
+            )),
+            // min and max are checked to fit an i32 by validate_generator
+            (GeneratorSpec::Integer { min, max }, ColumnType::Integer) => Value::Integer(Some(
+                util::generator::generate_numbers_i32(
+                    i32::try_from(*min).unwrap_or(i32::MIN),
+                    i32::try_from(*max).unwrap_or(i32::MAX),
+                ),
+            )),
+            (GeneratorSpec::Integer { min, max }, _) => {
+                Value::BigInt(Some(util::generator::generate_numbers_i64(*min, *max)))
+            }
+            (GeneratorSpec::Float { min, max }, _) => {
+                Value::Float(Some(util::generator::generate_numbers_f64(*min, *max)))
+            }
+            (GeneratorSpec::Decimal { min, max }, ColumnType::Decimal { scale, .. }) => Value::Decimal(
+                Some(util::generator::generate_numbers_decimal(*min, *max, scale)),
+            ),
+            (GeneratorSpec::Decimal { min, max }, _) => {
+                Value::Decimal(Some(util::generator::generate_numbers_decimal(*min, *max, 0)))
+            }
+            (GeneratorSpec::Date { min_year, max_year }, _) => Value::Timestamp(Some(
+                util::generator::generate_naivedate(*min_year, *max_year)
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap_or_default(),
+            )),
+            (GeneratorSpec::Now, _) => Value::Timestamp(Some(chrono::Utc::now().naive_utc())),
+            (GeneratorSpec::Boolean, _) => {
+                Value::Boolean(Some(util::generator::generate_numbers_i32(0, 1) == 1))
+            }
+        }
+    }
+
+    // the generator has to produce the same kind of value the column stores
+    pub fn is_generator_compatible(&self) -> bool {
+        matches!(
+            (&self.generator, self.column_type),
+            (GeneratorSpec::Uuid, ColumnType::Varchar { .. } | ColumnType::Text)
+                | (GeneratorSpec::Word { .. }, ColumnType::Varchar { .. } | ColumnType::Text)
+                | (GeneratorSpec::Integer { .. }, ColumnType::Integer | ColumnType::BigInt)
+                | (GeneratorSpec::Float { .. }, ColumnType::Float)
+                | (GeneratorSpec::Decimal { .. }, ColumnType::Decimal { .. })
+                | (GeneratorSpec::Date { .. }, ColumnType::Timestamp)
+                | (GeneratorSpec::Now, ColumnType::Timestamp)
+                | (GeneratorSpec::Boolean, ColumnType::Boolean)
+        )
+    }
+}
+
+impl TableDefinition {
+    pub fn generate_row(&self) -> DynamicRow {
+        self.columns.iter().map(ColumnDefinition::generate).collect()
+    }
+
+    // names are quoted in every statement so reserved words like order or user work, which
+    // also keeps their case on postgres
+    pub fn quote(&self, name: &str) -> String {
+        match self.backend {
+            Backend::Postgres => format!("\"{}\"", name.replace('"', "\"\"")),
+            Backend::SqlServer => format!("[{}]", name.replace(']', "]]")),
+        }
+    }
+
+    pub fn quoted_name(&self) -> String {
+        self.quote(&self.name)
+    }
+
+    pub fn quoted_column_list(&self) -> String {
+        self.columns
+            .iter()
+            .map(|column| self.quote(&column.name))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    pub fn create_table_statement(&self) -> String {
+        let mut definitions: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| {
+                let sql_type = match self.backend {
+                    Backend::Postgres => column.column_type.to_postgres_sql(),
+                    Backend::SqlServer => column.column_type.to_sql_server_sql(),
+                };
+                let nullable = if column.nullable { "NULL" } else { "NOT NULL" };
+                format!("{} {} {}", self.quote(&column.name), sql_type, nullable)
+            })
+            .collect();
+
+        if !self.primary_key.is_empty() {
+            let keys: Vec<String> = self.primary_key.iter().map(|key| self.quote(key)).collect();
+            definitions.push(format!("PRIMARY KEY ({})", keys.join(", ")));
+        }
+
+        format!("CREATE TABLE {} ({})", self.quoted_name(), definitions.join(", "))
+    }
+}
+
+impl ToSql for Value {
+    fn to_sql(&self, ty: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        match self {
+            Value::Text(value) => value.to_sql(ty, out),
+            Value::Integer(value) => value.to_sql(ty, out),
+            Value::BigInt(value) => value.to_sql(ty, out),
+            Value::Float(value) => value.to_sql(ty, out),
+            Value::Decimal(value) => value.to_sql(ty, out),
+            Value::Timestamp(value) => value.to_sql(ty, out),
+            Value::Boolean(value) => value.to_sql(ty, out),
+        }
+    }
+
+    fn accepts(_ty: &Type) -> bool {
+        true
+    }
+
+    to_sql_checked!();
+}
+
+impl tiberius::ToSql for Value {
+    fn to_sql(&self) -> ColumnData<'_> {
+        match self {
+            Value::Text(value) => tiberius::ToSql::to_sql(value),
+            Value::Integer(value) => tiberius::ToSql::to_sql(value),
+            Value::BigInt(value) => tiberius::ToSql::to_sql(value),
+            Value::Float(value) => tiberius::ToSql::to_sql(value),
+            Value::Decimal(value) => tiberius::ToSql::to_sql(value),
+            Value::Timestamp(value) => tiberius::ToSql::to_sql(value),
+            Value::Boolean(value) => tiberius::ToSql::to_sql(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definition(backend: Backend, length: u32) -> TableDefinition {
+        TableDefinition {
+            name: "order".to_string(),
+            backend,
+            columns: vec![
+                ColumnDefinition {
+                    name: "user".to_string(),
+                    column_type: ColumnType::Varchar { length },
+                    nullable: false,
+                    generator: GeneratorSpec::Uuid,
+                    null_ratio: 0.0,
+                },
+                ColumnDefinition {
+                    name: "Amount".to_string(),
+                    column_type: ColumnType::Integer,
+                    nullable: true,
+                    generator: GeneratorSpec::Integer { min: 0, max: 10 },
+                    null_ratio: 0.5,
+                },
+            ],
+            primary_key: vec!["user".to_string()],
+        }
+    }
+
+    #[test]
+    fn create_table_statement_quotes_identifiers() {
+        assert_eq!(
+            definition(Backend::Postgres, 36).create_table_statement(),
+            r#"CREATE TABLE "order" ("user" VARCHAR(36) NOT NULL, "Amount" INTEGER NULL, PRIMARY KEY ("user"))"#
+        );
+        assert_eq!(
+            definition(Backend::SqlServer, 36).create_table_statement(),
+            "CREATE TABLE [order] ([user] NVARCHAR(36) NOT NULL, [Amount] INT NULL, PRIMARY KEY ([user]))"
+        );
+    }
+
+    #[test]
+    fn varchar_length_is_checked_per_backend() {
+        assert!(definition(Backend::Postgres, 36).validate().is_ok());
+        assert!(definition(Backend::Postgres, 0).validate().is_err());
+        assert!(definition(Backend::Postgres, 8000).validate().is_ok());
+        assert!(definition(Backend::SqlServer, 4000).validate().is_ok());
+        assert!(definition(Backend::SqlServer, 4001).validate().is_err());
+    }
+
+    #[test]
+    fn primary_key_and_generators_are_checked() {
+        let mut unknown_key = definition(Backend::Postgres, 36);
+        unknown_key.primary_key = vec!["missing".to_string()];
+        assert!(unknown_key.validate().is_err());
+
+        let mut nullable_key = definition(Backend::Postgres, 36);
+        nullable_key.primary_key = vec!["Amount".to_string()];
+        assert!(nullable_key.validate().is_err());
+
+        let mut incompatible = definition(Backend::Postgres, 36);
+        incompatible.columns[1].generator = GeneratorSpec::Boolean;
+        assert!(incompatible.validate().is_err());
+    }
+}
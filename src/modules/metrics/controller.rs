@@ -0,0 +1,28 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Router,
+    http::{StatusCode, header},
+    routing::get,
+};
+
+use crate::{dto::app_error::AppError, modules::metrics::registry, state::AppState};
+
+pub fn new() -> Router {
+    Router::new().route("/", get(find_metrics))
+}
+
+// prometheus scrape target, plain text instead of AppResponse
+pub async fn find_metrics(
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, [(header::HeaderName, &'static str); 1], String), AppError> {
+    registry::update_pools(&_state);
+    let _result = registry::encode()?;
+
+    let status_code = StatusCode::OK;
+    Ok((
+        status_code,
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        _result,
+    ))
+}
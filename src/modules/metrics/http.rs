@@ -0,0 +1,41 @@
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use tokio::time::Instant;
+
+use crate::modules::metrics::registry;
+
+// decrements the in-flight gauge also when the client goes away and the handler is dropped
+struct InFlight;
+
+impl InFlight {
+    fn start() -> Self {
+        registry::http_requests_in_flight().inc();
+        InFlight
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        registry::http_requests_in_flight().dec();
+    }
+}
+
+// labelled with the route template instead of the uri so ids do not become label values,
+// added as a route layer because the matched path is only known after routing
+pub async fn track(request: Request, next: Next) -> Response {
+    let method = request.method().to_string();
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_default();
+
+    let _in_flight = InFlight::start();
+    let start = Instant::now();
+    let response = next.run(request).await;
+    registry::record_http_request(&method, &path, response.status().as_u16(), start.elapsed());
+    response
+}
@@ -0,0 +1,101 @@
+use std::time::Instant;
+
+use tracing::{
+    Subscriber,
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+};
+use tracing_subscriber::{Layer, layer::Context, registry::LookupSpan};
+
+use crate::modules::metrics::registry;
+
+// turns the spans of repository functions into db call metrics, so the duration and the `rows`
+// field recorded for logging are also what prometheus sees
+pub struct DbCallLayer;
+
+struct DbCall {
+    started: Instant,
+    rows: Option<u64>,
+}
+
+impl Visit for DbCall {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "rows" {
+            self.rows = Some(value);
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        if field.name() == "rows" {
+            self.rows = Some(value.max(0) as u64);
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
+
+// only spans created in a `repository` module are database calls
+pub fn is_db_call(target: &str) -> bool {
+    target.ends_with("::repository")
+}
+
+// e.g. axum_benchmark_database::modules::conditions_tiberius::repository
+fn repository(target: &str) -> &str {
+    target
+        .trim_end_matches("::repository")
+        .rsplit("::")
+        .next()
+        .unwrap_or(target)
+}
+
+fn driver(repository: &str) -> &str {
+    match repository {
+        "conditions" | "conditions_kafka" => "tokio_postgres",
+        "conditions_diesel" => "diesel",
+        "conditions_tiberius" | "conditions_tiberius_columns" => "tiberius",
+        other => other,
+    }
+}
+
+impl<S> Layer<S> for DbCallLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut call = DbCall {
+            started: Instant::now(),
+            rows: None,
+        };
+        attrs.record(&mut call);
+        span.extensions_mut().insert(call);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id)
+            && let Some(call) = span.extensions_mut().get_mut::<DbCall>()
+        {
+            values.record(call);
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(call) = span.extensions_mut().remove::<DbCall>() else {
+            return;
+        };
+        let metadata = span.metadata();
+        let repository = repository(metadata.target());
+        registry::record_db_call(
+            driver(repository),
+            repository,
+            metadata.name(),
+            call.started.elapsed(),
+            call.rows,
+        );
+    }
+}
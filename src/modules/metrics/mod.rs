@@ -0,0 +1,4 @@
+pub mod registry;
+pub mod layer;
+pub mod http;
+pub mod controller;
\ No newline at end of file
@@ -0,0 +1,161 @@
+use std::time::Duration;
+
+use lazy_static::lazy_static;
+use prometheus::{
+    HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, TextEncoder, exponential_buckets,
+    register_histogram_vec, register_int_counter_vec, register_int_gauge, register_int_gauge_vec,
+};
+
+use crate::{dto::app_error::AppError, state::AppState};
+
+// everything goes to the default prometheus registry, /metrics encodes all of it
+
+lazy_static! {
+    // 1ms up to about a minute
+    static ref DB_CALL_DURATION: HistogramVec = register_histogram_vec!(
+        "db_call_duration_seconds",
+        "Duration of repository calls",
+        &["driver", "repository", "operation"],
+        exponential_buckets(0.001, 2.0, 17).unwrap()
+    )
+    .unwrap();
+    static ref DB_ROWS: IntCounterVec = register_int_counter_vec!(
+        "db_rows_total",
+        "Rows read or written by repository calls",
+        &["driver", "repository", "operation", "direction"]
+    )
+    .unwrap();
+    static ref DB_POOL_CONNECTIONS: IntGaugeVec = register_int_gauge_vec!(
+        "db_pool_connections",
+        "Connection pool status, read when /metrics is scraped",
+        &["pool", "state"]
+    )
+    .unwrap();
+
+    static ref KAFKA_MESSAGES: IntCounterVec = register_int_counter_vec!(
+        "kafka_messages_total",
+        "Messages produced to or consumed from the message bus",
+        &["bus", "direction", "topic"]
+    )
+    .unwrap();
+    static ref KAFKA_PAYLOAD_BYTES: IntCounterVec = register_int_counter_vec!(
+        "kafka_payload_bytes_total",
+        "Payload bytes produced to or consumed from the message bus",
+        &["bus", "direction"]
+    )
+    .unwrap();
+    static ref KAFKA_ERRORS: IntCounterVec = register_int_counter_vec!(
+        "kafka_errors_total",
+        "Failed sends and receives on the message bus",
+        &["bus", "direction"]
+    )
+    .unwrap();
+
+    static ref HTTP_REQUESTS: IntCounterVec = register_int_counter_vec!(
+        "http_requests_total",
+        "Handled http requests",
+        &["method", "path", "status"]
+    )
+    .unwrap();
+    static ref HTTP_REQUEST_DURATION: HistogramVec = register_histogram_vec!(
+        "http_request_duration_seconds",
+        "Duration of http requests",
+        &["method", "path"],
+        exponential_buckets(0.001, 2.0, 20).unwrap()
+    )
+    .unwrap();
+    static ref HTTP_REQUESTS_IN_FLIGHT: IntGauge = register_int_gauge!(
+        "http_requests_in_flight",
+        "Http requests being handled"
+    )
+    .unwrap();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Read,
+    Written,
+}
+
+impl Direction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Read => "read",
+            Direction::Written => "written",
+        }
+    }
+}
+
+pub fn record_db_call(driver: &str, repository: &str, operation: &str, duration: Duration, rows: Option<u64>) {
+    DB_CALL_DURATION
+        .with_label_values(&[driver, repository, operation])
+        .observe(duration.as_secs_f64());
+
+    if let Some(rows) = rows {
+        let direction = match operation.starts_with("find") || operation.starts_with("count") {
+            true => Direction::Read,
+            false => Direction::Written,
+        };
+        DB_ROWS
+            .with_label_values(&[driver, repository, operation, direction.as_str()])
+            .inc_by(rows);
+    }
+}
+
+// produced is counted once the bus acknowledged the message
+pub fn record_message(bus: &str, direction: &str, topic: &str, payload_bytes: usize) {
+    KAFKA_MESSAGES.with_label_values(&[bus, direction, topic]).inc();
+    KAFKA_PAYLOAD_BYTES
+        .with_label_values(&[bus, direction])
+        .inc_by(payload_bytes as u64);
+}
+
+pub fn record_message_error(bus: &str, direction: &str) {
+    KAFKA_ERRORS.with_label_values(&[bus, direction]).inc();
+}
+
+pub fn record_http_request(method: &str, path: &str, status: u16, duration: Duration) {
+    HTTP_REQUESTS
+        .with_label_values(&[method, path, &status.to_string()])
+        .inc();
+    HTTP_REQUEST_DURATION
+        .with_label_values(&[method, path])
+        .observe(duration.as_secs_f64());
+}
+
+pub fn http_requests_in_flight() -> &'static IntGauge {
+    &HTTP_REQUESTS_IN_FLIGHT
+}
+
+// pools have no change events, their status is copied into the gauges on every scrape
+pub fn update_pools(state: &AppState) {
+    let diesel = state.diesel_pool_pg.state();
+    set_pool("diesel_pool_pg", "max_size", state.diesel_pool_pg.max_size() as usize);
+    set_pool("diesel_pool_pg", "size", diesel.connections as usize);
+    set_pool("diesel_pool_pg", "available", diesel.idle_connections as usize);
+
+    let pool_pg = state.pool_pg.status();
+    set_pool("pool_pg", "max_size", pool_pg.max_size);
+    set_pool("pool_pg", "size", pool_pg.size);
+    set_pool("pool_pg", "available", pool_pg.available);
+    set_pool("pool_pg", "waiting", pool_pg.waiting);
+
+    let pool_tiberius = state.pool_tiberius.status();
+    set_pool("pool_tiberius", "max_size", pool_tiberius.max_size);
+    set_pool("pool_tiberius", "size", pool_tiberius.size);
+    set_pool("pool_tiberius", "available", pool_tiberius.available);
+    set_pool("pool_tiberius", "waiting", pool_tiberius.waiting);
+}
+
+fn set_pool(pool: &str, state: &str, value: usize) {
+    DB_POOL_CONNECTIONS
+        .with_label_values(&[pool, state])
+        .set(value as i64);
+}
+
+// prometheus text exposition format
+pub fn encode() -> Result<String, AppError> {
+    TextEncoder::new()
+        .encode_to_string(&prometheus::gather())
+        .map_err(|error| AppError::Other(format!("encode metrics failed: {}", error)))
+}
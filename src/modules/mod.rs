@@ -2,4 +2,10 @@ pub mod conditions_diesel;
 pub mod conditions;
 pub mod conditions_kafka;
 pub mod conditions_tiberius;
-pub mod conditions_tiberius_columns;
\ No newline at end of file
+pub mod conditions_tiberius_columns;
+pub mod dynamic_table;
+pub mod metrics;
+pub mod provisioning;
+pub mod query_plan;
+pub mod server_metrics;
+pub mod storage;
\ No newline at end of file
@@ -0,0 +1,228 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, Query},
+    http::StatusCode,
+    routing::{get, post},
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use validator::Validate;
+
+use crate::{
+    config::environment::CONFIG,
+    dto::{app_error::AppError, app_response::AppResponse},
+    modules::{
+        dynamic_table::schema::Backend,
+        provisioning::{
+            ddl, migration, partition_manager, repository,
+            schema::{
+                Layout, MigrationStatus, PartitionChange, PartitionInfo, PartitionInterval,
+                PartitionListRequest, PrecreateRequest, ProvisionAction, ProvisionReport,
+                ProvisionRequest, RetentionRequest,
+            },
+        },
+    },
+    state::AppState,
+};
+
+pub fn new() -> Router {
+    Router::new()
+        .route("/migrations", get(migrations))
+        .route("/partitions", get(partitions))
+        .route("/partitions/precreate", post(precreate))
+        .route("/partitions/retention", post(retention))
+        .route("/{backend}/{layout}", post(provision))
+}
+
+// embedded diesel migrations and whether each one has been applied to the postgres database
+pub async fn migrations(
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, Json<AppResponse<Vec<MigrationStatus>>>), AppError> {
+    let _result = tokio::task::block_in_place(|| {
+        let mut connection = _state
+            .diesel_pool_pg
+            .get()
+            .map_err(|error| AppError::Other(format!("get connection failed {}", error)))?;
+        migration::status(&mut connection)
+    })?;
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", Some(_result)))))
+}
+
+// rows per partition of the postgres conditions table
+pub async fn partitions(
+    Query(list_request): Query<PartitionListRequest>,
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, Json<AppResponse<Vec<PartitionInfo>>>), AppError> {
+    let client = _state
+        .pool_pg
+        .get()
+        .await
+        .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+    let _result = partition_manager::partitions(&client, list_request.exact.unwrap_or(false)).await?;
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", Some(_result)))))
+}
+
+pub async fn precreate(
+    Query(precreate_request): Query<PrecreateRequest>,
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, Json<AppResponse<PartitionChange>>), AppError> {
+    let interval = precreate_request.interval.unwrap_or(CONFIG.partition_manager_interval);
+    let mut client = _state
+        .pool_pg
+        .get()
+        .await
+        .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+
+    let created = match (precreate_request.from, precreate_request.to) {
+        (Some(from), Some(to)) => partition_manager::precreate(&mut client, interval, from, to).await?,
+        (None, None) => {
+            let ahead = precreate_request.ahead.unwrap_or(CONFIG.partition_manager_ahead);
+            partition_manager::precreate_ahead(&mut client, interval, ahead).await?
+        }
+        _ => return Err(AppError::invalid_request("to", "from and to must be set together")),
+    };
+
+    let _result = PartitionChange {
+        interval,
+        partitions: created.into_iter().map(|partition| partition.name).collect(),
+    };
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", Some(_result)))))
+}
+
+pub async fn retention(
+    Query(retention_request): Query<RetentionRequest>,
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, Json<AppResponse<PartitionChange>>), AppError> {
+    retention_request.validate().map_err(AppError::InvalidRequest)?;
+
+    let interval = retention_request.interval.unwrap_or(CONFIG.partition_manager_interval);
+    let mode = retention_request.mode.unwrap_or_default();
+    let mut client = _state
+        .pool_pg
+        .get()
+        .await
+        .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+
+    let removed =
+        partition_manager::apply_retention(&mut client, interval, retention_request.keep, mode).await?;
+
+    let _result = PartitionChange { interval, partitions: removed };
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", Some(_result)))))
+}
+
+pub async fn provision(
+    Path((backend, layout)): Path<(Backend, Layout)>,
+    Query(provision_request): Query<ProvisionRequest>,
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, Json<AppResponse<ProvisionReport>>), AppError> {
+    let action = provision_request.action.unwrap_or_default();
+
+    let report = match backend {
+        Backend::Postgres => {
+            // README defaults, minute partitions and day-sized hypertable chunks
+            let interval = provision_request.interval.unwrap_or(match layout {
+                Layout::Hypertable => PartitionInterval::Day,
+                _ => PartitionInterval::Minute,
+            });
+            let partitions = match layout {
+                Layout::Partitioned => {
+                    let from = provision_request
+                        .from
+                        .unwrap_or_else(|| chrono::Utc::now().naive_utc());
+                    let to = provision_request
+                        .to
+                        .unwrap_or_else(|| intervals_after(interval, from, 10));
+                    interval.ranges(ddl::POSTGRES_TABLE, from, to, ddl::POSTGRES_MAX_PARTITIONS)?
+                }
+                _ => Vec::new(),
+            };
+
+            let mut statements = Vec::new();
+            if action != ProvisionAction::Create {
+                statements.extend(ddl::postgres_drop());
+            }
+            if action != ProvisionAction::Drop {
+                statements.extend(ddl::postgres_create(layout, interval, &partitions));
+            }
+
+            let mut client = _state
+                .pool_pg
+                .get()
+                .await
+                .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+            repository::execute_postgres(&mut client, &statements).await?;
+
+            ProvisionReport {
+                backend,
+                layout,
+                action,
+                table: ddl::POSTGRES_TABLE.to_string(),
+                partitions: partitions.len(),
+                statements,
+            }
+        }
+        Backend::SqlServer => {
+            if layout == Layout::Hypertable {
+                return Err(AppError::invalid_request("layout", "hypertable is a postgres only layout"));
+            }
+
+            // README defaults, yearly partitions with boundaries 2020-01-01 to 2025-01-01
+            let interval = provision_request.interval.unwrap_or(PartitionInterval::Year);
+            let boundaries: Vec<NaiveDateTime> = match layout {
+                Layout::Partitioned => {
+                    let from = provision_request.from.unwrap_or(
+                        NaiveDate::from_ymd_opt(2020, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap(),
+                    );
+                    let to = provision_request.to.unwrap_or(
+                        NaiveDate::from_ymd_opt(2026, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap(),
+                    );
+                    interval
+                        .ranges("", from, to, ddl::SQL_SERVER_MAX_BOUNDARIES)?
+                        .into_iter()
+                        .map(|range| range.from)
+                        .collect()
+                }
+                _ => Vec::new(),
+            };
+
+            let target = CONFIG.tiberius_conditions_target();
+            let mut statements = Vec::new();
+            if action != ProvisionAction::Create {
+                statements.extend(ddl::sql_server_drop(&target, layout, interval));
+            }
+            if action != ProvisionAction::Drop {
+                statements.extend(ddl::sql_server_create(&target, layout, interval, &boundaries));
+            }
+
+            let mut client = _state
+                .pool_tiberius
+                .get()
+                .await
+                .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+            repository::execute_sql_server(&mut client, &target, &statements).await?;
+
+            ProvisionReport {
+                backend,
+                layout,
+                action,
+                table: target.qualified(),
+                partitions: if boundaries.is_empty() { 0 } else { boundaries.len() + 1 },
+                statements,
+            }
+        }
+    };
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", Some(report)))))
+}
+
+fn intervals_after(interval: PartitionInterval, from: NaiveDateTime, count: usize) -> NaiveDateTime {
+    (0..count).fold(interval.truncate(from), |datetime, _| interval.next(datetime))
+}
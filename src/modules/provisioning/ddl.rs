@@ -0,0 +1,162 @@
+use chrono::NaiveDateTime;
+
+use crate::{
+    config::database::TableTarget,
+    modules::provisioning::schema::{Layout, PartitionInterval, PartitionRange},
+};
+
+// the postgres modules all read and write this table
+pub const POSTGRES_TABLE: &str = "conditions";
+pub const POSTGRES_DEFAULT_PARTITION: &str = "conditions_default";
+pub const POSTGRES_MAX_PARTITIONS: usize = 10000;
+// sql server allows 15000 partitions, one more than the boundaries
+pub const SQL_SERVER_MAX_BOUNDARIES: usize = 14999;
+
+// postgres
+
+pub fn postgres_create(
+    layout: Layout,
+    interval: PartitionInterval,
+    partitions: &[PartitionRange],
+) -> Vec<String> {
+    let partition_clause = match layout {
+        Layout::Partitioned => " PARTITION BY RANGE (created_on)",
+        _ => "",
+    };
+    let mut statements = vec![format!(
+        "CREATE TABLE {} (
+  id varchar(64) NOT NULL,
+  created_on timestamptz NOT NULL,
+  location TEXT NOT NULL,
+  temperature DOUBLE PRECISION NULL,
+  humidity DOUBLE PRECISION NULL,
+  PRIMARY KEY (id, created_on)
+){}",
+        POSTGRES_TABLE, partition_clause
+    )];
+
+    match layout {
+        Layout::Plain => {}
+        Layout::Indexed => statements.push(postgres_index()),
+        Layout::Hypertable => {
+            statements.push("CREATE EXTENSION IF NOT EXISTS timescaledb".to_string());
+            statements.push(format!(
+                "SELECT create_hypertable('{}', by_range('created_on', INTERVAL '{}'))",
+                POSTGRES_TABLE,
+                interval.postgres_interval()
+            ));
+        }
+        Layout::Partitioned => {
+            statements.push(postgres_index());
+            statements.extend(partitions.iter().map(postgres_partition));
+            statements.push(format!(
+                "CREATE TABLE {} PARTITION OF {} DEFAULT",
+                POSTGRES_DEFAULT_PARTITION, POSTGRES_TABLE
+            ));
+        }
+    }
+    statements
+}
+
+// partitions, chunks and the default partition go with the table
+pub fn postgres_drop() -> Vec<String> {
+    vec![format!("DROP TABLE IF EXISTS {} CASCADE", POSTGRES_TABLE)]
+}
+
+pub fn postgres_partition(partition: &PartitionRange) -> String {
+    format!(
+        "CREATE TABLE IF NOT EXISTS {} PARTITION OF {} FOR VALUES FROM ('{}') TO ('{}')",
+        partition.name,
+        POSTGRES_TABLE,
+        postgres_timestamp(partition.from),
+        postgres_timestamp(partition.to)
+    )
+}
+
+pub fn postgres_timestamp(datetime: NaiveDateTime) -> String {
+    format!("{}+00", datetime.format("%Y-%m-%d %H:%M:%S"))
+}
+
+fn postgres_index() -> String {
+    format!(
+        "CREATE INDEX idx_conditions ON {} (id, created_on, location, temperature, humidity)",
+        POSTGRES_TABLE
+    )
+}
+
+// sql server, every statement runs inside the target's database
+
+pub fn sql_server_create(
+    target: &TableTarget,
+    layout: Layout,
+    interval: PartitionInterval,
+    boundaries: &[NaiveDateTime],
+) -> Vec<String> {
+    let table = target.qualified();
+    let placement = match layout {
+        Layout::Partitioned => format!("\nON {} (created_on)", sql_server_partition_scheme(interval)),
+        _ => String::new(),
+    };
+
+    let mut statements = Vec::new();
+    if layout == Layout::Partitioned {
+        let values: Vec<String> = boundaries
+            .iter()
+            .map(|boundary| format!("'{}'", boundary.format("%Y-%m-%dT%H:%M:%S")))
+            .collect();
+        statements.push(format!(
+            "CREATE PARTITION FUNCTION {} (datetime2) AS RANGE RIGHT FOR VALUES ({})",
+            sql_server_partition_function(interval),
+            values.join(", ")
+        ));
+        statements.push(format!(
+            "CREATE PARTITION SCHEME {} AS PARTITION {} ALL TO ('PRIMARY')",
+            sql_server_partition_scheme(interval),
+            sql_server_partition_function(interval)
+        ));
+    }
+
+    statements.push(format!(
+        "CREATE TABLE {} (
+    id VARCHAR(64) NOT NULL,
+    created_on DATETIME2 NOT NULL,
+    location NVARCHAR(MAX) NOT NULL,
+    temperature FLOAT NULL,
+    humidity FLOAT NULL,
+    CONSTRAINT PK_{} PRIMARY KEY CLUSTERED (id, created_on)
+){}",
+        table, target.table, placement
+    ));
+
+    // NVARCHAR(MAX) cannot be an index key, so location is included instead
+    if layout == Layout::Indexed || layout == Layout::Partitioned {
+        statements.push(format!(
+            "CREATE NONCLUSTERED INDEX idx_{} ON {} (id, created_on, temperature, humidity) INCLUDE (location)",
+            target.table, table
+        ));
+    }
+    statements
+}
+
+pub fn sql_server_drop(target: &TableTarget, layout: Layout, interval: PartitionInterval) -> Vec<String> {
+    let mut statements = vec![format!("DROP TABLE IF EXISTS {}", target.qualified())];
+    if layout == Layout::Partitioned {
+        statements.push(format!(
+            "IF EXISTS (SELECT 1 FROM sys.partition_schemes WHERE name = '{0}') DROP PARTITION SCHEME {0}",
+            sql_server_partition_scheme(interval)
+        ));
+        statements.push(format!(
+            "IF EXISTS (SELECT 1 FROM sys.partition_functions WHERE name = '{0}') DROP PARTITION FUNCTION {0}",
+            sql_server_partition_function(interval)
+        ));
+    }
+    statements
+}
+
+pub fn sql_server_partition_function(interval: PartitionInterval) -> String {
+    format!("PF_conditions_{}", interval.adjective())
+}
+
+pub fn sql_server_partition_scheme(interval: PartitionInterval) -> String {
+    format!("PS_conditions_{}", interval.adjective())
+}
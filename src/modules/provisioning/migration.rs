@@ -0,0 +1,38 @@
+use diesel::{PgConnection, migration::MigrationSource, pg::Pg};
+use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
+
+use crate::{dto::app_error::AppError, modules::provisioning::schema::MigrationStatus};
+
+// compiled in from migrations/, a fresh database needs nothing but DATABASE_RUN_MIGRATIONS=true
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+
+// returns the versions that were applied by this call
+pub fn run_pending(connection: &mut PgConnection) -> Result<Vec<String>, AppError> {
+    let applied = connection
+        .run_pending_migrations(MIGRATIONS)
+        .map_err(|error| AppError::Other(format!("migration failed: {}", error)))?;
+    Ok(applied.iter().map(ToString::to_string).collect())
+}
+
+pub fn status(connection: &mut PgConnection) -> Result<Vec<MigrationStatus>, AppError> {
+    let applied: Vec<String> = connection
+        .applied_migrations()
+        .map_err(|error| AppError::Other(format!("read applied migrations failed: {}", error)))?
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    let migrations = MigrationSource::<Pg>::migrations(&MIGRATIONS)
+        .map_err(|error| AppError::Other(format!("read embedded migrations failed: {}", error)))?;
+
+    Ok(migrations
+        .iter()
+        .map(|migration| {
+            let version = migration.name().version().to_string();
+            MigrationStatus {
+                applied: applied.contains(&version),
+                name: migration.name().to_string(),
+                version,
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,6 @@
+pub mod schema;
+pub mod ddl;
+pub mod repository;
+pub mod migration;
+pub mod partition_manager;
+pub mod controller;
\ No newline at end of file
@@ -0,0 +1,121 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::NaiveDateTime;
+
+use crate::{
+    config::environment::CONFIG,
+    dto::app_error::AppError,
+    modules::provisioning::{
+        ddl, repository,
+        schema::{PartitionInfo, PartitionInterval, PartitionRange, RetentionMode},
+    },
+    state::AppState,
+};
+
+// creates every missing partition of [from, to), existing ones are left alone
+pub async fn precreate(
+    client: &mut tokio_postgres::Client,
+    interval: PartitionInterval,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+) -> Result<Vec<PartitionRange>, AppError> {
+    let existing: Vec<String> = repository::find_partitions(client)
+        .await?
+        .into_iter()
+        .map(|partition| partition.name)
+        .collect();
+    let missing: Vec<PartitionRange> = interval
+        .ranges(ddl::POSTGRES_TABLE, from, to, ddl::POSTGRES_MAX_PARTITIONS)?
+        .into_iter()
+        .filter(|partition| !existing.contains(&partition.name))
+        .collect();
+
+    // fails if conditions_default already holds rows of a new range, move them out first
+    let statements: Vec<String> = missing.iter().map(ddl::postgres_partition).collect();
+    repository::execute_postgres(client, &statements).await?;
+    Ok(missing)
+}
+
+// the current interval and the `ahead` intervals after it
+pub async fn precreate_ahead(
+    client: &mut tokio_postgres::Client,
+    interval: PartitionInterval,
+    ahead: usize,
+) -> Result<Vec<PartitionRange>, AppError> {
+    let from = interval.truncate(chrono::Utc::now().naive_utc());
+    let to = (0..=ahead).fold(from, |datetime, _| interval.next(datetime));
+    precreate(client, interval, from, to).await
+}
+
+// removes partitions that end before the last `keep` intervals, the current one included
+pub async fn apply_retention(
+    client: &mut tokio_postgres::Client,
+    interval: PartitionInterval,
+    keep: usize,
+    mode: RetentionMode,
+) -> Result<Vec<String>, AppError> {
+    let current = interval.truncate(chrono::Utc::now().naive_utc());
+    let cutoff = (1..keep).fold(current, |datetime, _| interval.previous(datetime));
+
+    let expired: Vec<String> = repository::find_partitions(client)
+        .await?
+        .into_iter()
+        .filter(|partition| partition.to.is_some_and(|to| to <= cutoff))
+        .map(|partition| partition.name)
+        .collect();
+
+    let mut statements = Vec::new();
+    for name in &expired {
+        statements.push(format!("ALTER TABLE {} DETACH PARTITION {}", ddl::POSTGRES_TABLE, name));
+        if mode == RetentionMode::Drop {
+            statements.push(format!("DROP TABLE {}", name));
+        }
+    }
+    repository::execute_postgres(client, &statements).await?;
+    Ok(expired)
+}
+
+pub async fn partitions(client: &tokio_postgres::Client, exact: bool) -> Result<Vec<PartitionInfo>, AppError> {
+    let mut partitions = repository::find_partitions(client).await?;
+    if exact {
+        for partition in &mut partitions {
+            partition.rows = repository::count_rows(client, &partition.name).await?;
+        }
+    }
+    Ok(partitions)
+}
+
+// keeps partitions ahead of the generator's created_on (now) and applies the retention policy,
+// started from main when PARTITION_MANAGER_ENABLED is set
+pub fn spawn(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(CONFIG.partition_manager_check_seconds.max(1)));
+        loop {
+            ticker.tick().await;
+            if let Err(error) = maintain(&state).await {
+                tracing::error!(?error, "partition manager failed");
+            }
+        }
+    });
+}
+
+async fn maintain(state: &AppState) -> Result<(), AppError> {
+    let mut client = state
+        .pool_pg
+        .get()
+        .await
+        .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+
+    let interval = CONFIG.partition_manager_interval;
+    precreate_ahead(&mut client, interval, CONFIG.partition_manager_ahead).await?;
+    if CONFIG.partition_manager_keep > 0 {
+        apply_retention(
+            &mut client,
+            interval,
+            CONFIG.partition_manager_keep,
+            CONFIG.partition_manager_retention_mode,
+        )
+        .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use chrono::{DateTime, Utc};
+
+use crate::{
+    config::database::TableTarget,
+    dto::app_error::AppError,
+    modules::provisioning::{ddl, schema::PartitionInfo},
+};
+
+// postgres ddl is transactional, a failed layout leaves nothing behind
+pub async fn execute_postgres(
+    client: &mut tokio_postgres::Client,
+    statements: &[String],
+) -> Result<(), AppError> {
+    let transaction = client
+        .transaction()
+        .await
+        .map_err(|error| AppError::Other(format!("transaction failed: {}", error)))?;
+    for statement in statements {
+        transaction
+            .batch_execute(statement)
+            .await
+            .map_err(|error| AppError::Other(format!("{} failed: {}", statement, error)))?;
+    }
+    transaction
+        .commit()
+        .await
+        .map_err(|error| AppError::Other(format!("commit failed: {}", error)))?;
+    Ok(())
+}
+
+// run through the target database's sp_executesql, partition functions and schemes belong to
+// a database and the pooled connection must not be switched with USE
+pub async fn execute_sql_server(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    target: &TableTarget,
+    statements: &[String],
+) -> Result<(), AppError> {
+    let procedure = format!("EXEC {}.sys.sp_executesql @P1", target.quoted_database());
+    for statement in statements {
+        client
+            .execute(procedure.as_str(), &[statement])
+            .await
+            .map_err(|error| AppError::Other(format!("{} failed: {}", statement, error)))?;
+    }
+    Ok(())
+}
+
+// partitions of the postgres conditions table with their bounds, the default partition has none
+pub async fn find_partitions(client: &tokio_postgres::Client) -> Result<Vec<PartitionInfo>, AppError> {
+    let statement = format!(
+        "SELECT c.relname::text AS name,
+            (regexp_match(pg_get_expr(c.relpartbound, c.oid), 'FROM \\(''([^'']+)''\\)'))[1]::timestamptz AS range_from,
+            (regexp_match(pg_get_expr(c.relpartbound, c.oid), 'TO \\(''([^'']+)''\\)'))[1]::timestamptz AS range_to,
+            coalesce(s.n_live_tup, 0) AS rows
+        FROM pg_inherits i
+        JOIN pg_class c ON c.oid = i.inhrelid
+        LEFT JOIN pg_stat_user_tables s ON s.relid = c.oid
+        WHERE i.inhparent = '{}'::regclass
+        ORDER BY range_from NULLS LAST, name",
+        ddl::POSTGRES_TABLE
+    );
+    let rows = client
+        .query(&statement, &[])
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let from: Option<DateTime<Utc>> = row.get("range_from");
+            let to: Option<DateTime<Utc>> = row.get("range_to");
+            PartitionInfo {
+                name: row.get("name"),
+                from: from.map(|from| from.naive_utc()),
+                to: to.map(|to| to.naive_utc()),
+                rows: row.get("rows"),
+            }
+        })
+        .collect())
+}
+
+pub async fn count_rows(client: &tokio_postgres::Client, table: &str) -> Result<i64, AppError> {
+    let statement = format!("SELECT count(*) AS rows FROM ONLY {}", table);
+    let row = client
+        .query_one(&statement, &[])
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+    Ok(row.get("rows"))
+}
@@ -0,0 +1,219 @@
+use chrono::{Datelike, Months, NaiveDateTime, TimeDelta, Timelike};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{dto::app_error::AppError, modules::dynamic_table::schema::Backend};
+
+// the table layouts documented in the README, not every layout exists on both backends
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Layout {
+    Plain,
+    // plain plus the covering idx_conditions index
+    Indexed,
+    // timescaledb, postgres only
+    Hypertable,
+    // range partitioned on created_on, one partition per interval of the requested range
+    Partitioned,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ProvisionAction {
+    #[default]
+    Create,
+    Drop,
+    // drop then create
+    Reset,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PartitionInterval {
+    Minute,
+    Hour,
+    Day,
+    Month,
+    Year,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProvisionRequest {
+    pub action: Option<ProvisionAction>,
+    // partition range [from, to), hypertable chunk size and partition size come from interval
+    pub from: Option<NaiveDateTime>,
+    pub to: Option<NaiveDateTime>,
+    pub interval: Option<PartitionInterval>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProvisionReport {
+    pub backend: Backend,
+    pub layout: Layout,
+    pub action: ProvisionAction,
+    pub table: String,
+    pub partitions: usize,
+    // every statement that was executed, in order
+    pub statements: Vec<String>,
+}
+
+// one range partition, bounds are utc
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartitionRange {
+    pub name: String,
+    pub from: NaiveDateTime,
+    pub to: NaiveDateTime,
+}
+
+impl PartitionInterval {
+    // start of the interval `datetime` falls in
+    pub fn truncate(self, datetime: NaiveDateTime) -> NaiveDateTime {
+        let date = datetime.date();
+        let time = datetime.time();
+        match self {
+            PartitionInterval::Minute => date.and_hms_opt(time.hour(), time.minute(), 0),
+            PartitionInterval::Hour => date.and_hms_opt(time.hour(), 0, 0),
+            PartitionInterval::Day => date.and_hms_opt(0, 0, 0),
+            PartitionInterval::Month => date.with_day(1).and_then(|date| date.and_hms_opt(0, 0, 0)),
+            PartitionInterval::Year => date
+                .with_day(1)
+                .and_then(|date| date.with_month(1))
+                .and_then(|date| date.and_hms_opt(0, 0, 0)),
+        }
+        .unwrap_or(datetime)
+    }
+
+    pub fn next(self, datetime: NaiveDateTime) -> NaiveDateTime {
+        match self {
+            PartitionInterval::Minute => datetime + TimeDelta::minutes(1),
+            PartitionInterval::Hour => datetime + TimeDelta::hours(1),
+            PartitionInterval::Day => datetime + TimeDelta::days(1),
+            PartitionInterval::Month => datetime + Months::new(1),
+            PartitionInterval::Year => datetime + Months::new(12),
+        }
+    }
+
+    pub fn previous(self, datetime: NaiveDateTime) -> NaiveDateTime {
+        match self {
+            PartitionInterval::Minute => datetime - TimeDelta::minutes(1),
+            PartitionInterval::Hour => datetime - TimeDelta::hours(1),
+            PartitionInterval::Day => datetime - TimeDelta::days(1),
+            PartitionInterval::Month => datetime - Months::new(1),
+            PartitionInterval::Year => datetime - Months::new(12),
+        }
+    }
+
+    // partition names sort by time and are the same for the same interval across runs
+    pub fn suffix(self, datetime: NaiveDateTime) -> String {
+        let format = match self {
+            PartitionInterval::Minute => "%Y%m%d_%H%M",
+            PartitionInterval::Hour => "%Y%m%d_%H",
+            PartitionInterval::Day => "%Y%m%d",
+            PartitionInterval::Month => "%Y%m",
+            PartitionInterval::Year => "%Y",
+        };
+        datetime.format(format).to_string()
+    }
+
+    pub fn postgres_interval(self) -> &'static str {
+        match self {
+            PartitionInterval::Minute => "1 minute",
+            PartitionInterval::Hour => "1 hour",
+            PartitionInterval::Day => "1 day",
+            PartitionInterval::Month => "1 month",
+            PartitionInterval::Year => "1 year",
+        }
+    }
+
+    // as in PF_conditions_yearly
+    pub fn adjective(self) -> &'static str {
+        match self {
+            PartitionInterval::Minute => "minutely",
+            PartitionInterval::Hour => "hourly",
+            PartitionInterval::Day => "daily",
+            PartitionInterval::Month => "monthly",
+            PartitionInterval::Year => "yearly",
+        }
+    }
+
+    // every interval touching [from, to), capped so a typo cannot create millions of tables
+    pub fn ranges(
+        self,
+        table: &str,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        max: usize,
+    ) -> Result<Vec<PartitionRange>, AppError> {
+        let mut ranges = Vec::new();
+        let mut start = self.truncate(from);
+        while start < to {
+            if ranges.len() == max {
+                return Err(AppError::invalid_request("to", format!("more than {} partitions requested", max)));
+            }
+            let end = self.next(start);
+            ranges.push(PartitionRange {
+                name: format!("{}_p{}", table, self.suffix(start)),
+                from: start,
+                to: end,
+            });
+            start = end;
+        }
+        Ok(ranges)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct MigrationStatus {
+    pub version: String,
+    pub name: String,
+    pub applied: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RetentionMode {
+    // the partition becomes a standalone table, its rows are kept but no longer queried
+    #[default]
+    Detach,
+    Drop,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PrecreateRequest {
+    pub interval: Option<PartitionInterval>,
+    // partitions from the current interval up to `ahead` intervals later, unless from/to are set
+    pub ahead: Option<usize>,
+    pub from: Option<NaiveDateTime>,
+    pub to: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RetentionRequest {
+    pub interval: Option<PartitionInterval>,
+    // intervals kept counting the current one, partitions ending before them are removed, at
+    // least 1 since PARTITION_MANAGER_KEEP=0 means no retention at all
+    #[validate(range(min = 1))]
+    pub keep: usize,
+    pub mode: Option<RetentionMode>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PartitionListRequest {
+    // count(*) every partition instead of using the n_live_tup estimate
+    pub exact: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PartitionInfo {
+    pub name: String,
+    // utc, both empty for the default partition
+    pub from: Option<NaiveDateTime>,
+    pub to: Option<NaiveDateTime>,
+    pub rows: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PartitionChange {
+    pub interval: PartitionInterval,
+    pub partitions: Vec<String>,
+}
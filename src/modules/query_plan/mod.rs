@@ -0,0 +1,3 @@
+pub mod schema;
+pub mod summary;
+pub mod repository;
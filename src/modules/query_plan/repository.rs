@@ -0,0 +1,111 @@
+use futures_util::StreamExt;
+use tiberius::QueryItem;
+use tokio_postgres::SimpleQueryMessage;
+
+use crate::{
+    config::database::TableTarget,
+    dto::app_error::AppError,
+    modules::{
+        dynamic_table::schema::Backend,
+        query_plan::{schema::QueryPlan, summary},
+    },
+};
+
+// EXPLAIN ANALYZE runs the statement, the rows are discarded by postgres
+pub async fn explain_postgres(
+    client: &tokio_postgres::Client,
+    statement: &str,
+    table: &str,
+) -> Result<QueryPlan, AppError> {
+    // simple_query returns every column as text, json included
+    let explain = format!("EXPLAIN (ANALYZE, BUFFERS, FORMAT JSON) {}", statement);
+    let messages = client
+        .simple_query(&explain)
+        .await
+        .map_err(|error| AppError::Other(format!("explain failed: {}", error)))?;
+    let text = messages
+        .iter()
+        .find_map(|message| match message {
+            SimpleQueryMessage::Row(row) => row.get(0),
+            _ => None,
+        })
+        .ok_or(AppError::Other("explain returned no plan".to_string()))?;
+    let plan: serde_json::Value =
+        serde_json::from_str(text).map_err(|error| AppError::Other(format!("invalid plan: {}", error)))?;
+
+    let rows = client
+        .query(
+            "SELECT c.relname::text AS name FROM pg_inherits i JOIN pg_class c ON c.oid = i.inhrelid
+            WHERE i.inhparent = $1::text::regclass",
+            &[&table],
+        )
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+    let partitions: Vec<String> = rows.iter().map(|row| row.get("name")).collect();
+
+    Ok(QueryPlan {
+        backend: Backend::Postgres,
+        statement: statement.to_string(),
+        summary: summary::postgres(&plan, &partitions),
+        plan,
+    })
+}
+
+// the statement runs with SET STATISTICS XML ON, its rows are read and dropped and the actual
+// plan comes back as one more result set. `params` are the statement's @P1.. values, a
+// parameterized statement runs through sp_executesql like the benchmark's own queries
+pub async fn explain_sql_server(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    statement: &str,
+    params: &[&dyn tiberius::ToSql],
+    target: &TableTarget,
+) -> Result<QueryPlan, AppError> {
+    let batch = format!("SET STATISTICS XML ON;\n{};\nSET STATISTICS XML OFF;", statement);
+    let stream = if params.is_empty() {
+        client.simple_query(batch).await
+    } else {
+        client.query(batch, params).await
+    };
+    let mut stream = stream.map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+
+    let mut showplan: Option<String> = None;
+    while let Some(item) = stream
+        .next()
+        .await
+        .transpose()
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?
+    {
+        if let QueryItem::Row(row) = item {
+            let is_showplan = row
+                .columns()
+                .first()
+                .is_some_and(|column| column.name().contains("Showplan"));
+            if is_showplan {
+                showplan = row.get::<&str, _>(0).map(str::to_string);
+            }
+        }
+    }
+    drop(stream);
+    let showplan = showplan.ok_or(AppError::Other("sql server returned no plan".to_string()))?;
+
+    let statement_partitions = format!(
+        "SELECT COUNT(*) AS partitions FROM {}.sys.partitions WHERE object_id = OBJECT_ID(@P1) AND index_id IN (0, 1)",
+        target.quoted_database()
+    );
+    let partitions: i32 = client
+        .query(statement_partitions, &[&target.qualified()])
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?
+        .into_row()
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?
+        .and_then(|row| row.get("partitions"))
+        .unwrap_or(0);
+
+    Ok(QueryPlan {
+        backend: Backend::SqlServer,
+        statement: statement.to_string(),
+        summary: summary::sql_server(&showplan, partitions.max(0) as usize),
+        plan: serde_json::Value::String(showplan),
+    })
+}
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+use crate::modules::dynamic_table::schema::Backend;
+
+#[derive(Debug, Deserialize)]
+pub struct PlanRequest {
+    // run the benchmarked statement once more with the plan captured, after the timed runs
+    pub plan: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryPlan {
+    pub backend: Backend,
+    pub statement: String,
+    pub summary: PlanSummary,
+    // EXPLAIN json on postgres, the showplan xml as a string on sql server
+    pub plan: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct PlanSummary {
+    // operators that read a table or an index, in plan order
+    pub scans: Vec<PlanScan>,
+    // postgres Seq Scan, sql server Table Scan and Clustered Index Scan
+    pub seq_scans: usize,
+    // postgres index and bitmap index scans, sql server index scans and seeks
+    pub index_scans: usize,
+    // set when the table is partitioned
+    pub partitions_total: Option<usize>,
+    pub partitions_scanned: Option<usize>,
+    pub partitions_pruned: Option<usize>,
+    pub estimated_rows: f64,
+    pub actual_rows: Option<f64>,
+    pub execution_ms: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PlanScan {
+    pub operator: String,
+    pub relation: Option<String>,
+    pub index: Option<String>,
+    pub estimated_rows: f64,
+    pub actual_rows: Option<f64>,
+    // sql server only, partitions the operator touched
+    pub partitions: Option<usize>,
+}
@@ -0,0 +1,326 @@
+use serde_json::Value;
+
+use crate::modules::query_plan::schema::{PlanScan, PlanSummary};
+
+// postgres, EXPLAIN (ANALYZE, BUFFERS, FORMAT JSON) output and the table's partition names
+
+pub fn postgres(explain: &Value, partitions: &[String]) -> PlanSummary {
+    let mut summary = PlanSummary::default();
+    let Some(root) = explain.get(0) else {
+        return summary;
+    };
+    summary.execution_ms = root.get("Execution Time").and_then(Value::as_f64);
+
+    if let Some(plan) = root.get("Plan") {
+        summary.estimated_rows = number(plan, "Plan Rows").unwrap_or(0.0);
+        summary.actual_rows = postgres_actual_rows(plan);
+        postgres_visit(plan, &mut summary);
+    }
+
+    if !partitions.is_empty() {
+        let mut scanned: Vec<&String> = summary
+            .scans
+            .iter()
+            .filter_map(|scan| scan.relation.as_ref())
+            .filter(|relation| partitions.contains(relation))
+            .collect();
+        scanned.sort();
+        scanned.dedup();
+        summary.partitions_total = Some(partitions.len());
+        summary.partitions_scanned = Some(scanned.len());
+        summary.partitions_pruned = Some(partitions.len() - scanned.len());
+    }
+    summary
+}
+
+fn postgres_visit(node: &Value, summary: &mut PlanSummary) {
+    let operator = node.get("Node Type").and_then(Value::as_str).unwrap_or_default();
+    let relation = node.get("Relation Name").and_then(Value::as_str);
+    let index = node.get("Index Name").and_then(Value::as_str);
+    if relation.is_some() || index.is_some() {
+        if operator == "Seq Scan" {
+            summary.seq_scans += 1;
+        } else if operator.contains("Index") {
+            summary.index_scans += 1;
+        }
+        summary.scans.push(PlanScan {
+            operator: operator.to_string(),
+            relation: relation.map(str::to_string),
+            index: index.map(str::to_string),
+            estimated_rows: number(node, "Plan Rows").unwrap_or(0.0),
+            actual_rows: postgres_actual_rows(node),
+            partitions: None,
+        });
+    }
+
+    for child in node.get("Plans").and_then(Value::as_array).into_iter().flatten() {
+        postgres_visit(child, summary);
+    }
+}
+
+// Actual Rows is per loop
+fn postgres_actual_rows(node: &Value) -> Option<f64> {
+    let rows = number(node, "Actual Rows")?;
+    Some(rows * number(node, "Actual Loops").unwrap_or(1.0))
+}
+
+fn number(node: &Value, key: &str) -> Option<f64> {
+    node.get(key).and_then(Value::as_f64)
+}
+
+// sql server, the SET STATISTICS XML showplan and the partition count of the table
+
+pub fn sql_server(showplan: &str, partitions_total: usize) -> PlanSummary {
+    let mut summary = PlanSummary::default();
+    // every RelOp, counters and objects belong to the closest RelOp before them because
+    // RunTimeInformation and the scanned Object come before the child operators
+    let mut operators: Vec<PlanScan> = Vec::new();
+
+    for (name, attributes) in xml_start_tags(showplan) {
+        let attribute = |key: &str| {
+            attributes
+                .iter()
+                .find(|(attribute, _)| attribute == key)
+                .map(|(_, value)| value.as_str())
+        };
+        match name.as_str() {
+            "RelOp" => operators.push(PlanScan {
+                operator: attribute("PhysicalOp").unwrap_or_default().to_string(),
+                relation: None,
+                index: None,
+                estimated_rows: attribute("EstimateRows").and_then(|rows| rows.parse().ok()).unwrap_or(0.0),
+                actual_rows: None,
+                partitions: None,
+            }),
+            "RunTimeCountersPerThread" => {
+                if let (Some(operator), Some(rows)) = (
+                    operators.last_mut(),
+                    attribute("ActualRows").and_then(|rows| rows.parse::<f64>().ok()),
+                ) {
+                    operator.actual_rows = Some(operator.actual_rows.unwrap_or(0.0) + rows);
+                }
+            }
+            "Object" => {
+                if let Some(operator) = operators.last_mut()
+                    && operator.relation.is_none()
+                {
+                    operator.relation = attribute("Table").map(unbracket);
+                    operator.index = attribute("Index").map(unbracket);
+                }
+            }
+            "PartitionsAccessed" => {
+                if let (Some(operator), Some(count)) = (
+                    operators.last_mut(),
+                    attribute("PartitionCount").and_then(|count| count.parse::<usize>().ok()),
+                ) {
+                    operator.partitions = Some(operator.partitions.unwrap_or(0) + count);
+                }
+            }
+            "QueryTimeStats" => {
+                summary.execution_ms = attribute("ElapsedTime").and_then(|elapsed| elapsed.parse().ok());
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(root) = operators.first() {
+        summary.estimated_rows = root.estimated_rows;
+        summary.actual_rows = root.actual_rows;
+    }
+    for operator in operators {
+        if operator.relation.is_none() {
+            continue;
+        }
+        match operator.operator.as_str() {
+            "Table Scan" | "Clustered Index Scan" => summary.seq_scans += 1,
+            "Index Scan" | "Index Seek" | "Clustered Index Seek" => summary.index_scans += 1,
+            _ => {}
+        }
+        summary.scans.push(operator);
+    }
+
+    if partitions_total > 1 {
+        let scanned = summary
+            .scans
+            .iter()
+            .map(|scan| scan.partitions.unwrap_or(partitions_total))
+            .max()
+            .unwrap_or(0)
+            .min(partitions_total);
+        summary.partitions_total = Some(partitions_total);
+        summary.partitions_scanned = Some(scanned);
+        summary.partitions_pruned = Some(partitions_total - scanned);
+    }
+    summary
+}
+
+fn unbracket(name: &str) -> String {
+    name.trim_start_matches('[').trim_end_matches(']').to_string()
+}
+
+// start and empty element tags with their attributes, in document order, enough for showplan xml
+fn xml_start_tags(xml: &str) -> Vec<(String, Vec<(String, String)>)> {
+    let mut tags = Vec::new();
+    for fragment in xml.split('<').skip(1) {
+        let Some(end) = fragment.find('>') else { continue };
+        let tag = fragment[..end].trim_end_matches('/');
+        if tag.starts_with('/') || tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+
+        let (name, mut rest) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        let mut attributes = Vec::new();
+        while let Some((key, after)) = rest.split_once("=\"") {
+            let Some((value, remaining)) = after.split_once('"') else { break };
+            attributes.push((key.trim().to_string(), xml_unescape(value)));
+            rest = remaining;
+        }
+        tags.push((name.to_string(), attributes));
+    }
+    tags
+}
+
+fn xml_unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn postgres_counts_scans_and_pruned_partitions() {
+        let explain = json!([{
+            "Plan": {
+                "Node Type": "Append", "Plan Rows": 150, "Actual Rows": 120, "Actual Loops": 1,
+                "Plans": [
+                    {
+                        "Node Type": "Seq Scan", "Relation Name": "conditions_p2023",
+                        "Plan Rows": 100, "Actual Rows": 80, "Actual Loops": 1
+                    },
+                    {
+                        "Node Type": "Nested Loop", "Plan Rows": 50, "Actual Rows": 40, "Actual Loops": 1,
+                        "Plans": [{
+                            "Node Type": "Index Scan", "Relation Name": "conditions_p2024",
+                            "Index Name": "conditions_p2024_pkey",
+                            "Plan Rows": 5, "Actual Rows": 4, "Actual Loops": 10
+                        }]
+                    }
+                ]
+            },
+            "Execution Time": 3.5
+        }]);
+        let partitions = ["conditions_p2022", "conditions_p2023", "conditions_p2024"].map(String::from);
+
+        let summary = postgres(&explain, &partitions);
+        assert_eq!(summary.seq_scans, 1);
+        assert_eq!(summary.index_scans, 1);
+        assert_eq!(summary.scans.len(), 2);
+        assert_eq!(summary.scans[1].index.as_deref(), Some("conditions_p2024_pkey"));
+        // per loop rows times the loops
+        assert_eq!(summary.scans[1].actual_rows, Some(40.0));
+        assert_eq!(summary.estimated_rows, 150.0);
+        assert_eq!(summary.actual_rows, Some(120.0));
+        assert_eq!(summary.execution_ms, Some(3.5));
+        assert_eq!(summary.partitions_total, Some(3));
+        assert_eq!(summary.partitions_scanned, Some(2));
+        assert_eq!(summary.partitions_pruned, Some(1));
+    }
+
+    #[test]
+    fn postgres_without_partitions() {
+        let explain = json!([{ "Plan": { "Node Type": "Seq Scan", "Relation Name": "conditions", "Plan Rows": 10 } }]);
+        let summary = postgres(&explain, &[]);
+        assert_eq!(summary.seq_scans, 1);
+        assert_eq!(summary.actual_rows, None);
+        assert_eq!(summary.partitions_pruned, None);
+    }
+
+    const SHOWPLAN: &str = r#"<?xml version="1.0" encoding="utf-16"?>
+<ShowPlanXML xmlns="http://schemas.microsoft.com/sqlserver/2004/07/showplan" Version="1.564">
+  <BatchSequence><Batch><Statements>
+    <StmtSimple StatementText="SELECT * FROM conditions WHERE location = &quot;a&quot;">
+      <QueryPlan>
+        <QueryTimeStats CpuTime="9" ElapsedTime="12" />
+        <RelOp NodeId="0" PhysicalOp="Parallelism" LogicalOp="Gather Streams" EstimateRows="150">
+          <RunTimeInformation>
+            <RunTimeCountersPerThread Thread="0" ActualRows="120" />
+          </RunTimeInformation>
+          <Parallelism>
+            <RelOp NodeId="1" PhysicalOp="Clustered Index Scan" LogicalOp="Clustered Index Scan" EstimateRows="100">
+              <RunTimeInformation>
+                <RunTimeCountersPerThread Thread="1" ActualRows="30" />
+                <RunTimeCountersPerThread Thread="2" ActualRows="50" />
+              </RunTimeInformation>
+              <RunTimePartitionSummary>
+                <PartitionsAccessed PartitionCount="2"><PartitionRange Start="2" End="3" /></PartitionsAccessed>
+              </RunTimePartitionSummary>
+              <IndexScan Ordered="false">
+                <Object Database="[tiberius]" Schema="[dbo]" Table="[conditions]" Index="[PK_conditions]" />
+              </IndexScan>
+            </RelOp>
+            <RelOp NodeId="2" PhysicalOp="Index Seek" LogicalOp="Index Seek" EstimateRows="50">
+              <RunTimeInformation>
+                <RunTimeCountersPerThread Thread="1" ActualRows="40" />
+              </RunTimeInformation>
+              <RunTimePartitionSummary>
+                <PartitionsAccessed PartitionCount="1"><PartitionRange Start="3" End="3" /></PartitionsAccessed>
+              </RunTimePartitionSummary>
+              <IndexScan Ordered="true">
+                <Object Database="[tiberius]" Schema="[dbo]" Table="[conditions]" Index="[IX_conditions_location]" />
+              </IndexScan>
+            </RelOp>
+          </Parallelism>
+        </RelOp>
+      </QueryPlan>
+    </StmtSimple>
+  </Statements></Batch></BatchSequence>
+</ShowPlanXML>"#;
+
+    #[test]
+    fn sql_server_counts_scans_and_pruned_partitions() {
+        let summary = sql_server(SHOWPLAN, 5);
+        assert_eq!(summary.seq_scans, 1);
+        assert_eq!(summary.index_scans, 1);
+        // the parallelism operator reads no table
+        assert_eq!(summary.scans.len(), 2);
+        assert_eq!(summary.scans[0].relation.as_deref(), Some("conditions"));
+        assert_eq!(summary.scans[0].index.as_deref(), Some("PK_conditions"));
+        // summed over the threads
+        assert_eq!(summary.scans[0].actual_rows, Some(80.0));
+        assert_eq!(summary.scans[0].partitions, Some(2));
+        assert_eq!(summary.scans[1].index.as_deref(), Some("IX_conditions_location"));
+        assert_eq!(summary.estimated_rows, 150.0);
+        assert_eq!(summary.actual_rows, Some(120.0));
+        assert_eq!(summary.execution_ms, Some(12.0));
+        assert_eq!(summary.partitions_total, Some(5));
+        assert_eq!(summary.partitions_scanned, Some(2));
+        assert_eq!(summary.partitions_pruned, Some(3));
+    }
+
+    #[test]
+    fn sql_server_unpartitioned_table() {
+        let summary = sql_server(SHOWPLAN, 1);
+        assert_eq!(summary.partitions_total, None);
+        assert_eq!(summary.partitions_pruned, None);
+    }
+
+    #[test]
+    fn xml_start_tags_reads_attributes() {
+        let tags = xml_start_tags(r#"<?xml version="1.0"?><!-- c --><A x="1 &amp; 2" y="&lt;b&gt;"><B/></A>"#);
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags[0].0, "A");
+        assert_eq!(
+            tags[0].1,
+            vec![("x".to_string(), "1 & 2".to_string()), ("y".to_string(), "<b>".to_string())]
+        );
+        assert_eq!(tags[1], ("B".to_string(), Vec::new()));
+    }
+}
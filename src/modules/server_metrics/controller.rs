@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, Query},
+    http::StatusCode,
+    routing::get,
+};
+
+use crate::{
+    config::environment::CONFIG,
+    dto::{app_error::AppError, app_response::AppResponse},
+    modules::{
+        dynamic_table::schema::Backend,
+        server_metrics::schema::{ServerMetrics, ServerMetricsRequest},
+        storage::capture,
+    },
+    state::AppState,
+};
+
+pub fn new() -> Router {
+    Router::new().route("/{backend}", get(find_snapshot))
+}
+
+// current counters, two snapshots give the delta of anything run in between
+pub async fn find_snapshot(
+    Path(backend): Path<Backend>,
+    Query(metrics_request): Query<ServerMetricsRequest>,
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, Json<AppResponse<ServerMetrics>>), AppError> {
+    let database = metrics_request
+        .database
+        .unwrap_or(CONFIG.tiberius_conditions_target().database);
+    let _result = capture::collect_server_metrics(&_state, backend, &database).await?;
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", Some(_result)))))
+}
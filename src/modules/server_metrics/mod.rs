@@ -0,0 +1,3 @@
+pub mod schema;
+pub mod repository;
+pub mod controller;
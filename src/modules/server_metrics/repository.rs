@@ -0,0 +1,106 @@
+use std::collections::BTreeMap;
+
+use futures_util::StreamExt;
+use tiberius::QueryItem;
+use tokio_postgres::SimpleQueryMessage;
+
+use crate::{
+    dto::app_error::AppError,
+    modules::{dynamic_table::schema::Backend, server_metrics::schema::ServerMetrics},
+};
+
+// postgres, rows go through to_jsonb so the counters of every server version are picked up,
+// pg_stat_bgwriter lost its checkpoint columns to pg_stat_checkpointer in 17
+pub async fn postgres_snapshot(client: &tokio_postgres::Client) -> Result<ServerMetrics, AppError> {
+    let statement = "SELECT
+            (SELECT to_jsonb(d) FROM pg_stat_database d WHERE d.datname = current_database()) AS database,
+            (SELECT to_jsonb(b) FROM pg_stat_bgwriter b) AS bgwriter,
+            pg_current_wal_lsn() - '0/0'::pg_lsn AS wal_bytes";
+    let messages = client
+        .simple_query(statement)
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+    let row = messages
+        .iter()
+        .find_map(|message| match message {
+            SimpleQueryMessage::Row(row) => Some(row),
+            _ => None,
+        })
+        .ok_or(AppError::Other("server metrics returned no row".to_string()))?;
+
+    let mut counters = BTreeMap::new();
+    for view in ["database", "bgwriter"] {
+        let Some(text) = row.get(view) else { continue };
+        let value: serde_json::Value =
+            serde_json::from_str(text).map_err(|error| AppError::Other(format!("invalid {}: {}", view, error)))?;
+        for (name, value) in value.as_object().into_iter().flatten() {
+            // oids and timestamps are not counters
+            if name == "datid" {
+                continue;
+            }
+            if let Some(number) = value.as_f64() {
+                counters.insert(format!("{}.{}", view, name), number);
+            }
+        }
+    }
+    if let Some(wal_bytes) = row.get("wal_bytes").and_then(|text| text.parse::<f64>().ok()) {
+        counters.insert("wal.bytes".to_string(), wal_bytes);
+    }
+
+    Ok(ServerMetrics {
+        backend: Backend::Postgres,
+        captured_at: chrono::Utc::now().naive_utc(),
+        counters,
+    })
+}
+
+// sql server, server wide waits and the file io of one database, both need VIEW SERVER STATE
+pub async fn sql_server_snapshot(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    database: &str,
+) -> Result<ServerMetrics, AppError> {
+    let statement = "SELECT 'wait.' + wait_type + '.wait_time_ms' AS name, CAST(wait_time_ms AS FLOAT) AS value
+            FROM sys.dm_os_wait_stats WHERE waiting_tasks_count > 0
+        UNION ALL
+        SELECT 'wait.' + wait_type + '.waiting_tasks_count', CAST(waiting_tasks_count AS FLOAT)
+            FROM sys.dm_os_wait_stats WHERE waiting_tasks_count > 0
+        UNION ALL
+        SELECT 'wait.' + wait_type + '.signal_wait_time_ms', CAST(signal_wait_time_ms AS FLOAT)
+            FROM sys.dm_os_wait_stats WHERE waiting_tasks_count > 0
+        UNION ALL
+        SELECT 'io.' + f.name + '.' + counter.name, counter.value
+        FROM sys.dm_io_virtual_file_stats(DB_ID(@P1), NULL) s
+        JOIN sys.master_files f ON f.database_id = s.database_id AND f.file_id = s.file_id
+        CROSS APPLY (VALUES
+            ('num_of_reads', CAST(s.num_of_reads AS FLOAT)),
+            ('num_of_bytes_read', CAST(s.num_of_bytes_read AS FLOAT)),
+            ('io_stall_read_ms', CAST(s.io_stall_read_ms AS FLOAT)),
+            ('num_of_writes', CAST(s.num_of_writes AS FLOAT)),
+            ('num_of_bytes_written', CAST(s.num_of_bytes_written AS FLOAT)),
+            ('io_stall_write_ms', CAST(s.io_stall_write_ms AS FLOAT))
+        ) counter (name, value)";
+    let mut stream = client
+        .query(statement, &[&database])
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+
+    let mut counters = BTreeMap::new();
+    while let Some(item) = stream
+        .next()
+        .await
+        .transpose()
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?
+    {
+        if let QueryItem::Row(row) = item
+            && let (Some(name), Some(value)) = (row.get::<&str, _>("name"), row.get::<f64, _>("value"))
+        {
+            counters.insert(name.to_string(), value);
+        }
+    }
+
+    Ok(ServerMetrics {
+        backend: Backend::SqlServer,
+        captured_at: chrono::Utc::now().naive_utc(),
+        counters,
+    })
+}
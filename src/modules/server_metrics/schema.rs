@@ -0,0 +1,46 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::modules::dynamic_table::schema::Backend;
+
+#[derive(Debug, Deserialize)]
+pub struct ServerMetricsRequest {
+    // sql server only, the database whose files are read, the conditions target's when not set
+    pub database: Option<String>,
+}
+
+// cumulative server counters, keys are prefixed with the view they come from
+#[derive(Debug, Serialize, Clone)]
+pub struct ServerMetrics {
+    pub backend: Backend,
+    pub captured_at: NaiveDateTime,
+    pub counters: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ServerMetricsDelta {
+    pub backend: Backend,
+    pub elapsed_ms: i64,
+    // after minus before, counters that did not move are left out
+    pub counters: BTreeMap<String, f64>,
+}
+
+impl ServerMetrics {
+    pub fn delta(&self, after: &ServerMetrics) -> ServerMetricsDelta {
+        let counters = after
+            .counters
+            .iter()
+            .filter_map(|(name, value)| {
+                let delta = value - self.counters.get(name).copied().unwrap_or(0.0);
+                (delta != 0.0).then(|| (name.clone(), delta))
+            })
+            .collect();
+        ServerMetricsDelta {
+            backend: self.backend,
+            elapsed_ms: (after.captured_at - self.captured_at).num_milliseconds(),
+            counters,
+        }
+    }
+}
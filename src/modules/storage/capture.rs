@@ -0,0 +1,309 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use axum::{
+    Extension, Router,
+    extract::{Path, Query, Request, State},
+    middleware::{self, Next},
+    response::Response,
+};
+use tokio::time::Instant;
+use tracing::Instrument;
+
+use crate::{
+    config::{database::{self, TableTarget}, environment::CONFIG},
+    dto::app_error::AppError,
+    modules::{
+        conditions_kafka::schema::{ConsumerRequest, Sink},
+        dynamic_table::{self, schema::{Backend, TableDefinition}},
+        provisioning::ddl,
+        server_metrics::{self, schema::ServerMetrics},
+        storage::{
+            repository,
+            schema::{StorageRun, StorageStats, StorageTable},
+        },
+    },
+    state::AppState,
+};
+
+// the table a benchmark request works on, sql server counters come from the target's database
+#[derive(Debug, Clone)]
+pub enum CaptureTarget {
+    Postgres(String),
+    SqlServer(TableTarget),
+}
+
+impl CaptureTarget {
+    pub fn of(backend: Backend, table: StorageTable) -> CaptureTarget {
+        match backend {
+            Backend::Postgres => CaptureTarget::Postgres(ddl::POSTGRES_TABLE.to_string()),
+            Backend::SqlServer => CaptureTarget::SqlServer(sql_server_target(table)),
+        }
+    }
+
+    // dynamic tables are created through the pools, so they live in the pool's database and
+    // the login's default schema
+    pub fn of_dynamic_table(definition: &TableDefinition) -> CaptureTarget {
+        match definition.backend {
+            Backend::Postgres => CaptureTarget::Postgres(definition.quoted_name()),
+            Backend::SqlServer => CaptureTarget::SqlServer(TableTarget {
+                database: database::TIBERIUS_DATABASE.to_string(),
+                schema: "dbo".to_string(),
+                table: definition.name.clone(),
+            }),
+        }
+    }
+
+    pub fn backend(&self) -> Backend {
+        match self {
+            CaptureTarget::Postgres(_) => Backend::Postgres,
+            CaptureTarget::SqlServer(_) => Backend::SqlServer,
+        }
+    }
+}
+
+pub fn sql_server_target(table: StorageTable) -> TableTarget {
+    match table {
+        StorageTable::Conditions => CONFIG.tiberius_conditions_target(),
+        StorageTable::Columns => CONFIG.tiberius_columns_target(),
+    }
+}
+
+pub async fn collect(state: &AppState, backend: Backend, table: StorageTable) -> Result<StorageStats, AppError> {
+    if backend == Backend::Postgres && table != StorageTable::Conditions {
+        return Err(AppError::Other("postgres only has the conditions table".to_string()));
+    }
+    collect_target(state, &CaptureTarget::of(backend, table)).await
+}
+
+pub async fn collect_target(state: &AppState, target: &CaptureTarget) -> Result<StorageStats, AppError> {
+    match target {
+        CaptureTarget::Postgres(table) => {
+            let client = state
+                .pool_pg
+                .get()
+                .await
+                .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+            repository::postgres_stats(&client, table).await
+        }
+        CaptureTarget::SqlServer(target) => {
+            let mut client = state
+                .pool_tiberius
+                .get()
+                .await
+                .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+            repository::sql_server_stats(&mut client, target).await
+        }
+    }
+}
+
+// postgres counters are database wide, sql server waits are server wide and its file io is the
+// database's
+pub async fn collect_server_metrics(
+    state: &AppState,
+    backend: Backend,
+    database: &str,
+) -> Result<ServerMetrics, AppError> {
+    match backend {
+        Backend::Postgres => {
+            let client = state
+                .pool_pg
+                .get()
+                .await
+                .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+            server_metrics::repository::postgres_snapshot(&client).await
+        }
+        Backend::SqlServer => {
+            let mut client = state
+                .pool_tiberius
+                .get()
+                .await
+                .map_err(|error| AppError::Other(format!("get connection failed {:?}", error)))?;
+            server_metrics::repository::sql_server_snapshot(&mut client, database).await
+        }
+    }
+}
+
+// wraps a benchmark router so every request records the storage of its table and the server
+// counters before and after, the run shows up in /storage/runs once the settle time has passed
+pub fn around_benchmarks(router: Router, backend: Backend, table: StorageTable) -> Router {
+    if !capture_enabled() {
+        return router;
+    }
+    router.layer(middleware::from_fn_with_state(CaptureTarget::of(backend, table), capture))
+}
+
+// the same for the dynamic table benchmarks, whose table is the `{name}` of the route, so the
+// layer runs after routing
+pub fn around_dynamic_table_benchmarks(router: Router) -> Router {
+    if !capture_enabled() {
+        return router;
+    }
+    router.route_layer(middleware::from_fn(capture_dynamic_table))
+}
+
+// the kafka benchmarks consume into the `sink` of the query, so its server is the one that
+// does the work, postgres unless the request asks for sql server
+pub fn around_kafka_benchmarks(router: Router) -> Router {
+    if !capture_enabled() {
+        return router;
+    }
+    router.layer(middleware::from_fn(capture_kafka))
+}
+
+fn capture_enabled() -> bool {
+    CONFIG.storage_capture_enabled || CONFIG.server_metrics_capture_enabled
+}
+
+async fn capture(
+    State(target): State<CaptureTarget>,
+    Extension(state): Extension<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    capture_target(state, target, request, next).await
+}
+
+async fn capture_dynamic_table(
+    Path(params): Path<HashMap<String, String>>,
+    Extension(state): Extension<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let definition = match params.get("name") {
+        Some(name) => dynamic_table::controller::get_definition(&state, name).await.ok(),
+        None => None,
+    };
+    // an unknown table is answered by the handler, there is nothing to capture
+    match definition {
+        Some(definition) => capture_target(state, CaptureTarget::of_dynamic_table(&definition), request, next).await,
+        None => next.run(request).await,
+    }
+}
+
+async fn capture_kafka(Extension(state): Extension<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let sink = Query::<ConsumerRequest>::try_from_uri(request.uri())
+        .ok()
+        .and_then(|Query(consumer_request)| consumer_request.sink)
+        .unwrap_or_default();
+    let backend = match sink {
+        Sink::Postgres => Backend::Postgres,
+        Sink::SqlServer => Backend::SqlServer,
+    };
+    capture_target(state, CaptureTarget::of(backend, StorageTable::Conditions), request, next).await
+}
+
+async fn capture_target(state: Arc<AppState>, target: CaptureTarget, request: Request, next: Next) -> Response {
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let mut errors = Vec::new();
+
+    let mut before = None;
+    if CONFIG.storage_capture_enabled {
+        before = collect_target(&state, &target)
+            .await
+            .map_err(|error| errors.push(format!("before: {:?}", error)))
+            .ok();
+    }
+    // taken last so the storage queries are not part of the delta
+    let mut metrics_before = None;
+    if CONFIG.server_metrics_capture_enabled {
+        metrics_before = collect_server_metrics_of(&state, &target)
+            .await
+            .map_err(|error| errors.push(format!("server metrics before: {:?}", error)))
+            .ok();
+    }
+
+    let started_at = chrono::Utc::now().naive_utc();
+    let start = Instant::now();
+    let response = next.run(request).await;
+    let elapsed_ms = start.elapsed().as_millis();
+    let status = response.status().as_u16();
+
+    // the after snapshots wait for the statistics to settle, the response does not
+    tokio::spawn(
+        async move {
+            // postgres backends flush their table and database statistics at most once a second
+            // while busy, an idle pooled connection only after 10 seconds
+            if target.backend() == Backend::Postgres {
+                tokio::time::sleep(Duration::from_millis(CONFIG.storage_capture_settle_ms)).await;
+            }
+
+            let run = StorageRun {
+                method,
+                path,
+                status,
+                started_at,
+                elapsed_ms,
+                before,
+                after: None,
+                rows_delta: None,
+                bytes_delta: None,
+                server_metrics: None,
+                errors,
+            };
+            store(&state, finish(&state, &target, run, metrics_before).await).await;
+        }
+        .in_current_span(),
+    );
+
+    response
+}
+
+async fn collect_server_metrics_of(state: &AppState, target: &CaptureTarget) -> Result<ServerMetrics, AppError> {
+    match target {
+        CaptureTarget::Postgres(_) => collect_server_metrics(state, Backend::Postgres, "").await,
+        CaptureTarget::SqlServer(target) => {
+            collect_server_metrics(state, Backend::SqlServer, &target.database).await
+        }
+    }
+}
+
+// takes the after snapshots and fills the deltas of `run`
+async fn finish(
+    state: &AppState,
+    target: &CaptureTarget,
+    mut run: StorageRun,
+    metrics_before: Option<ServerMetrics>,
+) -> StorageRun {
+    let errors = &mut run.errors;
+    let mut server_metrics = None;
+    if let Some(metrics_before) = metrics_before {
+        server_metrics = collect_server_metrics_of(state, target)
+            .await
+            .map(|metrics_after| metrics_before.delta(&metrics_after))
+            .map_err(|error| errors.push(format!("server metrics after: {:?}", error)))
+            .ok();
+    }
+    let mut after = None;
+    if CONFIG.storage_capture_enabled {
+        after = collect_target(state, target)
+            .await
+            .map_err(|error| errors.push(format!("after: {:?}", error)))
+            .ok();
+    }
+
+    let (rows_delta, bytes_delta) = match (&run.before, &after) {
+        (Some(before), Some(after)) => (
+            Some(after.rows - before.rows),
+            Some(after.total_bytes - before.total_bytes),
+        ),
+        _ => (None, None),
+    };
+    tracing::info!(backend = ?target.backend(), rows_delta, bytes_delta, "storage captured");
+
+    StorageRun {
+        after,
+        rows_delta,
+        bytes_delta,
+        server_metrics,
+        ..run
+    }
+}
+
+async fn store(state: &AppState, run: StorageRun) {
+    let mut runs = state.storage_runs.write().await;
+    runs.push_back(run);
+    while runs.len() > CONFIG.storage_capture_history {
+        runs.pop_front();
+    }
+}
@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, Query},
+    http::StatusCode,
+    routing::get,
+};
+
+use crate::{
+    dto::{app_error::AppError, app_response::AppResponse},
+    modules::{
+        dynamic_table::schema::Backend,
+        storage::{
+            capture,
+            schema::{StorageRequest, StorageRun, StorageStats},
+        },
+    },
+    state::AppState,
+};
+
+pub fn new() -> Router {
+    Router::new()
+        .route("/runs", get(find_runs).delete(delete_runs))
+        .route("/{backend}", get(find_stats))
+}
+
+// table and index sizes, row counts per partition and dead tuples or fragmentation
+pub async fn find_stats(
+    Path(backend): Path<Backend>,
+    Query(storage_request): Query<StorageRequest>,
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, Json<AppResponse<StorageStats>>), AppError> {
+    let _result = capture::collect(&_state, backend, storage_request.table.unwrap_or_default()).await?;
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", Some(_result)))))
+}
+
+// storage captured around the latest benchmark requests, oldest first
+pub async fn find_runs(
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, Json<AppResponse<Vec<StorageRun>>>), AppError> {
+    let _result: Vec<StorageRun> = _state.storage_runs.read().await.iter().cloned().collect();
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", Some(_result)))))
+}
+
+pub async fn delete_runs(
+    Extension(_state): Extension<Arc<AppState>>,
+) -> Result<(StatusCode, Json<AppResponse<Vec<StorageRun>>>), AppError> {
+    _state.storage_runs.write().await.clear();
+
+    let status_code = StatusCode::OK;
+    Ok((status_code, Json(AppResponse::ok("success", None))))
+}
@@ -0,0 +1,4 @@
+pub mod schema;
+pub mod repository;
+pub mod capture;
+pub mod controller;
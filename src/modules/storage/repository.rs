@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+
+use crate::{
+    config::database::TableTarget,
+    dto::app_error::AppError,
+    modules::{
+        dynamic_table::schema::Backend,
+        storage::schema::{IndexStats, PartitionStats, StorageStats},
+    },
+};
+
+// postgres
+
+// the table and everything inheriting from it, which covers declarative partitions as well as
+// timescaledb chunks
+const POSTGRES_TREE: &str = "WITH RECURSIVE tree AS (
+    SELECT $1::text::regclass::oid AS relid
+    UNION ALL
+    SELECT i.inhrelid FROM pg_inherits i JOIN tree ON i.inhparent = tree.relid
+)";
+
+pub async fn postgres_stats(client: &tokio_postgres::Client, table: &str) -> Result<StorageStats, AppError> {
+    let statement = format!(
+        "{}
+        SELECT c.oid = $1::text::regclass::oid AS is_root,
+            c.oid::regclass::text AS name,
+            coalesce(s.n_live_tup, 0) AS rows,
+            coalesce(s.n_dead_tup, 0) AS dead_rows,
+            pg_table_size(c.oid) AS table_bytes,
+            pg_indexes_size(c.oid) AS index_bytes
+        FROM tree
+        JOIN pg_class c ON c.oid = tree.relid
+        LEFT JOIN pg_stat_user_tables s ON s.relid = c.oid
+        WHERE c.relkind = 'r'
+        ORDER BY name",
+        POSTGRES_TREE
+    );
+    let rows = client
+        .query(&statement, &[&table])
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+
+    let mut stats = StorageStats {
+        backend: Backend::Postgres,
+        table: table.to_string(),
+        captured_at: chrono::Utc::now().naive_utc(),
+        rows: 0,
+        dead_rows: Some(0),
+        table_bytes: 0,
+        index_bytes: 0,
+        total_bytes: 0,
+        indexes: Vec::new(),
+        partitions: Vec::new(),
+    };
+    for row in &rows {
+        let live: i64 = row.get("rows");
+        let dead: i64 = row.get("dead_rows");
+        let table_bytes: i64 = row.get("table_bytes");
+        let index_bytes: i64 = row.get("index_bytes");
+        stats.rows += live;
+        stats.dead_rows = stats.dead_rows.map(|dead_rows| dead_rows + dead);
+        stats.table_bytes += table_bytes;
+        stats.index_bytes += index_bytes;
+
+        let is_root: bool = row.get("is_root");
+        if !is_root {
+            stats.partitions.push(PartitionStats {
+                name: row.get("name"),
+                rows: live,
+                dead_rows: Some(dead),
+                bytes: table_bytes + index_bytes,
+            });
+        }
+    }
+    stats.total_bytes = stats.table_bytes + stats.index_bytes;
+
+    // a partitioned index is empty itself, its size is the sum of the partitions' indexes
+    let statement = "SELECT x.indexrelid::regclass::text AS name,
+            (SELECT coalesce(sum(pg_relation_size(t.relid)), 0)::bigint
+                FROM pg_partition_tree(x.indexrelid) t) AS bytes
+        FROM pg_index x
+        WHERE x.indrelid = $1::text::regclass
+        ORDER BY name";
+    let rows = client
+        .query(statement, &[&table])
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+    stats.indexes = rows
+        .iter()
+        .map(|row| IndexStats {
+            name: row.get("name"),
+            bytes: row.get("bytes"),
+            fragmentation_percent: None,
+        })
+        .collect();
+
+    Ok(stats)
+}
+
+// sql server, sys.dm_db_partition_stats of the target's database
+
+pub async fn sql_server_stats(
+    client: &mut tiberius::Client<tokio_util::compat::Compat<tokio::net::TcpStream>>,
+    target: &TableTarget,
+) -> Result<StorageStats, AppError> {
+    let qualified = target.qualified();
+    let database = target.quoted_database();
+
+    // index 0 is the heap and 1 the clustered index, both hold the rows
+    let statement = format!(
+        "SELECT s.index_id, s.partition_number, coalesce(i.name, 'heap') AS name,
+            s.row_count AS rows, s.used_page_count * 8192 AS bytes
+        FROM {0}.sys.dm_db_partition_stats s
+        JOIN {0}.sys.indexes i ON i.object_id = s.object_id AND i.index_id = s.index_id
+        WHERE s.object_id = OBJECT_ID(@P1)
+        ORDER BY s.index_id, s.partition_number",
+        database
+    );
+    let rows = client
+        .query(statement, &[&qualified])
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?
+        .into_first_result()
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?;
+    if rows.is_empty() {
+        return Err(AppError::Other(format!("table {} does not exist", qualified)));
+    }
+
+    // LIMITED only reads the pages above the leaf level, cheap enough to run around every benchmark
+    let statement = "SELECT index_id, AVG(avg_fragmentation_in_percent) AS fragmentation
+        FROM sys.dm_db_index_physical_stats(DB_ID(@P2), OBJECT_ID(@P1), NULL, NULL, 'LIMITED')
+        WHERE alloc_unit_type_desc = 'IN_ROW_DATA'
+        GROUP BY index_id";
+    let fragmentation: HashMap<i32, f64> = client
+        .query(statement, &[&qualified, &target.database])
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?
+        .into_first_result()
+        .await
+        .map_err(|error| AppError::Other(format!("query failed: {}", error)))?
+        .iter()
+        .filter_map(|row| Some((row.get::<i32, _>("index_id")?, row.get::<f64, _>("fragmentation")?)))
+        .collect();
+
+    let mut stats = StorageStats {
+        backend: Backend::SqlServer,
+        table: qualified,
+        captured_at: chrono::Utc::now().naive_utc(),
+        rows: 0,
+        dead_rows: None,
+        table_bytes: 0,
+        index_bytes: 0,
+        total_bytes: 0,
+        indexes: Vec::new(),
+        partitions: Vec::new(),
+    };
+    let mut partitions = Vec::new();
+    for row in &rows {
+        let index_id: i32 = row.get("index_id").unwrap_or(0);
+        let partition_number: i32 = row.get("partition_number").unwrap_or(1);
+        let name: &str = row.get("name").unwrap_or("heap");
+        let row_count: i64 = row.get("rows").unwrap_or(0);
+        let bytes: i64 = row.get("bytes").unwrap_or(0);
+
+        if index_id <= 1 {
+            stats.rows += row_count;
+            stats.table_bytes += bytes;
+            partitions.push(PartitionStats {
+                name: format!("partition {}", partition_number),
+                rows: row_count,
+                dead_rows: None,
+                bytes,
+            });
+        } else {
+            stats.index_bytes += bytes;
+        }
+
+        match stats.indexes.last_mut() {
+            Some(index) if index.name == name => index.bytes += bytes,
+            _ => stats.indexes.push(IndexStats {
+                name: name.to_string(),
+                bytes,
+                fragmentation_percent: fragmentation.get(&index_id).copied(),
+            }),
+        }
+    }
+    stats.total_bytes = stats.table_bytes + stats.index_bytes;
+    if partitions.len() > 1 {
+        stats.partitions = partitions;
+    }
+
+    Ok(stats)
+}
@@ -0,0 +1,70 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::modules::{dynamic_table::schema::Backend, server_metrics::schema::ServerMetricsDelta};
+
+// which sql server target to inspect, postgres only has the conditions table
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageTable {
+    #[default]
+    Conditions,
+    Columns,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StorageRequest {
+    pub table: Option<StorageTable>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct StorageStats {
+    pub backend: Backend,
+    pub table: String,
+    pub captured_at: NaiveDateTime,
+    // n_live_tup on postgres, an estimate until the table is analyzed
+    pub rows: i64,
+    // postgres only, vacuum has not reclaimed these yet
+    pub dead_rows: Option<i64>,
+    // heap or clustered index
+    pub table_bytes: i64,
+    pub index_bytes: i64,
+    pub total_bytes: i64,
+    pub indexes: Vec<IndexStats>,
+    // empty unless the table is partitioned
+    pub partitions: Vec<PartitionStats>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct IndexStats {
+    pub name: String,
+    pub bytes: i64,
+    // sql server only, average over the partitions
+    pub fragmentation_percent: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PartitionStats {
+    pub name: String,
+    pub rows: i64,
+    pub dead_rows: Option<i64>,
+    pub bytes: i64,
+}
+
+// one benchmark request with the storage of its table before and after it ran and what the server
+// counted while it did
+#[derive(Debug, Serialize, Clone)]
+pub struct StorageRun {
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    pub started_at: NaiveDateTime,
+    pub elapsed_ms: u128,
+    pub before: Option<StorageStats>,
+    pub after: Option<StorageStats>,
+    pub rows_delta: Option<i64>,
+    pub bytes_delta: Option<i64>,
+    pub server_metrics: Option<ServerMetricsDelta>,
+    // a failed capture never fails the benchmark
+    pub errors: Vec<String>,
+}
@@ -1,13 +1,30 @@
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
 
 use diesel::{r2d2, PgConnection};
-use tokio::{net::TcpStream, sync::Mutex};
-use tokio_util::compat::Compat;
+use tokio::sync::{Mutex, RwLock};
+
+use crate::modules::{
+    conditions_kafka::{
+        bus::{BusConsumer, MessageBus},
+        worker::IngestWorker,
+    },
+    dynamic_table::schema::TableDefinition,
+    storage::schema::StorageRun,
+};
 
 pub struct AppState {
     pub diesel_pool_pg: Arc<r2d2::Pool<r2d2::ConnectionManager<PgConnection>>>,
     pub pool_pg: deadpool_postgres::Pool,
     pub tokio_postgres_client: Mutex<tokio_postgres::Client>,
     pub pool_tiberius: deadpool_tiberius::Pool,
+    pub message_bus: MessageBus,
+    pub bus_consumer: Mutex<BusConsumer>,
+    pub ingest_worker: IngestWorker,
+    // None while the table's CREATE or DROP is running
+    pub dynamic_tables: RwLock<HashMap<String, Option<TableDefinition>>>,
+    pub storage_runs: RwLock<VecDeque<StorageRun>>,
     pub status: String
 }
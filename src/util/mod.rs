@@ -1,2 +1,6 @@
 pub mod serializer;
-pub mod generator;
\ No newline at end of file
+pub mod generator;
+pub mod statistics;
+pub mod parallel_writer;
+pub mod resource_usage;
+pub mod request_id;
\ No newline at end of file
@@ -0,0 +1,189 @@
+use std::{
+    future::Future,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use tokio::{
+    sync::{Mutex, mpsc},
+    task::JoinHandle,
+};
+
+use crate::dto::app_error::AppError;
+
+// a fixed number of writer tasks fed through a bounded channel, the generator waits in `send`
+// whenever every writer is busy and the queue is full, so memory stays bounded by the queue
+pub struct ParallelWriter<T> {
+    sender: mpsc::Sender<Vec<T>>,
+    workers: Vec<JoinHandle<Result<WriterCounts, AppError>>>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct WriterCounts {
+    pub batches: usize,
+    pub rows: usize,
+}
+
+impl<T: Send + 'static> ParallelWriter<T> {
+    // `queue` is the number of batches that may wait for a writer
+    pub fn spawn<F, Fut>(workers: usize, queue: usize, write: F) -> Self
+    where
+        F: Fn(Vec<T>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), AppError>> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel::<Vec<T>>(queue.max(1));
+        let receiver = Arc::new(Mutex::new(receiver));
+        let write = Arc::new(write);
+        let failed = Arc::new(AtomicBool::new(false));
+
+        let workers = (0..workers.max(1))
+            .map(|_| {
+                let receiver = receiver.clone();
+                let write = write.clone();
+                let failed = failed.clone();
+                tokio::spawn(async move {
+                    let mut counts = WriterCounts::default();
+                    loop {
+                        // the lock is only held while waiting for the next batch
+                        let batch = receiver.lock().await.recv().await;
+                        let Some(batch) = batch else { break };
+                        // batches still queued when another writer failed are dropped
+                        if failed.load(Ordering::Relaxed) {
+                            break;
+                        }
+
+                        let rows = batch.len();
+                        if let Err(error) = write(batch).await {
+                            // closing the channel makes the generator's next send fail fast
+                            failed.store(true, Ordering::Relaxed);
+                            receiver.lock().await.close();
+                            return Err(error);
+                        }
+                        counts.batches += 1;
+                        counts.rows += rows;
+                    }
+                    Ok(counts)
+                })
+            })
+            .collect();
+
+        ParallelWriter { sender, workers }
+    }
+
+    // fails once a writer has failed, `finish` returns that writer's error
+    pub async fn send(&self, batch: Vec<T>) -> Result<(), AppError> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        self.sender
+            .send(batch)
+            .await
+            .map_err(|_| AppError::Other("parallel writer stopped after a failed batch".to_string()))
+    }
+
+    // waits for every queued batch to be written, the first writer error wins
+    pub async fn finish(self) -> Result<WriterCounts, AppError> {
+        drop(self.sender);
+
+        let mut total = WriterCounts::default();
+        let mut first_error = None;
+        for worker in self.workers {
+            match worker.await {
+                Ok(Ok(counts)) => {
+                    total.batches += counts.batches;
+                    total.rows += counts.rows;
+                }
+                Ok(Err(error)) => {
+                    first_error.get_or_insert(error);
+                }
+                Err(error) => {
+                    first_error.get_or_insert(AppError::Other(format!("writer task failed: {}", error)));
+                }
+            }
+        }
+
+        match first_error {
+            Some(error) => Err(error),
+            None => Ok(total),
+        }
+    }
+
+    // joins the writers whether or not generating the batches succeeded, a writer error is
+    // returned before the generator's, which after a failed batch is only the failed send
+    pub async fn finish_after(self, generated: Result<(), AppError>) -> Result<WriterCounts, AppError> {
+        let counts = self.finish().await?;
+        generated?;
+        Ok(counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+
+    // writes every batch except one that contains `fail_on`, counting the rows written
+    fn writer(workers: usize, queue: usize, fail_on: Option<i32>) -> (ParallelWriter<i32>, Arc<AtomicUsize>) {
+        let written = Arc::new(AtomicUsize::new(0));
+        let counter = written.clone();
+        let writer = ParallelWriter::spawn(workers, queue, move |batch: Vec<i32>| {
+            let counter = counter.clone();
+            async move {
+                if fail_on.is_some_and(|value| batch.contains(&value)) {
+                    return Err(AppError::Other(format!("write failed on {:?}", batch)));
+                }
+                counter.fetch_add(batch.len(), Ordering::Relaxed);
+                Ok(())
+            }
+        });
+        (writer, written)
+    }
+
+    #[tokio::test]
+    async fn finish_joins_every_worker() {
+        let (writer, written) = writer(3, 2, None);
+        for batch in 0..10 {
+            writer.send(vec![batch, batch]).await.unwrap();
+        }
+        writer.send(Vec::new()).await.unwrap();
+
+        let counts = writer.finish().await.unwrap();
+        assert_eq!(counts, WriterCounts { batches: 10, rows: 20 });
+        assert_eq!(written.load(Ordering::Relaxed), 20);
+    }
+
+    #[tokio::test]
+    async fn finish_returns_the_worker_error() {
+        let (writer, _) = writer(2, 4, Some(3));
+        for batch in 0..5 {
+            // sends after the failure may already be refused
+            let _ = writer.send(vec![batch]).await;
+        }
+
+        let result = writer.finish().await;
+        assert_eq!(result, Err(AppError::Other("write failed on [3]".to_string())));
+    }
+
+    #[tokio::test]
+    async fn send_fails_fast_after_a_worker_error() {
+        let (writer, written) = writer(1, 1, Some(0));
+        writer.send(vec![0]).await.unwrap();
+
+        // the channel is closed by the failed worker, so a send can not wait on a full queue forever
+        let mut refused = false;
+        for batch in 1..100 {
+            if writer.send(vec![batch]).await.is_err() {
+                refused = true;
+                break;
+            }
+        }
+        assert!(refused);
+
+        let result = writer.finish_after(Err(AppError::Other("generator stopped".to_string()))).await;
+        assert_eq!(result, Err(AppError::Other("write failed on [0]".to_string())));
+        assert_eq!(written.load(Ordering::Relaxed), 0);
+    }
+}
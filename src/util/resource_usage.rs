@@ -0,0 +1,127 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicU64, Ordering},
+    time::Instant,
+};
+
+use serde::Serialize;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+static HEAP_BYTES: AtomicU64 = AtomicU64::new(0);
+static PEAK_HEAP_BYTES: AtomicU64 = AtomicU64::new(0);
+
+// the system allocator plus a few relaxed counters, registered as the global allocator in main
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let pointer = unsafe { System.alloc(layout) };
+        if !pointer.is_null() {
+            record_alloc(layout.size());
+        }
+        pointer
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let pointer = unsafe { System.alloc_zeroed(layout) };
+        if !pointer.is_null() {
+            record_alloc(layout.size());
+        }
+        pointer
+    }
+
+    unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(pointer, layout) };
+        HEAP_BYTES.fetch_sub(layout.size() as u64, Ordering::Relaxed);
+    }
+
+    // a realloc counts as one allocation of the new size
+    unsafe fn realloc(&self, pointer: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_pointer = unsafe { System.realloc(pointer, layout, new_size) };
+        if !new_pointer.is_null() {
+            HEAP_BYTES.fetch_sub(layout.size() as u64, Ordering::Relaxed);
+            record_alloc(new_size);
+        }
+        new_pointer
+    }
+}
+
+fn record_alloc(size: usize) {
+    let size = size as u64;
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED_BYTES.fetch_add(size, Ordering::Relaxed);
+    let heap = HEAP_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK_HEAP_BYTES.fetch_max(heap, Ordering::Relaxed);
+}
+
+// client side cost of one benchmark iteration, the counters are process wide so anything else
+// running at the same time is included
+#[derive(Debug, Serialize, Clone)]
+pub struct ResourceUsage {
+    pub elapsed_ms: f64,
+    pub user_cpu_ms: f64,
+    pub system_cpu_ms: f64,
+    // VmHWM, reset at the start of the iteration when the kernel allows it
+    pub peak_rss_bytes: Option<u64>,
+    // all zero unless CountingAllocator is the global allocator
+    pub allocations: u64,
+    pub allocated_bytes: u64,
+    // highest live heap during the iteration above what was live when it started
+    pub peak_heap_bytes: u64,
+}
+
+pub struct ResourceMeter {
+    start: Instant,
+    cpu: (f64, f64),
+    allocations: u64,
+    allocated_bytes: u64,
+    heap_bytes: u64,
+}
+
+impl ResourceMeter {
+    pub fn start() -> Self {
+        let heap_bytes = HEAP_BYTES.load(Ordering::Relaxed);
+        PEAK_HEAP_BYTES.store(heap_bytes, Ordering::Relaxed);
+        // "5" resets the peak resident set size to the current one, linux 4.0 and later
+        let _ = std::fs::write("/proc/self/clear_refs", "5");
+
+        ResourceMeter {
+            start: Instant::now(),
+            cpu: cpu_time_ms(),
+            allocations: ALLOCATIONS.load(Ordering::Relaxed),
+            allocated_bytes: ALLOCATED_BYTES.load(Ordering::Relaxed),
+            heap_bytes,
+        }
+    }
+
+    pub fn finish(self) -> ResourceUsage {
+        let (user, system) = cpu_time_ms();
+        ResourceUsage {
+            elapsed_ms: self.start.elapsed().as_secs_f64() * 1000.0,
+            user_cpu_ms: user - self.cpu.0,
+            system_cpu_ms: system - self.cpu.1,
+            peak_rss_bytes: peak_rss_bytes(),
+            allocations: ALLOCATIONS.load(Ordering::Relaxed) - self.allocations,
+            allocated_bytes: ALLOCATED_BYTES.load(Ordering::Relaxed) - self.allocated_bytes,
+            peak_heap_bytes: PEAK_HEAP_BYTES.load(Ordering::Relaxed).saturating_sub(self.heap_bytes),
+        }
+    }
+}
+
+// user and system time of the whole process
+fn cpu_time_ms() -> (f64, f64) {
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) } != 0 {
+        return (0.0, 0.0);
+    }
+    let millis = |time: libc::timeval| time.tv_sec as f64 * 1000.0 + time.tv_usec as f64 / 1000.0;
+    (millis(usage.ru_utime), millis(usage.ru_stime))
+}
+
+fn peak_rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kilobytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kilobytes * 1024)
+}
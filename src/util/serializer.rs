@@ -47,4 +47,16 @@ pub mod date_serializer {
         let time: String = Deserialize::deserialize(deserializer)?;
         Ok(NaiveDate::parse_from_str(&time, "%Y-%m-%d").map_err(D::Error::custom)?)
     }
-}
\ No newline at end of file
+}
+
+// patch fields, a missing key leaves the column as is and null sets it back to NULL,
+// use with #[serde(default, deserialize_with = "double_option::deserialize")]
+pub mod double_option {
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Option<T>>, D::Error> {
+        Option::<T>::deserialize(deserializer).map(Some)
+    }
+}
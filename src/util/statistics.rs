@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use serde::Serialize;
+
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct LatencySummary {
+    pub count: usize,
+    pub min_ms: f64,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+impl LatencySummary {
+    pub fn from_durations(durations: &[Duration]) -> Self {
+        if durations.is_empty() {
+            return LatencySummary::default();
+        }
+
+        let mut millis: Vec<f64> = durations.iter().map(|d| d.as_secs_f64() * 1000.0).collect();
+        millis.sort_by(|a, b| a.total_cmp(b));
+
+        LatencySummary {
+            count: millis.len(),
+            min_ms: millis[0],
+            mean_ms: millis.iter().sum::<f64>() / millis.len() as f64,
+            p50_ms: percentile(&millis, 50.0),
+            p90_ms: percentile(&millis, 90.0),
+            p99_ms: percentile(&millis, 99.0),
+            max_ms: millis[millis.len() - 1],
+        }
+    }
+}
+
+// nearest-rank percentile, values must be sorted ascending
+pub fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((percent / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+pub fn per_second(count: u64, elapsed: Duration) -> f64 {
+    let seconds = elapsed.as_secs_f64();
+    if seconds == 0.0 {
+        return 0.0;
+    }
+    count as f64 / seconds
+}